@@ -19,7 +19,11 @@ class RustCore {
 
     private external fun start(pattern: String)
 
+    private external fun stop()
+
     fun daemon(path: String) = start(path)
+
+    fun shutdown() = stop()
 }
 
 /** EsseCorePlugin */
@@ -48,6 +52,9 @@ class EsseCorePlugin: FlutterPlugin, MethodCallHandler {
           print("Empty string")
       }
       result.success("Daemon success.")
+    } else if (call.method == "stop") {
+      RustCore().shutdown()
+      result.success("Stop success.")
     } else {
       result.notImplemented()
     }
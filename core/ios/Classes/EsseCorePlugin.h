@@ -2,5 +2,7 @@
 
 void start(const char *db_path);
 
+void stop(void);
+
 @interface EsseCorePlugin : NSObject<FlutterPlugin>
 @end
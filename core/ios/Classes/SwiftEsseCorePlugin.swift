@@ -32,6 +32,9 @@ public class SwiftEsseCorePlugin: NSObject, FlutterPlugin {
       } else {
         result("Daemon path invalid")
       }
+    case "stop":
+      stop()
+      result("Stop success")
     default:
       result(FlutterMethodNotImplemented)
     }
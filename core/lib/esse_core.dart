@@ -15,4 +15,9 @@ class EsseCore {
         await _channel.invokeMethod('daemon', {'path': path});
     print("over daemon: " + version);
   }
+
+  /// stop the running daemon, it will logout all accounts.
+  static Future<void> stop() async {
+    await _channel.invokeMethod('stop');
+  }
 }
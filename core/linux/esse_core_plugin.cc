@@ -61,6 +61,11 @@ static void esse_core_plugin_handle_method_call(
 
     g_autoptr(FlValue) result = fl_value_new_string(path);
     response = FL_METHOD_RESPONSE(fl_method_success_response_new(result));
+  } else if (strcmp(method, "stop") == 0) {
+    stop();
+
+    g_autoptr(FlValue) result = fl_value_new_string("Stop success");
+    response = FL_METHOD_RESPONSE(fl_method_success_response_new(result));
   } else {
     response = FL_METHOD_RESPONSE(fl_method_not_implemented_response_new());
   }
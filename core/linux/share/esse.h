@@ -1 +1,3 @@
-extern "C" void start(const char *db_path);
+void start(const char *db_path);
+
+void stop(void);
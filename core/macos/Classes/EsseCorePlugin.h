@@ -1 +1,3 @@
-void start(const char *db_path);
\ No newline at end of file
+void start(const char *db_path);
+
+void stop(void);
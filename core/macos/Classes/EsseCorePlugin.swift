@@ -26,6 +26,9 @@ public class EsseCorePlugin: NSObject, FlutterPlugin {
       } else {
         result("Daemon path invalid")
       }
+    case "stop":
+      stop()
+      result("Stop success")
     default:
       result(FlutterMethodNotImplemented)
     }
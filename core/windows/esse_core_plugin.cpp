@@ -93,6 +93,9 @@ void EsseCorePlugin::HandleMethodCall(
     auto thread1 = std::thread(StartDaemon, path);
     thread1.detach();
     result->Success(flutter::EncodableValue("Daemon success"));
+  } else if (method_call.method_name().compare("stop") == 0) {
+    stop();
+    result->Success(flutter::EncodableValue("Stop success"));
   } else {
     result->NotImplemented();
   }
@@ -1 +1,4 @@
 extern "C" void start(const char *db_path);
+
+extern "C" void stop();
+
@@ -8,6 +8,7 @@ class Global {
   //static String httpRpc = '192.168.50.250:8001'; // test code
   //static String wsRpc = '192.168.50.250:8081';   // test code
   static String optionCache = 'option';
+  static String rpcToken = '';
   static String addr = '0x';
 
   static String home = '.tdn';
@@ -18,12 +18,24 @@ void coreServer() async {
   final path = await homeDir();
   print("home path: " + path);
   Global.home = path;
+  Global.rpcToken = await rpcToken(path);
 
   final res = await httpPost('echo', []);
   if (res.isOk) {
     print('Had running');
   } else {
     EsseCore.daemon(path);
+    WidgetsBinding.instance!.addObserver(CoreObserver());
+  }
+}
+
+/// stop the daemon started by the app when the app is detached.
+class CoreObserver extends WidgetsBindingObserver {
+  @override
+  void didChangeAppLifecycleState(AppLifecycleState state) {
+    if (state == AppLifecycleState.detached) {
+      EsseCore.stop();
+    }
   }
 }
 
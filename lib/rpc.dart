@@ -5,6 +5,7 @@ import 'package:web_socket_channel/web_socket_channel.dart';
 import 'package:http/http.dart' as http;
 
 import 'package:esse/utils/websocket/MyWsChannel.dart';
+import 'package:esse/utils/home_dir.dart';
 import 'package:esse/global.dart';
 
 Map jsonrpc = {
@@ -23,9 +24,18 @@ class Response {
   const Response({required this.isOk, required this.params, required this.error});
 }
 
+// the token maybe created after UI started, when the daemon first running.
+Future<String> loadToken() async {
+  if (Global.rpcToken.isEmpty) {
+    Global.rpcToken = await rpcToken(Global.home);
+  }
+  return Global.rpcToken;
+}
+
 Future<Response> httpPost(String method, List params) async {
   jsonrpc['method'] = method;
   jsonrpc['params'] = params;
+  jsonrpc['token'] = await loadToken();
   //print(json.encode(jsonrpc));
 
   try {
@@ -59,6 +69,9 @@ class WebSocketsNotifications {
 
   bool _closed = true;
 
+  // keep alive, the daemon removes the idle client after 120s.
+  Timer? _ping;
+
   Map<String, List> _listeners = new Map<String, List>();
   Function? _notice;
 
@@ -85,7 +98,18 @@ class WebSocketsNotifications {
             } catch (_) {}
             print("WebSocket done… " + closeReason);
             _closed = true;
+            _ping?.cancel();
         });
+        _ping = Timer.periodic(Duration(seconds: 30), (_) => send('rpc-ping', []));
+
+        // handshake first, the daemon handles the requests in order.
+        final token = await loadToken();
+        _channel!.sink.add(json.encode({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "rpc-auth",
+            "params": [token],
+        }));
         return true;
       } catch (e) {
         print("DEBUG Flutter: got websockt error.........retry ${i}s");
@@ -102,6 +126,7 @@ class WebSocketsNotifications {
   }
 
   reset() {
+    _ping?.cancel();
     if (_channel != null) {
       _channel!.sink.close();
     }
@@ -1,4 +1,4 @@
-import 'dart:io' show Directory;
+import 'dart:io' show Directory, File;
 
 import 'package:path_provider/path_provider.dart';
 
@@ -12,3 +12,12 @@ Future<String> homeDir() async {
   }
   return myDir.path;
 }
+
+// the daemon's admin token, created in home when daemon started.
+Future<String> rpcToken(String home) async {
+  final file = new File(home + '/rpc.token');
+  if (await file.exists()) {
+    return (await file.readAsString()).trim();
+  }
+  return '';
+}
@@ -0,0 +1,111 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+
+use crate::events::Event;
+use crate::methods::{Method, RpcSubscribe};
+use crate::models::FromRpc;
+
+/// Client of the daemon's unix socket RPC, default is `<data>/esse.sock`,
+/// or `rpc_unix` in config.toml.
+pub struct Client {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    id: u64,
+    /// current account, all methods are called with it.
+    pub gid: String,
+    /// the events received when waiting response.
+    events: VecDeque<Value>,
+}
+
+impl Client {
+    pub async fn connect(path: impl AsRef<Path>, gid: impl Into<String>) -> Result<Client> {
+        let path = path.as_ref();
+        let stream = UnixStream::connect(path)
+            .await
+            .map_err(|e| anyhow!("connect daemon {:?} failure: {}", path, e))?;
+        let (reader, writer) = stream.into_split();
+        Ok(Client {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            id: 0,
+            gid: gid.into(),
+            events: VecDeque::new(),
+        })
+    }
+
+    /// call the typed method and waiting the response.
+    pub async fn call<M: Method>(&mut self, method: &M) -> Result<M::Response> {
+        let result = self.call_raw(M::NAME, method.params()).await?;
+        M::Response::from_rpc(&result)
+    }
+
+    /// call the method with raw params.
+    pub async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.id += 1;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.id,
+            "gid": self.gid,
+            "method": method,
+            "params": params,
+        });
+        self.writer
+            .write_all(format!("{}\n", request).as_bytes())
+            .await?;
+
+        loop {
+            let response = self.next_raw().await?;
+            if response["id"].as_u64() == Some(0) {
+                self.events.push_back(response);
+                continue;
+            }
+            if response["id"].as_u64() != Some(self.id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                let msg = error["message"].as_str().unwrap_or("");
+                return Err(anyhow!("{} failure: {}", method, msg));
+            }
+            return Ok(response["result"].clone());
+        }
+    }
+
+    /// subscribe the events of the accounts and topics (method prefix), empty is all.
+    pub async fn subscribe(&mut self, gids: Vec<String>, topics: Vec<String>) -> Result<()> {
+        self.call(&RpcSubscribe { gids, topics }).await?;
+        Ok(())
+    }
+
+    /// next pushed event, and the account's gid of it.
+    pub async fn next_event(&mut self) -> Result<(String, Event)> {
+        let param = self.next_event_raw().await?;
+        Event::from_rpc(&param)
+    }
+
+    /// next pushed event, the raw notification: {gid, method, result}.
+    pub async fn next_event_raw(&mut self) -> Result<Value> {
+        loop {
+            let param = match self.events.pop_front() {
+                Some(param) => param,
+                None => self.next_raw().await?,
+            };
+            // responses of other requests.
+            if param["id"].as_u64() != Some(0) {
+                continue;
+            }
+            return Ok(param);
+        }
+    }
+
+    async fn next_raw(&mut self) -> Result<Value> {
+        match self.lines.next_line().await? {
+            Some(line) => Ok(serde_json::from_str(&line)?),
+            None => Err(anyhow!("daemon closed.")),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Typed notifications which daemon pushed (the response id is 0).
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::models::*;
+
+/// Pushed notification.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// [gid, name, avatar].
+    AccountUpdate(Account),
+    /// gid, profile.
+    AccountProfile(String, Profile),
+    /// gid.
+    AccountLock(String),
+    AuditCreate(Audit),
+    SessionCreate(Session),
+    /// id, datetime, content, readed, unread.
+    SessionLast(i64, i64, String, bool, i64),
+    /// id, addr (empty if only name changed), name, is_top.
+    SessionUpdate(i64, String, String, bool),
+    /// id, addr.
+    SessionConnect(i64, String),
+    SessionSuspend(i64),
+    SessionLost(i64),
+    SessionDelete(i64),
+    SessionClose(i64),
+    ChatFriendInfo(Friend),
+    /// fid, remark.
+    ChatFriendUpdate(i64, String),
+    ChatFriendClose(i64),
+    ChatFriendDelete(i64),
+    /// fid, old addr, new addr.
+    ChatFriendKeyChanged(i64, String, String),
+    ChatRequestCreate(Request),
+    /// id, is_delivery.
+    ChatRequestDelivery(i64, bool),
+    /// request id, new friend.
+    ChatRequestAgree(i64, Friend),
+    ChatRequestReject(i64),
+    ChatRequestDelete(i64),
+    ChatMessageCreate(Message),
+    /// id, is_delivery.
+    ChatMessageDelivery(i64, bool),
+    ChatMessageDelete(i64),
+    ChatLabelCreate(Label),
+    ChatLabelDelete(i64),
+    /// label id, fid, is_add.
+    ChatLabelFriend(i64, i64, bool),
+    DeviceCreate(Device),
+    DeviceRemove(i64),
+    DeviceOnline(i64),
+    DeviceOffline(i64),
+    /// the new daemon found in local network.
+    DeviceDiscover(Vec<LanPeer>),
+    WalletBalance(Balance),
+    /// status (waiting, connecting, done, failed, cancel), attempt, delay seconds, pending peers.
+    NetworkReconnect(String, i64, i64, i64),
+    /// other notifications, not typed.
+    Other {
+        method: String,
+        gid: String,
+        result: Value,
+    },
+}
+
+impl Event {
+    /// parse from the pushed message, it is JSON-RPC response with id 0.
+    pub fn from_rpc(param: &Value) -> Result<(String, Event)> {
+        let method = param["method"].as_str().unwrap_or("");
+        let gid = param["gid"].as_str().unwrap_or("").to_owned();
+        let v = &param["result"];
+
+        let event = match method {
+            "account-update" => Event::AccountUpdate(Account::from_rpc(v)?),
+            "account-profile" => {
+                let r = Row::new(v)?;
+                Event::AccountProfile(r.string(0)?, Profile::from_row(&r, 1)?)
+            }
+            "account-lock" => Event::AccountLock(Row::new(v)?.string(0)?),
+            "audit-create" => Event::AuditCreate(Audit::from_rpc(v)?),
+            "session-create" => Event::SessionCreate(Session::from_rpc(v)?),
+            "session-last" => {
+                let r = Row::new(v)?;
+                Event::SessionLast(r.i64(0)?, r.i64(1)?, r.string(2)?, r.bool(3)?, r.i64(4)?)
+            }
+            "session-update" => {
+                let r = Row::new(v)?;
+                Event::SessionUpdate(r.i64(0)?, r.string(1)?, r.string(2)?, r.bool(3)?)
+            }
+            "session-connect" => {
+                let r = Row::new(v)?;
+                Event::SessionConnect(r.i64(0)?, r.string(1)?)
+            }
+            "session-suspend" => Event::SessionSuspend(Row::new(v)?.i64(0)?),
+            "session-lost" => Event::SessionLost(Row::new(v)?.i64(0)?),
+            "session-delete" => Event::SessionDelete(Row::new(v)?.i64(0)?),
+            "session-close" => Event::SessionClose(Row::new(v)?.i64(0)?),
+            "chat-friend-info" => Event::ChatFriendInfo(Friend::from_rpc(v)?),
+            "chat-friend-update" => {
+                let r = Row::new(v)?;
+                Event::ChatFriendUpdate(r.i64(0)?, r.string(1)?)
+            }
+            "chat-friend-close" => Event::ChatFriendClose(Row::new(v)?.i64(0)?),
+            "chat-friend-delete" => Event::ChatFriendDelete(Row::new(v)?.i64(0)?),
+            "chat-friend-key-changed" => {
+                let r = Row::new(v)?;
+                Event::ChatFriendKeyChanged(r.i64(0)?, r.string(1)?, r.string(2)?)
+            }
+            "chat-request-create" => Event::ChatRequestCreate(Request::from_rpc(v)?),
+            "chat-request-delivery" => {
+                let r = Row::new(v)?;
+                Event::ChatRequestDelivery(r.i64(0)?, r.bool(1)?)
+            }
+            "chat-request-agree" => {
+                let r = Row::new(v)?;
+                Event::ChatRequestAgree(r.i64(0)?, Friend::from_rpc(r.value(1)?)?)
+            }
+            "chat-request-reject" => Event::ChatRequestReject(Row::new(v)?.i64(0)?),
+            "chat-request-delete" => Event::ChatRequestDelete(Row::new(v)?.i64(0)?),
+            "chat-message-create" => Event::ChatMessageCreate(Message::from_rpc(v)?),
+            "chat-message-delivery" => {
+                let r = Row::new(v)?;
+                Event::ChatMessageDelivery(r.i64(0)?, r.bool(1)?)
+            }
+            "chat-message-delete" => Event::ChatMessageDelete(Row::new(v)?.i64(0)?),
+            "chat-label-create" => Event::ChatLabelCreate(Label::from_rpc(v)?),
+            "chat-label-delete" => Event::ChatLabelDelete(Row::new(v)?.i64(0)?),
+            "chat-label-friend" => {
+                let r = Row::new(v)?;
+                Event::ChatLabelFriend(r.i64(0)?, r.i64(1)?, r.bool(2)?)
+            }
+            "device-create" => Event::DeviceCreate(Device::from_rpc(v)?),
+            "device-remove" => Event::DeviceRemove(Row::new(v)?.i64(0)?),
+            "device-online" => Event::DeviceOnline(Row::new(v)?.i64(0)?),
+            "device-offline" => Event::DeviceOffline(Row::new(v)?.i64(0)?),
+            "device-discover" => Event::DeviceDiscover(Vec::<LanPeer>::from_rpc(v)?),
+            "wallet-balance" => Event::WalletBalance(Balance::from_rpc(v)?),
+            "network-reconnect" => {
+                let r = Row::new(v)?;
+                Event::NetworkReconnect(r.string(0)?, r.i64(1)?, r.i64(2)?, r.i64(3)?)
+            }
+            _ => Event::Other {
+                method: method.to_owned(),
+                gid: gid.clone(),
+                result: v.clone(),
+            },
+        };
+
+        Ok((gid, event))
+    }
+}
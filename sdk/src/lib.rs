@@ -0,0 +1,26 @@
+//! Typed client of ESSE daemon's RPC.
+//!
+//! ```ignore
+//! use esse_sdk::{methods::ChatFriendList, Client, Event};
+//!
+//! let mut client = Client::connect("./.tdn/esse.sock", gid).await?;
+//! let friends = client.call(&ChatFriendList::default()).await?;
+//!
+//! client.subscribe(vec![], vec!["session-".to_owned()]).await?;
+//! while let Ok((gid, event)) = client.next_event().await {
+//!     if let Event::SessionLost(id) = event { ... }
+//! }
+//! ```
+
+pub mod events;
+pub mod methods;
+pub mod models;
+
+#[cfg(unix)]
+mod client;
+
+#[cfg(unix)]
+pub use client::Client;
+pub use events::Event;
+pub use methods::Method;
+pub use models::FromRpc;
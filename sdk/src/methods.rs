@@ -0,0 +1,289 @@
+//! Request of every RPC method, params are in the same positional order as daemon.
+
+use serde_json::{json, Value};
+
+use crate::models::*;
+
+/// The RPC method, and the typed response.
+pub trait Method {
+    /// method name in daemon.
+    const NAME: &'static str;
+    /// typed result.
+    type Response: FromRpc;
+    /// positional params.
+    fn params(&self) -> Value;
+}
+
+/// remove the missing optional params at tail.
+fn trim(mut params: Vec<Value>) -> Value {
+    while params.last() == Some(&Value::Null) {
+        params.pop();
+    }
+    Value::Array(params)
+}
+
+macro_rules! methods {
+    ($(
+        $(#[$doc:meta])*
+        $name:ident($method:literal) -> $resp:ty {
+            $($field:ident: $ty:ty),* $(,)?
+        } $([$($opt:ident: $oty:ty),* $(,)?])?;
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Clone, Debug, Default)]
+            pub struct $name {
+                $(pub $field: $ty,)*
+                $($(pub $opt: Option<$oty>,)*)?
+            }
+
+            impl Method for $name {
+                const NAME: &'static str = $method;
+                type Response = $resp;
+
+                fn params(&self) -> Value {
+                    trim(vec![$(json!(self.$field),)* $($(json!(self.$opt),)*)?])
+                }
+            }
+        )*
+    };
+}
+
+// --- account & session (new_rpc_handler) ---
+methods! {
+    /// result is the params.
+    Echo("echo") -> Vec<String> { text: String };
+    /// [peer id].
+    AccountSystemInfo("account-system-info") -> Vec<String> {};
+    /// connect the bootstrap peer, and save it as seed.
+    AddBootstrap("add-bootstrap") -> () { socket: String, transport: String };
+    /// [is_private, [[socket, transport, source]]].
+    BootstrapList("bootstrap-list") -> Bootstraps {};
+    /// [socket].
+    BootstrapRemove("bootstrap-remove") -> Vec<String> { socket: String };
+    AccountList("account-list") -> Vec<Account> {};
+    /// [mnemonic words].
+    AccountGenerate("account-generate") -> Vec<String> { lang: i64 };
+    /// [gid].
+    AccountCreate("account-create") -> Vec<String> {
+        lang: i64, seed: String, pass: String, name: String, lock: String, avatar: String,
+    };
+    /// [gid], addr is the device which sync from, empty if none.
+    AccountRestore("account-restore") -> Vec<String> {
+        lang: i64, seed: String, pass: String, name: String, lock: String, addr: String,
+    };
+    /// the profile fields are optional, keep current when missing.
+    AccountUpdate("account-update") -> () {
+        name: String, avatar: String,
+    } [bio: String, status: String, status_expire: i64, domain: String];
+    /// [is_ok].
+    AccountPinCheck("account-pin-check") -> Vec<bool> { gid: String, lock: String };
+    /// [new lock].
+    AccountPin("account-pin") -> Vec<String> { old: String, new: String };
+    /// [mnemonic].
+    AccountMnemonic("account-mnemonic") -> Vec<String> { lock: String };
+    /// [gid].
+    AccountLogin("account-login") -> Vec<String> { gid: String, lock: String };
+    AccountLogout("account-logout") -> () {};
+    /// close the friendships by default, `close_friends = false` keeps them in my other devices.
+    AccountDelete("account-delete") -> () { gid: String, lock: String } [close_friends: bool];
+    AccountAutolock("account-autolock") -> () { seconds: i64 };
+    /// store the lock in the device, login when daemon start.
+    AccountAutologin("account-autologin") -> () { lock: String, enable: bool };
+    /// [gid].
+    AccountLock("account-lock") -> Vec<String> {};
+    /// [gid].
+    AccountUnlock("account-unlock") -> Vec<String> { lock: String };
+    AuditList("audit-list") -> Audits {} [from: i64, limit: i64];
+    AccountOnline("account-online") -> () { gid: String };
+    AccountOffline("account-offline") -> () { gid: String };
+    SessionList("session-list") -> Vec<Session> {} [is_archived: bool, only_unread: bool];
+    SessionConnect("session-connect") -> Option<SessionOnline> { id: i64, remote: String };
+    SessionSuspend("session-suspend") -> Option<Vec<i64>> { id: i64, remote: String, must: bool };
+    SessionReaded("session-readed") -> () { id: i64 };
+    SessionNotify("session-notify") -> crate::models::SessionNotify { id: i64, notify: i64 };
+    SessionUpdate("session-update") -> () {
+        id: i64, is_top: bool, is_close: bool,
+    } [is_archived: bool, mute_until: i64];
+    Presence("presence") -> crate::models::Presence { id: i64 } [limit: i64];
+    /// [heartbeat, suspend] seconds, missing is keep.
+    PresencePolicy("presence-policy") -> Vec<i64> {} [heartbeat: i64, suspend: i64];
+}
+
+// --- local rpc (auth & subscribe) ---
+methods! {
+    /// [scopes].
+    RpcAuth("rpc-auth") -> Vec<String> { token: String };
+    /// gids & topics are filters, empty is all.
+    RpcSubscribe("rpc-subscribe") -> Subscription { gids: Vec<String>, topics: Vec<String> };
+    RpcUnsubscribe("rpc-unsubscribe") -> () {};
+    RpcPing("rpc-ping") -> () {};
+    /// [token, [scopes]].
+    RpcTokenCreate("rpc-token-create") -> RpcToken { scopes: Vec<String> };
+    RpcTokenList("rpc-token-list") -> Vec<RpcToken> {};
+    RpcTokenDelete("rpc-token-delete") -> Vec<String> { token: String };
+    /// OpenRPC document of all methods.
+    RpcDiscover("rpc-discover") -> RpcDocument {};
+}
+
+// --- chat ---
+methods! {
+    ChatEcho("chat-echo") -> Vec<String> { text: String };
+    ChatFriendList("chat-friend-list") -> Vec<Friend> { need_online: bool } [label: i64];
+    ChatFriendUpdate("chat-friend-update") -> () { id: i64, remark: String };
+    /// [id, safety number, qr, is_verified].
+    ChatFriendSafety("chat-friend-safety") -> FriendSafety { id: i64 };
+    ChatFriendVerify("chat-friend-verify") -> Friend { id: i64, is_verified: bool };
+    ChatFriendVerifyQr("chat-friend-verify-qr") -> Friend { qr: String };
+    ChatFriendClose("chat-friend-close") -> () { id: i64 };
+    ChatFriendDelete("chat-friend-delete") -> () { id: i64 };
+    ChatLabelList("chat-label-list") -> Vec<Label> {};
+    ChatLabelCreate("chat-label-create") -> Label { name: String };
+    ChatLabelDelete("chat-label-delete") -> Vec<i64> { id: i64 };
+    ChatLabelFriend("chat-label-friend") -> LabelFriend { id: i64, fid: i64, is_add: bool };
+    ChatRequestList("chat-request-list") -> Vec<Request> {};
+    ChatRequestCreate("chat-request-create") -> Request {
+        gid: String, addr: String, name: String, remark: String,
+    };
+    ChatInvitationList("chat-invitation-list") -> Vec<Invitation> {};
+    /// [invitation, code].
+    ChatInvitationCreate("chat-invitation-create") -> InvitationCode {
+        expire: i64, is_once: bool, is_auto: bool,
+    };
+    ChatInvitationDelete("chat-invitation-delete") -> () { id: i64 };
+    ChatInvitationRedeem("chat-invitation-redeem") -> Request { code: String, remark: String };
+    ChatRecoveryHolders("chat-recovery-holders") -> Vec<RecoveryHolder> {};
+    ChatRecoverySetup("chat-recovery-setup") -> Vec<RecoveryHolder> {
+        lock: String, threshold: i64, fids: Vec<i64>,
+    };
+    ChatRecoveryShares("chat-recovery-shares") -> Vec<RecoveryShare> { is_collect: bool };
+    ChatRecoveryAsk("chat-recovery-ask") -> () {
+        owner: String, holder_gid: String, holder_addr: String,
+    };
+    ChatRecoveryRelease("chat-recovery-release") -> () {
+        id: i64, to_gid: String, to_addr: String,
+    };
+    /// [lang, mnemonic].
+    ChatRecoveryCombine("chat-recovery-combine") -> RecoveredMnemonic { owner: String };
+    ChatRequestAgree("chat-request-agree") -> RequestAgree { id: i64 };
+    ChatRequestReject("chat-request-reject") -> () { id: i64 };
+    ChatRequestDelete("chat-request-delete") -> () { id: i64 };
+    ChatDetail("chat-detail") -> FriendDetail { id: i64 };
+    ChatMessageList("chat-message-list") -> Vec<Message> { fid: i64 };
+    /// m_type 0 is string.
+    ChatMessageCreate("chat-message-create") -> Message {
+        fid: i64, fgid: String, m_type: i64, content: String,
+    };
+    ChatMessageDelete("chat-message-delete") -> () { id: i64 };
+}
+
+// --- cloud ---
+methods! {
+    CloudEcho("cloud-echo") -> Vec<String> { text: String };
+}
+
+// --- device ---
+methods! {
+    DeviceEcho("device-echo") -> Vec<String> { text: String };
+    DeviceList("device-list") -> Vec<Device> {};
+    /// local device's result is `SystemStatus`, remote device's status is pushed.
+    DeviceStatus("device-status") -> Option<SystemStatus> { addr: String };
+    DeviceCreate("device-create") -> () { addr: String };
+    DeviceConnect("device-connect") -> () { addr: String };
+    DeviceDelete("device-delete") -> () { id: i64 };
+    /// the daemons found in local network, the new found is pushed.
+    DeviceDiscover("device-discover") -> Vec<LanPeer> {};
+}
+
+// --- domain ---
+methods! {
+    /// [providers, names].
+    DomainList("domain-list") -> Domains {};
+    DomainProviderAdd("domain-provider-add") -> () { provider: String };
+    DomainProviderDefault("domain-provider-default") -> () { id: i64 };
+    DomainProviderRemove("domain-provider-remove") -> () { id: i64 };
+    DomainRegister("domain-register") -> () {
+        provider: i64, addr: String, name: String, bio: String,
+    };
+    DomainActive("domain-active") -> () { name: String, provider: String, active: bool };
+    DomainRemove("domain-remove") -> () { name: String, provider: String };
+    DomainSearch("domain-search") -> () { addr: String, name: String };
+}
+
+// --- file (dc) ---
+methods! {
+    DcEcho("dc-echo") -> Vec<String> { text: String };
+    DcList("dc-list") -> Vec<File> { root: i64, parent: i64 };
+    DcFileCreate("dc-file-create") -> File { root: i64, parent: i64, name: String };
+    DcFileUpload("dc-file-upload") -> File { root: i64, parent: i64, path: String };
+    DcFolderCreate("dc-folder-create") -> File { root: i64, parent: i64, name: String };
+    DcFileUpdate("dc-file-update") -> File { id: i64, root: i64, parent: i64, name: String };
+    DcFileStar("dc-file-star") -> () { id: i64, starred: bool };
+    DcFileTrash("dc-file-trash") -> () { id: i64 };
+    DcFileDelete("dc-file-delete") -> () { id: i64 };
+}
+
+// --- group ---
+methods! {
+    GroupList("group-list") -> Vec<GroupChat> {};
+    GroupDetail("group-detail") -> crate::models::GroupDetail { id: i64 };
+    GroupCreate("group-create") -> GroupCreated { name: String };
+    GroupMemberJoin("group-member-join") -> Member { id: i64, fid: i64 };
+    GroupMessageCreate("group-message-create") -> GroupMessage {
+        id: i64, m_type: i64, content: String,
+    };
+    GroupName("group-name") -> crate::models::GroupName { id: i64, name: String };
+    GroupDelete("group-delete") -> () { id: i64 };
+}
+
+// --- jarvis ---
+methods! {
+    JarvisList("jarvis-list") -> Vec<JarvisMessage> {};
+    JarvisCreate("jarvis-create") -> JarvisMessage { lang: i64, m_type: i64, content: String };
+    JarvisDelete("jarvis-delete") -> () { id: i64 };
+}
+
+// --- wallet ---
+methods! {
+    WalletEcho("wallet-echo") -> Vec<String> { text: String };
+    WalletList("wallet-list") -> Vec<Address> {};
+    WalletGenerate("wallet-generate") -> Address { chain: i64, lock: String };
+    WalletImport("wallet-import") -> Address { chain: i64, secret: String, lock: String };
+    WalletTokenImport("wallet-token-import") -> () {
+        chain: i64, network: i64, address: String, contract: String,
+    };
+    /// [price, gas].
+    WalletGasPrice("wallet-gas-price") -> GasPrice {
+        chain: i64, network: i64, from: String, to: String, amount: String, contract: String,
+    };
+    /// from is the address id.
+    WalletTransfer("wallet-transfer") -> Transfer {
+        chain: i64, network: i64, from: i64, to: String,
+        amount: String, contract: String, lock: String,
+    };
+    WalletNft("wallet-nft") -> Nfts { address: i64, token: i64 };
+    WalletNftAdd("wallet-nft-add") -> Nft { address: i64, token: i64, hash: String };
+    WalletMain("wallet-main") -> () { id: i64 };
+}
+
+/// [network, [tokens]], the balance is pushed by `wallet-balance`.
+#[derive(Clone, Debug, Default)]
+pub struct WalletToken {
+    pub network: i64,
+    pub address: String,
+    /// query the token's balance, default is main token.
+    pub token: Option<i64>,
+}
+
+impl Method for WalletToken {
+    const NAME: &'static str = "wallet-token";
+    type Response = Tokens;
+
+    fn params(&self) -> Value {
+        match self.token {
+            // daemon check the token by params length.
+            Some(token) => json!([self.network, self.address, token, Value::Null]),
+            None => json!([self.network, self.address]),
+        }
+    }
+}
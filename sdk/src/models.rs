@@ -0,0 +1,1361 @@
+//! Typed models of the positional `to_rpc()` layouts.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// Parse the typed value from the rpc result.
+pub trait FromRpc: Sized {
+    fn from_rpc(value: &Value) -> Result<Self>;
+}
+
+impl FromRpc for Value {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl<T: FromRpc> FromRpc for Vec<T> {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let items = value.as_array().ok_or(anyhow!("result is not list."))?;
+        items.iter().map(T::from_rpc).collect()
+    }
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $f:ident),*) => {
+        $(
+            impl FromRpc for $ty {
+                fn from_rpc(value: &Value) -> Result<Self> {
+                    value
+                        .$f()
+                        .map(|v| v.to_owned())
+                        .ok_or(anyhow!("result is not {}.", stringify!($ty)))
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(String => as_str, i64 => as_i64, bool => as_bool);
+
+/// the method has no result, or the result is pushed.
+impl FromRpc for () {
+    fn from_rpc(_value: &Value) -> Result<Self> {
+        Ok(())
+    }
+}
+
+/// the method has result only in some cases, others are pushed.
+impl<T: FromRpc> FromRpc for Option<T> {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::from_rpc(value).map(Some)
+        }
+    }
+}
+
+/// Positional row reader.
+pub(crate) struct Row<'a>(&'a [Value]);
+
+impl<'a> Row<'a> {
+    pub fn new(value: &'a Value) -> Result<Row<'a>> {
+        value
+            .as_array()
+            .map(|v| Row(v.as_slice()))
+            .ok_or(anyhow!("result is not list."))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn value(&self, i: usize) -> Result<&'a Value> {
+        self.0.get(i).ok_or(anyhow!("result missing field {}.", i))
+    }
+
+    pub fn i64(&self, i: usize) -> Result<i64> {
+        let v = self.value(i)?;
+        // some old layouts use string number.
+        v.as_i64()
+            .or(v.as_str().and_then(|s| s.parse().ok()))
+            .ok_or(anyhow!("result field {} is not number.", i))
+    }
+
+    pub fn bool(&self, i: usize) -> Result<bool> {
+        let v = self.value(i)?;
+        v.as_bool()
+            .or(v.as_str().map(|s| s == "1"))
+            .ok_or(anyhow!("result field {} is not bool.", i))
+    }
+
+    pub fn string(&self, i: usize) -> Result<String> {
+        self.value(i)?
+            .as_str()
+            .map(|s| s.to_owned())
+            .ok_or(anyhow!("result field {} is not string.", i))
+    }
+
+    pub fn i64s(&self, i: usize) -> Result<Vec<i64>> {
+        self.value(i)?
+            .as_array()
+            .map(|v| v.iter().filter_map(|i| i.as_i64()).collect())
+            .ok_or(anyhow!("result field {} is not list.", i))
+    }
+
+    pub fn strings(&self, i: usize) -> Result<Vec<String>> {
+        Vec::from_rpc(self.value(i)?)
+    }
+}
+
+/// Account in `account-list`.
+#[derive(Clone, Debug)]
+pub struct Account {
+    pub gid: String,
+    pub name: String,
+    /// base64 avatar.
+    pub avatar: String,
+}
+
+impl FromRpc for Account {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Account {
+            gid: r.string(0)?,
+            name: r.string(1)?,
+            avatar: r.string(2)?,
+        })
+    }
+}
+
+/// Account's profile.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub bio: String,
+    pub status: String,
+    pub status_expire: i64,
+    pub domain: String,
+}
+
+impl Profile {
+    pub(crate) fn from_row(r: &Row, start: usize) -> Result<Self> {
+        Ok(Profile {
+            bio: r.string(start)?,
+            status: r.string(start + 1)?,
+            status_expire: r.i64(start + 2)?,
+            domain: r.string(start + 3)?,
+        })
+    }
+}
+
+/// Friend, `Friend::to_rpc` & `Friend::to_rpc_online`.
+#[derive(Clone, Debug)]
+pub struct Friend {
+    pub id: i64,
+    pub gid: String,
+    pub addr: String,
+    pub name: String,
+    pub wallet: String,
+    pub remark: String,
+    pub is_closed: bool,
+    pub datetime: i64,
+    /// only has value when list with online.
+    pub online: Option<bool>,
+    pub is_verified: bool,
+    pub profile: Profile,
+}
+
+impl FromRpc for Friend {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        // with online, it inserted after datetime.
+        let (online, i) = if r.len() > 13 {
+            (Some(r.bool(8)?), 9)
+        } else {
+            (None, 8)
+        };
+        Ok(Friend {
+            id: r.i64(0)?,
+            gid: r.string(1)?,
+            addr: r.string(2)?,
+            name: r.string(3)?,
+            wallet: r.string(4)?,
+            remark: r.string(5)?,
+            is_closed: r.bool(6)?,
+            datetime: r.i64(7)?,
+            online,
+            is_verified: r.bool(i)?,
+            profile: Profile::from_row(&r, i + 1)?,
+        })
+    }
+}
+
+/// Friend request, `Request::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub id: i64,
+    pub gid: String,
+    pub addr: String,
+    pub name: String,
+    pub remark: String,
+    pub is_me: bool,
+    pub is_ok: bool,
+    pub is_over: bool,
+    pub is_delivery: bool,
+    pub datetime: i64,
+}
+
+impl FromRpc for Request {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Request {
+            id: r.i64(0)?,
+            gid: r.string(1)?,
+            addr: r.string(2)?,
+            name: r.string(3)?,
+            remark: r.string(4)?,
+            is_me: r.bool(5)?,
+            is_ok: r.bool(6)?,
+            is_over: r.bool(7)?,
+            is_delivery: r.bool(8)?,
+            datetime: r.i64(9)?,
+        })
+    }
+}
+
+/// Chat message, `Message::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub id: i64,
+    pub hash: String,
+    pub fid: i64,
+    pub is_me: bool,
+    /// 0 is string, see `MessageType`.
+    pub m_type: i64,
+    pub content: String,
+    pub is_delivery: bool,
+    pub datetime: i64,
+}
+
+impl FromRpc for Message {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Message {
+            id: r.i64(0)?,
+            hash: r.string(1)?,
+            fid: r.i64(2)?,
+            is_me: r.bool(3)?,
+            m_type: r.i64(4)?,
+            content: r.string(5)?,
+            is_delivery: r.bool(6)?,
+            datetime: r.i64(7)?,
+        })
+    }
+}
+
+/// Friend's detail with messages, `chat-detail`.
+#[derive(Clone, Debug)]
+pub struct FriendDetail {
+    pub friend: Friend,
+    pub messages: Vec<Message>,
+}
+
+impl FromRpc for FriendDetail {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(FriendDetail {
+            friend: Friend::from_rpc(r.value(0)?)?,
+            messages: Vec::from_rpc(r.value(1)?)?,
+        })
+    }
+}
+
+/// Session, `Session::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub id: i64,
+    pub fid: i64,
+    pub gid: String,
+    pub addr: String,
+    pub s_type: i64,
+    pub name: String,
+    pub is_top: bool,
+    pub is_close: bool,
+    pub last_datetime: i64,
+    pub last_content: String,
+    pub last_readed: bool,
+    pub notify: i64,
+    pub is_archived: bool,
+    pub mute_until: i64,
+    pub unread: i64,
+    /// the online session is relayed by my other device, only in `session-list`.
+    pub is_relayed: bool,
+}
+
+impl FromRpc for Session {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Session {
+            id: r.i64(0)?,
+            fid: r.i64(1)?,
+            gid: r.string(2)?,
+            addr: r.string(3)?,
+            s_type: r.i64(4)?,
+            name: r.string(5)?,
+            is_top: r.bool(6)?,
+            is_close: r.bool(7)?,
+            last_datetime: r.i64(8)?,
+            last_content: r.string(9)?,
+            last_readed: r.bool(10)?,
+            notify: r.i64(11)?,
+            is_archived: r.bool(12)?,
+            mute_until: r.i64(13)?,
+            unread: r.i64(14)?,
+            is_relayed: r.len() > 15 && r.bool(15)?,
+        })
+    }
+}
+
+/// Device, `Device::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Device {
+    pub id: i64,
+    pub name: String,
+    pub info: String,
+    pub addr: String,
+    pub lasttime: i64,
+    pub online: bool,
+}
+
+impl FromRpc for Device {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Device {
+            id: r.i64(0)?,
+            name: r.string(1)?,
+            info: r.string(2)?,
+            addr: r.string(3)?,
+            lasttime: r.i64(4)?,
+            online: r.bool(5)?,
+        })
+    }
+}
+
+/// The daemon found in local network, `device-discover`.
+#[derive(Clone, Debug)]
+pub struct LanPeer {
+    pub addr: String,
+    pub socket: String,
+    pub lasttime: i64,
+}
+
+impl FromRpc for LanPeer {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(LanPeer {
+            addr: r.string(0)?,
+            socket: r.string(1)?,
+            lasttime: r.i64(2)?,
+        })
+    }
+}
+
+/// Device's status, `device-status`.
+#[derive(Clone, Debug)]
+pub struct SystemStatus {
+    pub cpu: i64,
+    pub memory: i64,
+    pub swap: i64,
+    pub disk: i64,
+    pub cpu_p: i64,
+    pub memory_p: i64,
+    pub swap_p: i64,
+    pub disk_p: i64,
+    pub uptime: i64,
+    /// the account login automatically in the device, the lock is stored in it.
+    pub is_autologin: bool,
+}
+
+impl FromRpc for SystemStatus {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(SystemStatus {
+            cpu: r.i64(0)?,
+            memory: r.i64(1)?,
+            swap: r.i64(2)?,
+            disk: r.i64(3)?,
+            cpu_p: r.i64(4)?,
+            memory_p: r.i64(5)?,
+            swap_p: r.i64(6)?,
+            disk_p: r.i64(7)?,
+            uptime: r.i64(8)?,
+            is_autologin: r.len() > 9 && r.bool(9)?,
+        })
+    }
+}
+
+/// Friend's label, `Label::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub id: i64,
+    pub name: String,
+    /// friends' id.
+    pub friends: Vec<i64>,
+    pub datetime: i64,
+}
+
+impl FromRpc for Label {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Label {
+            id: r.i64(0)?,
+            name: r.string(1)?,
+            friends: r.i64s(2)?,
+            datetime: r.i64(3)?,
+        })
+    }
+}
+
+/// Invitation, `Invitation::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Invitation {
+    pub id: i64,
+    pub token: String,
+    pub expire: i64,
+    pub is_once: bool,
+    pub is_auto: bool,
+    pub used: i64,
+    pub datetime: i64,
+}
+
+impl FromRpc for Invitation {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Invitation {
+            id: r.i64(0)?,
+            token: r.string(1)?,
+            expire: r.i64(2)?,
+            is_once: r.bool(3)?,
+            is_auto: r.bool(4)?,
+            used: r.i64(5)?,
+            datetime: r.i64(6)?,
+        })
+    }
+}
+
+/// Holder of my mnemonic's share, `RecoveryHolder::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct RecoveryHolder {
+    pub id: i64,
+    pub fid: i64,
+    pub index: i64,
+    pub threshold: i64,
+    pub total: i64,
+    pub datetime: i64,
+}
+
+impl FromRpc for RecoveryHolder {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(RecoveryHolder {
+            id: r.i64(0)?,
+            fid: r.i64(1)?,
+            index: r.i64(2)?,
+            threshold: r.i64(3)?,
+            total: r.i64(4)?,
+            datetime: r.i64(5)?,
+        })
+    }
+}
+
+/// Share of other's mnemonic, `RecoveryShare::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct RecoveryShare {
+    pub id: i64,
+    pub owner: String,
+    pub peer: String,
+    pub threshold: i64,
+    pub is_collect: bool,
+    pub datetime: i64,
+}
+
+impl FromRpc for RecoveryShare {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(RecoveryShare {
+            id: r.i64(0)?,
+            owner: r.string(1)?,
+            peer: r.string(2)?,
+            threshold: r.i64(3)?,
+            is_collect: r.bool(4)?,
+            datetime: r.i64(5)?,
+        })
+    }
+}
+
+/// Audit log entry, `Audit::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Audit {
+    pub id: i64,
+    pub kind: i64,
+    pub info: String,
+    pub device: String,
+    pub hash: String,
+    pub datetime: i64,
+}
+
+impl FromRpc for Audit {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Audit {
+            id: r.i64(0)?,
+            kind: r.i64(1)?,
+            info: r.string(2)?,
+            device: r.string(3)?,
+            hash: r.string(4)?,
+            datetime: r.i64(5)?,
+        })
+    }
+}
+
+/// Audit logs, `audit-list`.
+#[derive(Clone, Debug)]
+pub struct Audits {
+    /// the first tampered entry's id, none if chain is valid.
+    pub broken: Option<i64>,
+    pub audits: Vec<Audit>,
+}
+
+impl FromRpc for Audits {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Audits {
+            broken: r.value(0)?.as_i64(),
+            audits: Vec::from_rpc(r.value(1)?)?,
+        })
+    }
+}
+
+/// Session's presence, `presence`.
+#[derive(Clone, Debug)]
+pub struct Presence {
+    pub id: i64,
+    pub is_online: bool,
+    /// now if online, 0 if never seen.
+    pub last_seen: i64,
+    /// (is_online, datetime), the newest first.
+    pub history: Vec<(bool, i64)>,
+}
+
+impl FromRpc for Presence {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        let mut history = vec![];
+        for item in Vec::<Value>::from_rpc(r.value(3)?)? {
+            let h = Row::new(&item)?;
+            history.push((h.bool(0)?, h.i64(1)?));
+        }
+        Ok(Presence {
+            id: r.i64(0)?,
+            is_online: r.bool(1)?,
+            last_seen: r.i64(2)?,
+            history,
+        })
+    }
+}
+
+/// Wallet address, `Address::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Address {
+    pub id: i64,
+    pub chain: i64,
+    pub index: i64,
+    pub name: String,
+    pub address: String,
+    pub is_gen: bool,
+    pub main: bool,
+    pub balance: String,
+}
+
+impl FromRpc for Address {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Address {
+            id: r.i64(0)?,
+            chain: r.i64(1)?,
+            index: r.i64(2)?,
+            name: r.string(3)?,
+            address: r.string(4)?,
+            is_gen: r.bool(5)?,
+            main: r.bool(6)?,
+            balance: r.string(7)?,
+        })
+    }
+}
+
+/// Wallet token, `Token::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub id: i64,
+    pub chain: i64,
+    pub network: i64,
+    pub name: String,
+    pub contract: String,
+    pub decimal: i64,
+}
+
+impl FromRpc for Token {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Token {
+            id: r.i64(0)?,
+            chain: r.i64(1)?,
+            network: r.i64(2)?,
+            name: r.string(3)?,
+            contract: r.string(4)?,
+            decimal: r.i64(5)?,
+        })
+    }
+}
+
+/// Wallet balance, pushed by `wallet-balance`.
+#[derive(Clone, Debug)]
+pub struct Balance {
+    pub address: String,
+    pub network: i64,
+    pub balance: String,
+    pub token: Option<Token>,
+}
+
+impl FromRpc for Balance {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        let token = if r.len() > 3 {
+            Some(Token::from_rpc(r.value(3)?)?)
+        } else {
+            None
+        };
+        Ok(Balance {
+            address: r.string(0)?,
+            network: r.i64(1)?,
+            balance: r.string(2)?,
+            token,
+        })
+    }
+}
+
+/// Wallet tokens of the network, `wallet-token`.
+#[derive(Clone, Debug)]
+pub struct Tokens {
+    pub network: i64,
+    pub tokens: Vec<Token>,
+}
+
+impl FromRpc for Tokens {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Tokens {
+            network: r.i64(0)?,
+            tokens: Vec::from_rpc(r.value(1)?)?,
+        })
+    }
+}
+
+/// Gas of the transfer, `wallet-gas-price`.
+#[derive(Clone, Debug)]
+pub struct GasPrice {
+    pub price: String,
+    pub gas: String,
+}
+
+impl FromRpc for GasPrice {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(GasPrice {
+            price: r.string(0)?,
+            gas: r.string(1)?,
+        })
+    }
+}
+
+/// The sent transaction, `wallet-transfer`.
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    /// the address id.
+    pub from: i64,
+    pub network: i64,
+    pub hash: String,
+    pub to: String,
+}
+
+impl FromRpc for Transfer {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        let tx = Row::new(r.value(2)?)?;
+        Ok(Transfer {
+            from: r.i64(0)?,
+            network: r.i64(1)?,
+            hash: tx.string(0)?,
+            to: tx.string(1)?,
+        })
+    }
+}
+
+/// NFTs of the address, `wallet-nft`.
+#[derive(Clone, Debug)]
+pub struct Nfts {
+    pub address: i64,
+    pub token: i64,
+    pub hashes: Vec<String>,
+}
+
+impl FromRpc for Nfts {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Nfts {
+            address: r.i64(0)?,
+            token: r.i64(1)?,
+            hashes: r.strings(2)?,
+        })
+    }
+}
+
+/// The added NFT, `wallet-nft-add`.
+#[derive(Clone, Debug)]
+pub struct Nft {
+    pub address: i64,
+    pub token: i64,
+    pub hash: String,
+}
+
+impl FromRpc for Nft {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Nft {
+            address: r.i64(0)?,
+            token: r.i64(1)?,
+            hash: r.string(2)?,
+        })
+    }
+}
+
+/// Bootstrap peer, `bootstrap-list`.
+#[derive(Clone, Debug)]
+pub struct BootstrapPeer {
+    pub socket: String,
+    pub transport: String,
+    /// cache, seed or public.
+    pub source: String,
+}
+
+impl FromRpc for BootstrapPeer {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(BootstrapPeer {
+            socket: r.string(0)?,
+            transport: r.string(1)?,
+            source: r.string(2)?,
+        })
+    }
+}
+
+/// Bootstrap peers, `bootstrap-list`.
+#[derive(Clone, Debug)]
+pub struct Bootstraps {
+    /// the public seeds are disabled.
+    pub is_private: bool,
+    pub peers: Vec<BootstrapPeer>,
+}
+
+impl FromRpc for Bootstraps {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Bootstraps {
+            is_private: r.bool(0)?,
+            peers: Vec::from_rpc(r.value(1)?)?,
+        })
+    }
+}
+
+/// The online session's remote, `session-connect`.
+#[derive(Clone, Debug)]
+pub struct SessionOnline {
+    pub id: i64,
+    pub addr: String,
+}
+
+impl FromRpc for SessionOnline {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(SessionOnline {
+            id: r.i64(0)?,
+            addr: r.string(1)?,
+        })
+    }
+}
+
+/// Session's notify, `session-notify`.
+#[derive(Clone, Debug)]
+pub struct SessionNotify {
+    pub id: i64,
+    pub notify: i64,
+}
+
+impl FromRpc for SessionNotify {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(SessionNotify {
+            id: r.i64(0)?,
+            notify: r.i64(1)?,
+        })
+    }
+}
+
+/// Local RPC token, `rpc-token-create` & `rpc-token-list`.
+#[derive(Clone, Debug)]
+pub struct RpcToken {
+    pub token: String,
+    pub scopes: Vec<String>,
+}
+
+impl FromRpc for RpcToken {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(RpcToken {
+            token: r.string(0)?,
+            scopes: r.strings(1)?,
+        })
+    }
+}
+
+/// The subscribed filters, `rpc-subscribe`.
+#[derive(Clone, Debug)]
+pub struct Subscription {
+    pub gids: Vec<String>,
+    pub topics: Vec<String>,
+}
+
+impl FromRpc for Subscription {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Subscription {
+            gids: r.strings(0)?,
+            topics: r.strings(1)?,
+        })
+    }
+}
+
+/// Method in the OpenRPC document, `rpc-discover`.
+#[derive(Clone, Debug)]
+pub struct MethodDoc {
+    pub name: String,
+    pub summary: String,
+    /// (name, required), by position.
+    pub params: Vec<(String, bool)>,
+    /// layout of the result, "none" if the result is pushed.
+    pub result: String,
+}
+
+impl FromRpc for MethodDoc {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let mut params = vec![];
+        for p in value["params"].as_array().unwrap_or(&vec![]) {
+            let name = p["name"].as_str().unwrap_or("").to_owned();
+            params.push((name, p["required"].as_bool().unwrap_or(false)));
+        }
+        Ok(MethodDoc {
+            name: String::from_rpc(&value["name"])?,
+            summary: value["summary"].as_str().unwrap_or("").to_owned(),
+            params,
+            result: value["result"]["description"]
+                .as_str()
+                .unwrap_or("")
+                .to_owned(),
+        })
+    }
+}
+
+/// OpenRPC document of all methods, `rpc-discover`.
+#[derive(Clone, Debug)]
+pub struct RpcDocument {
+    pub openrpc: String,
+    /// daemon's version.
+    pub version: String,
+    pub methods: Vec<MethodDoc>,
+}
+
+impl FromRpc for RpcDocument {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        Ok(RpcDocument {
+            openrpc: String::from_rpc(&value["openrpc"])?,
+            version: value["info"]["version"].as_str().unwrap_or("").to_owned(),
+            methods: Vec::from_rpc(&value["methods"])?,
+        })
+    }
+}
+
+/// Friend's safety number, `chat-friend-safety`.
+#[derive(Clone, Debug)]
+pub struct FriendSafety {
+    pub id: i64,
+    pub number: String,
+    pub qr: String,
+    pub is_verified: bool,
+}
+
+impl FromRpc for FriendSafety {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(FriendSafety {
+            id: r.i64(0)?,
+            number: r.string(1)?,
+            qr: r.string(2)?,
+            is_verified: r.bool(3)?,
+        })
+    }
+}
+
+/// Friend added to or removed from label, `chat-label-friend`.
+#[derive(Clone, Debug)]
+pub struct LabelFriend {
+    pub id: i64,
+    pub fid: i64,
+    pub is_add: bool,
+}
+
+impl FromRpc for LabelFriend {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(LabelFriend {
+            id: r.i64(0)?,
+            fid: r.i64(1)?,
+            is_add: r.bool(2)?,
+        })
+    }
+}
+
+/// The created invitation and its code, `chat-invitation-create`.
+#[derive(Clone, Debug)]
+pub struct InvitationCode {
+    pub invitation: Invitation,
+    /// share it to others, redeem by `chat-invitation-redeem`.
+    pub code: String,
+}
+
+impl FromRpc for InvitationCode {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(InvitationCode {
+            invitation: Invitation::from_rpc(r.value(0)?)?,
+            code: r.string(1)?,
+        })
+    }
+}
+
+/// The recovered mnemonic, `chat-recovery-combine`.
+#[derive(Clone, Debug)]
+pub struct RecoveredMnemonic {
+    pub lang: i64,
+    pub mnemonic: String,
+}
+
+impl FromRpc for RecoveredMnemonic {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(RecoveredMnemonic {
+            lang: r.i64(0)?,
+            mnemonic: r.string(1)?,
+        })
+    }
+}
+
+/// The agreed request and new friend, `chat-request-agree`.
+#[derive(Clone, Debug)]
+pub struct RequestAgree {
+    pub id: i64,
+    pub friend: Friend,
+}
+
+impl FromRpc for RequestAgree {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(RequestAgree {
+            id: r.i64(0)?,
+            friend: Friend::from_rpc(r.value(1)?)?,
+        })
+    }
+}
+
+/// Domain provider, `Provider::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Provider {
+    pub id: i64,
+    pub name: String,
+    pub addr: String,
+    pub is_ok: bool,
+    pub is_default: bool,
+    pub is_proxy: bool,
+    pub is_actived: bool,
+}
+
+impl FromRpc for Provider {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Provider {
+            id: r.i64(0)?,
+            name: r.string(1)?,
+            addr: r.string(2)?,
+            is_ok: r.bool(3)?,
+            is_default: r.bool(4)?,
+            is_proxy: r.bool(5)?,
+            is_actived: r.bool(6)?,
+        })
+    }
+}
+
+/// Registered domain name, `Name::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Name {
+    pub id: i64,
+    /// the provider's id.
+    pub provider: i64,
+    pub name: String,
+    pub bio: String,
+    pub is_ok: bool,
+    pub is_actived: bool,
+}
+
+impl FromRpc for Name {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Name {
+            id: r.i64(0)?,
+            provider: r.i64(1)?,
+            name: r.string(2)?,
+            bio: r.string(3)?,
+            is_ok: r.bool(4)?,
+            is_actived: r.bool(5)?,
+        })
+    }
+}
+
+/// Domain providers and my names, `domain-list`.
+#[derive(Clone, Debug)]
+pub struct Domains {
+    pub providers: Vec<Provider>,
+    pub names: Vec<Name>,
+}
+
+impl FromRpc for Domains {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Domains {
+            providers: Vec::from_rpc(r.value(0)?)?,
+            names: Vec::from_rpc(r.value(1)?)?,
+        })
+    }
+}
+
+/// File or folder, `File::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct File {
+    pub id: i64,
+    pub did: String,
+    pub parent: i64,
+    pub root: i64,
+    pub name: String,
+    pub starred: bool,
+    pub datetime: i64,
+}
+
+impl FromRpc for File {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(File {
+            id: r.i64(0)?,
+            did: r.string(1)?,
+            parent: r.i64(2)?,
+            root: r.i64(3)?,
+            name: r.string(4)?,
+            starred: r.bool(5)?,
+            datetime: r.i64(6)?,
+        })
+    }
+}
+
+/// Group chat, `GroupChat::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct GroupChat {
+    pub id: i64,
+    pub gid: String,
+    pub addr: String,
+    pub name: String,
+    pub close: bool,
+    pub local: bool,
+}
+
+impl FromRpc for GroupChat {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(GroupChat {
+            id: r.i64(0)?,
+            gid: r.string(1)?,
+            addr: r.string(2)?,
+            name: r.string(3)?,
+            close: r.bool(4)?,
+            local: r.bool(5)?,
+        })
+    }
+}
+
+/// Group's member, `Member::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub id: i64,
+    /// the group's id.
+    pub fid: i64,
+    pub gid: String,
+    pub addr: String,
+    pub name: String,
+    pub leave: bool,
+}
+
+impl FromRpc for Member {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(Member {
+            id: r.i64(0)?,
+            fid: r.i64(1)?,
+            gid: r.string(2)?,
+            addr: r.string(3)?,
+            name: r.string(4)?,
+            leave: r.bool(5)?,
+        })
+    }
+}
+
+/// Group's message, `group::Message::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct GroupMessage {
+    pub id: i64,
+    pub height: i64,
+    /// the group's id.
+    pub fid: i64,
+    /// the member's id.
+    pub mid: i64,
+    pub is_me: bool,
+    pub m_type: i64,
+    pub content: String,
+    pub is_delivery: bool,
+    pub datetime: i64,
+}
+
+impl FromRpc for GroupMessage {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(GroupMessage {
+            id: r.i64(0)?,
+            height: r.i64(1)?,
+            fid: r.i64(2)?,
+            mid: r.i64(3)?,
+            is_me: r.bool(4)?,
+            m_type: r.i64(5)?,
+            content: r.string(6)?,
+            is_delivery: r.bool(7)?,
+            datetime: r.i64(8)?,
+        })
+    }
+}
+
+/// Group's detail, `group-detail`.
+#[derive(Clone, Debug)]
+pub struct GroupDetail {
+    pub group: GroupChat,
+    pub members: Vec<Member>,
+    pub messages: Vec<GroupMessage>,
+}
+
+impl FromRpc for GroupDetail {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(GroupDetail {
+            group: GroupChat::from_rpc(r.value(0)?)?,
+            members: Vec::from_rpc(r.value(1)?)?,
+            messages: Vec::from_rpc(r.value(2)?)?,
+        })
+    }
+}
+
+/// The created group, `group-create`.
+#[derive(Clone, Debug)]
+pub struct GroupCreated {
+    /// the session's id.
+    pub sid: i64,
+    pub id: i64,
+}
+
+impl FromRpc for GroupCreated {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(GroupCreated {
+            sid: r.i64(0)?,
+            id: r.i64(1)?,
+        })
+    }
+}
+
+/// The renamed group, `group-name`.
+#[derive(Clone, Debug)]
+pub struct GroupName {
+    pub id: i64,
+    pub name: String,
+}
+
+impl FromRpc for GroupName {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(GroupName {
+            id: r.i64(0)?,
+            name: r.string(1)?,
+        })
+    }
+}
+
+/// Jarvis's message, `jarvis::Message::to_rpc`.
+#[derive(Clone, Debug)]
+pub struct JarvisMessage {
+    pub id: i64,
+    pub is_me: bool,
+    pub m_type: i64,
+    pub content: String,
+    pub datetime: i64,
+}
+
+impl FromRpc for JarvisMessage {
+    fn from_rpc(value: &Value) -> Result<Self> {
+        let r = Row::new(value)?;
+        Ok(JarvisMessage {
+            id: r.i64(0)?,
+            is_me: r.bool(1)?,
+            m_type: r.i64(2)?,
+            content: r.string(3)?,
+            datetime: r.i64(4)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn primitive_and_option() {
+        assert_eq!(
+            Vec::<String>::from_rpc(&json!(["a", "b"])).unwrap(),
+            vec!["a", "b"]
+        );
+        assert!(i64::from_rpc(&json!("1")).is_err());
+        assert!(<()>::from_rpc(&json!(null)).is_ok());
+        assert!(Option::<SessionOnline>::from_rpc(&json!(null))
+            .unwrap()
+            .is_none());
+        let online = Option::<SessionOnline>::from_rpc(&json!([1, "ab"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!((online.id, online.addr.as_str()), (1, "ab"));
+    }
+
+    #[test]
+    fn friend_with_online() {
+        let profile = json!(["bio", "busy", 10, "esse"]);
+        let mut row = json!([1, "gid", "addr", "name", "0x", "remark", false, 100, true]);
+        row.as_array_mut()
+            .unwrap()
+            .extend(profile.as_array().unwrap().clone());
+        let f = Friend::from_rpc(&row).unwrap();
+        assert_eq!(f.online, None);
+        assert!(f.is_verified);
+        assert_eq!(f.profile.status_expire, 10);
+
+        // online is inserted after datetime.
+        let mut row = json!([1, "gid", "addr", "name", "0x", "remark", false, 100, true, false]);
+        row.as_array_mut()
+            .unwrap()
+            .extend(profile.as_array().unwrap().clone());
+        let f = Friend::from_rpc(&row).unwrap();
+        assert_eq!(f.online, Some(true));
+        assert!(!f.is_verified);
+        assert_eq!(f.profile.domain, "esse");
+    }
+
+    #[test]
+    fn bool_as_string() {
+        let r = Message::from_rpc(&json!([1, "hash", 2, "1", 0, "hi", "0", 100])).unwrap();
+        assert!(r.is_me);
+        assert!(!r.is_delivery);
+    }
+
+    #[test]
+    fn nested_layouts() {
+        let invitation = json!([1, "token", 100, true, false, 0, 100]);
+        let code = InvitationCode::from_rpc(&json!([invitation, "esse://code"])).unwrap();
+        assert_eq!(code.invitation.token, "token");
+        assert_eq!(code.code, "esse://code");
+
+        let transfer = Transfer::from_rpc(&json!([3, 1, ["0xhash", "0xto"]])).unwrap();
+        assert_eq!((transfer.from, transfer.hash.as_str()), (3, "0xhash"));
+
+        let tokens = Tokens::from_rpc(&json!([1, [[1, 0, 1, "USDT", "0xc", 6]]])).unwrap();
+        assert_eq!(tokens.tokens[0].decimal, 6);
+
+        let audits = Audits::from_rpc(&json!([null, [[1, 0, "", "addr", "hash", 100]]])).unwrap();
+        assert_eq!(audits.broken, None);
+        assert_eq!(audits.audits.len(), 1);
+
+        let group = json!([1, "gid", "addr", "name", false, true]);
+        let member = json!([1, 1, "mgid", "maddr", "mname", false]);
+        let message = json!([1, 2, 1, 1, true, 0, "hi", true, 100]);
+        let detail = GroupDetail::from_rpc(&json!([group, [member], [message]])).unwrap();
+        assert!(detail.group.local);
+        assert_eq!(detail.members[0].name, "mname");
+        assert_eq!(detail.messages[0].content, "hi");
+
+        let domains = Domains::from_rpc(&json!([
+            [[1, "p", "addr", true, true, false, true]],
+            [[1, 1, "n", "bio", true, false]]
+        ]))
+        .unwrap();
+        assert!(domains.providers[0].is_actived);
+        assert_eq!(domains.names[0].bio, "bio");
+
+        let bootstraps =
+            Bootstraps::from_rpc(&json!([false, [["1.1.1.1:7364", "quic", "seed"]]])).unwrap();
+        assert_eq!(bootstraps.peers[0].source, "seed");
+    }
+
+    #[test]
+    fn rpc_document() {
+        let doc = RpcDocument::from_rpc(&json!({
+            "openrpc": "1.2.6",
+            "info": {"title": "ESSE RPC", "version": "0.1.0"},
+            "methods": [{
+                "name": "echo",
+                "summary": "echo.",
+                "params": [{"name": "text", "required": true, "schema": {}}],
+                "result": {"name": "result", "description": "params", "schema": {}},
+            }],
+        }))
+        .unwrap();
+        assert_eq!(doc.version, "0.1.0");
+        assert_eq!(doc.methods[0].params, vec![("text".to_owned(), true)]);
+        assert_eq!(doc.methods[0].result, "params");
+    }
+
+    #[test]
+    fn missing_field() {
+        assert!(Device::from_rpc(&json!([1, "name"])).is_err());
+        assert!(Label::from_rpc(&json!({"id": 1})).is_err());
+    }
+}
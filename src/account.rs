@@ -43,6 +43,77 @@ pub fn lang_from_i64(u: i64) -> Language {
     }
 }
 
+/// escape the string from user and friends in SQL.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace("'", "''")
+}
+
+/// account's public profile, except name and avatar.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) struct Profile {
+    pub bio: String,
+    pub status: String,
+    /// status's expire time, 0 is never expired.
+    pub status_expire: i64,
+    /// domain name, empty is none.
+    pub domain: String,
+}
+
+impl Profile {
+    pub fn new(bio: String, status: String, status_expire: i64, domain: String) -> Self {
+        Self {
+            bio,
+            status,
+            status_expire,
+            domain,
+        }
+    }
+
+    /// the SQL's SET of the profile, the strings are from user and friends, escape them.
+    pub fn to_sql(&self) -> String {
+        format!(
+            "bio='{}', status='{}', status_expire={}, domain='{}'",
+            escape(&self.bio),
+            escape(&self.status),
+            self.status_expire,
+            escape(&self.domain)
+        )
+    }
+
+    /// here is zero-copy and unwrap is safe. checked.
+    pub fn from_values(v: &mut Vec<DsValue>) -> Self {
+        let domain = v.pop().unwrap().as_string();
+        let status_expire = v.pop().unwrap().as_i64();
+        let status = v.pop().unwrap().as_string();
+        let bio = v.pop().unwrap().as_string();
+        Self::new(bio, status, status_expire, domain)
+    }
+}
+
+/// account's metadata, all accounts' are loaded when start,
+/// and the full account is loaded when login.
+pub(crate) struct AccountMeta {
+    pub gid: GroupId,
+    pub name: String,
+    pub avatar: Vec<u8>,
+}
+
+impl AccountMeta {
+    pub fn all(db: &DStorage) -> Result<Vec<AccountMeta>> {
+        let matrix = db.query("SELECT gid, name, avatar FROM accounts ORDER BY datetime DESC")?;
+        let mut metas = vec![];
+        for mut v in matrix {
+            metas.push(AccountMeta {
+                avatar: base64::decode(v.pop().unwrap().as_str()).unwrap_or(vec![]),
+                name: v.pop().unwrap().as_string(),
+                gid: GroupId::from_hex(v.pop().unwrap().as_str()).unwrap_or(GroupId::default()),
+            });
+        }
+        Ok(metas)
+    }
+}
+
+#[derive(Clone)]
 pub(crate) struct Account {
     pub id: i64,
     pub gid: GroupId,
@@ -60,6 +131,8 @@ pub(crate) struct Account {
     pub own_height: u64,  // own data consensus height.
     pub event: EventId,
     pub datetime: i64,
+    pub autolock: i64, // auto-lock idle seconds, 0 is disabled.
+    pub profile: Profile,
 }
 
 impl Account {
@@ -98,6 +171,8 @@ impl Account {
             encrypt,
             avatar,
             datetime,
+            autolock: 0,
+            profile: Profile::default(),
         }
     }
 
@@ -180,6 +255,8 @@ impl Account {
     /// here is zero-copy and unwrap is safe. checked.
     fn from_values(mut v: Vec<DsValue>) -> Account {
         Account {
+            profile: Profile::from_values(&mut v),
+            autolock: v.pop().unwrap().as_i64(),
             datetime: v.pop().unwrap().as_i64(),
             event: EventId::from_hex(v.pop().unwrap().as_str()).unwrap_or(EventId::default()),
             own_height: v.pop().unwrap().as_i64() as u64,
@@ -201,7 +278,7 @@ impl Account {
 
     pub fn get(db: &DStorage, gid: &GroupId) -> Result<Account> {
         let sql = format!(
-            "SELECT id, gid, indx, lang, pass, name, lock, mnemonic, secret, encrypt, avatar, wallet, pub_height, own_height, event, datetime FROM accounts WHERE gid = '{}'",
+            "SELECT id, gid, indx, lang, pass, name, lock, mnemonic, secret, encrypt, avatar, wallet, pub_height, own_height, event, datetime, autolock, bio, status, status_expire, domain FROM accounts WHERE gid = '{}'",
             gid.to_hex()
         );
         let mut matrix = db.query(&sql)?;
@@ -213,17 +290,6 @@ impl Account {
         }
     }
 
-    pub fn all(db: &DStorage) -> Result<Vec<Account>> {
-        let matrix = db.query(
-            "SELECT id, gid, indx, lang, pass, name, lock, mnemonic, secret, encrypt, avatar, wallet, pub_height, own_height, event, datetime FROM accounts ORDER BY datetime DESC",
-        )?;
-        let mut accounts = vec![];
-        for values in matrix {
-            accounts.push(Account::from_values(values));
-        }
-        Ok(accounts)
-    }
-
     pub fn insert(&mut self, db: &DStorage) -> Result<()> {
         let mut unique_check = db.query(&format!(
             "SELECT id from accounts WHERE gid = '{}'",
@@ -275,21 +341,35 @@ impl Account {
 
     pub fn update_info(&self, db: &DStorage) -> Result<usize> {
         let sql = format!(
-            "UPDATE accounts SET name='{}', avatar='{}', wallet='{}', pub_height={} WHERE id = {}",
-            self.name,
+            "UPDATE accounts SET name='{}', avatar='{}', wallet='{}', pub_height={}, {} WHERE id = {}",
+            escape(&self.name),
             base64::encode(&self.avatar),
-            self.wallet,
+            escape(&self.wallet),
             self.pub_height,
+            self.profile.to_sql(),
             self.id,
         );
         db.update(&sql)
     }
 
-    pub fn _delete(&self, db: &DStorage) -> Result<usize> {
+    pub fn delete(&self, db: &DStorage) -> Result<usize> {
+        let sql = format!(
+            "DELETE FROM account_migrates WHERE gid = '{}'",
+            self.gid.to_hex()
+        );
+        db.delete(&sql)?;
         let sql = format!("DELETE FROM accounts WHERE id = {}", self.id);
         db.delete(&sql)
     }
 
+    pub fn update_autolock(&self, db: &DStorage) -> Result<usize> {
+        let sql = format!(
+            "UPDATE accounts SET autolock={} WHERE id = {}",
+            self.autolock, self.id,
+        );
+        db.update(&sql)
+    }
+
     pub fn update_consensus(&mut self, db: &DStorage, height: u64, eid: EventId) -> Result<usize> {
         self.own_height = height;
         self.event = eid;
@@ -343,3 +423,22 @@ impl User {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_sql_escaped() {
+        let profile = Profile::new(
+            "I'm away".to_owned(),
+            "'); DROP TABLE friends; --".to_owned(),
+            0,
+            "o'neil.esse".to_owned(),
+        );
+        assert_eq!(
+            profile.to_sql(),
+            "bio='I''m away', status='''); DROP TABLE friends; --', status_expire=0, domain='o''neil.esse'"
+        );
+    }
+}
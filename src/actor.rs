@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tdn::types::group::GroupId;
+use tokio::{
+    sync::mpsc::{error::SendError, unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+/// the actor exit when no task in it, new actor will start when new task coming.
+const ACTOR_IDLE: Duration = Duration::from_secs(60);
+
+type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+type Key = (GroupId, GroupId);
+
+/// Actors which handle the main loop's messages.
+/// every key (account or session) has its own actor, the messages with same key
+/// are handled in order, and different keys are handled concurrently,
+/// so a slow key not block others.
+pub(crate) struct Actors {
+    actors: HashMap<Key, (UnboundedSender<Task>, JoinHandle<()>)>,
+}
+
+/// the key of the account's messages, e.g. devices' events and rpc.
+pub(crate) fn account_key(gid: GroupId) -> Key {
+    (gid, GroupId::default())
+}
+
+/// the key of the session's messages. (my gid, remote gid).
+pub(crate) fn session_key(mgid: GroupId, fgid: GroupId) -> Key {
+    (mgid, fgid)
+}
+
+/// run the key's tasks in order, after the previous actor of the key finished.
+async fn run(previous: Option<JoinHandle<()>>, mut recver: UnboundedReceiver<Task>) {
+    if let Some(previous) = previous {
+        let _ = previous.await;
+    }
+
+    loop {
+        let task = match tokio::time::timeout(ACTOR_IDLE, recver.recv()).await {
+            Ok(Some(task)) => task,
+            Ok(None) => break,
+            Err(_) => {
+                // idle, not receive new tasks, and handle the remain tasks.
+                recver.close();
+                continue;
+            }
+        };
+
+        // run in new task, one task panic not stop the actor.
+        if let Err(e) = tokio::spawn(task).await {
+            error!("Actor task failure: {}", e);
+        }
+    }
+}
+
+impl Actors {
+    pub fn init() -> Actors {
+        Actors {
+            actors: HashMap::new(),
+        }
+    }
+
+    /// send the task to the key's actor, it will run after the key's previous tasks.
+    /// it never blocks the caller.
+    pub fn send<F>(&mut self, key: Key, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut task: Task = Box::pin(task);
+        let mut previous = None;
+        if let Some((sender, handle)) = self.actors.remove(&key) {
+            match sender.send(task) {
+                Ok(()) => {
+                    self.actors.insert(key, (sender, handle));
+                    return;
+                }
+                Err(SendError(t)) => {
+                    // the actor is idle closed, the new one runs after it.
+                    task = t;
+                    previous = Some(handle);
+                }
+            }
+        }
+
+        // clean the idle closed actors.
+        self.actors
+            .retain(|_, (sender, handle)| !sender.is_closed() || !handle.is_finished());
+
+        let (sender, recver) = unbounded_channel();
+        let _ = sender.send(task);
+        let handle = tokio::spawn(run(previous, recver));
+        self.actors.insert(key, (sender, handle));
+    }
+
+    /// stop receiving tasks, and waiting the pending tasks finished.
+    /// return false if timeout.
+    pub async fn stop(self, timeout: Duration) -> bool {
+        let handles: Vec<JoinHandle<()>> = self
+            .actors
+            .into_iter()
+            .map(|(_, (_sender, handle))| handle)
+            .collect();
+        tokio::time::timeout(timeout, async move {
+            for handle in handles {
+                let _ = handle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
@@ -11,8 +11,9 @@ use tokio::sync::RwLock;
 
 use chat_types::{MessageType, NetworkMessage};
 
-use crate::account::{Account, User};
+use crate::account::{Account, Profile, User};
 use crate::event::InnerEvent;
+use crate::group::Group;
 use crate::layer::{Layer, Online};
 use crate::migrate::consensus::{FRIEND_TABLE_PATH, MESSAGE_TABLE_PATH, REQUEST_TABLE_PATH};
 use crate::rpc::{
@@ -21,8 +22,12 @@ use crate::rpc::{
 };
 use crate::session::{connect_session, Session, SessionType};
 use crate::storage::{account_db, chat_db, session_db, write_avatar_sync};
+use crate::utils::crypto::encrypt_with_key;
 
-use super::models::{handle_nmsg, raw_to_network_message, Friend, Message, Request};
+use super::models::{
+    handle_nmsg, is_setup, raw_to_network_message, Friend, Invitation, Message, RecoveryShare,
+    Request, RECOVERY_KEY_CONTEXT,
+};
 use super::rpc;
 
 /// Chat connect data structure.
@@ -55,6 +60,20 @@ pub(crate) enum LayerEvent {
     InfoRes(User),
     /// close friendship.
     Close,
+    /// make friendship request with invitation's token.
+    /// params is name, remark, proof, token.
+    RequestInvitation(String, String, Proof, String),
+    /// friend's mnemonic share, hold it for social recovery.
+    /// params is mnemonic's lang, threshold, setup's id, all holders, share.
+    RecoveryShare(i64, i64, String, Vec<GroupId>, Vec<u8>),
+    /// ask the holder to release the share.
+    /// params is the owner's gid (the lost account).
+    RecoveryAsk(GroupId),
+    /// holder released share.
+    /// params is owner's gid, mnemonic's lang, threshold, setup's id, all holders, share.
+    RecoveryReply(GroupId, i64, i64, String, Vec<GroupId>, Vec<u8>),
+    /// user's profile, send with the InfoRes.
+    InfoProfile(Profile),
 }
 
 pub(crate) async fn handle(
@@ -65,10 +84,11 @@ pub(crate) async fn handle(
 ) -> Result<HandleResult> {
     debug!("---------DEBUG--------- GOT CHAT EVENT");
     let mut results = HandleResult::new();
-    let mut layer = arc_layer.write().await;
+    let (base, _, group) = layer_info(arc_layer).await;
 
     match msg {
         RecvType::Leave(addr) => {
+            let mut layer = arc_layer.write().await;
             for (mgid, running) in &mut layer.runnings {
                 for sid in running.peer_leave(&addr) {
                     results.rpcs.push(session_lost(*mgid, &sid));
@@ -77,10 +97,11 @@ pub(crate) async fn handle(
         }
         RecvType::Connect(addr, data) | RecvType::ResultConnect(addr, data) => {
             // ESSE chat layer connect date structure.
-            if let Ok(height) = handle_connect(&mgid, &fgid, &addr, data, &mut layer, &mut results)
+            if let Ok(height) =
+                handle_connect(&mgid, &fgid, &addr, data, arc_layer, &mut results).await
             {
                 let peer_id = addr.id;
-                let proof = layer.group.read().await.prove_addr(&mgid, &addr.id)?;
+                let proof = group.read().await.prove_addr(&mgid, &addr.id)?;
                 let data = bincode::serialize(&proof).unwrap_or(vec![]);
                 let msg = SendType::Result(0, addr, true, false, data);
                 results.layers.push((mgid, fgid, msg));
@@ -98,7 +119,7 @@ pub(crate) async fn handle(
             // ESSE chat layer result date structure.
             if is_ok {
                 if let Ok(height) =
-                    handle_connect(&mgid, &fgid, &addr, data, &mut layer, &mut results)
+                    handle_connect(&mgid, &fgid, &addr, data, arc_layer, &mut results).await
                 {
                     let info = LayerEvent::InfoReq(height);
                     let data = bincode::serialize(&info).unwrap_or(vec![]);
@@ -109,7 +130,7 @@ pub(crate) async fn handle(
                     results.layers.push((mgid, fgid, msg));
                 }
             } else {
-                let db = chat_db(&layer.base, &mgid)?;
+                let db = chat_db(&base, &mgid)?;
                 let friend = Friend::get_id(&db, &fgid)?;
                 if friend.contains_addr(&addr.id) {
                     results.rpcs.push(rpc::friend_close(mgid, friend.id));
@@ -118,7 +139,7 @@ pub(crate) async fn handle(
             }
         }
         RecvType::Event(addr, bytes) => {
-            return LayerEvent::handle(fgid, mgid, &mut layer, addr, bytes).await;
+            return LayerEvent::handle(fgid, mgid, arc_layer, addr, bytes).await;
         }
         RecvType::Stream(_uid, _stream, _bytes) => {
             // TODO stream
@@ -127,8 +148,9 @@ pub(crate) async fn handle(
             debug!("delivery: tid: {}, is_ok: {}", tid, is_ok);
             // TODO maybe send failure need handle.
             if is_ok {
-                if let Some((gid, db_id)) = layer.delivery.remove(&tid) {
-                    let db = chat_db(&layer.base, &mgid)?;
+                let delivery = arc_layer.write().await.delivery.remove(&tid);
+                if let Some((gid, db_id)) = delivery {
+                    let db = chat_db(&base, &mgid)?;
                     let resp = match t {
                         DeliveryType::Event => {
                             Message::delivery(&db, db_id, true)?;
@@ -155,36 +177,50 @@ pub(crate) async fn handle(
     Ok(results)
 }
 
-fn handle_connect(
+/// the layer's storage path, self addr and group, they are not changed when running.
+/// so the layer lock only held when lookup or change the running layers.
+async fn layer_info(arc_layer: &Arc<RwLock<Layer>>) -> (PathBuf, PeerId, Arc<RwLock<Group>>) {
+    let layer = arc_layer.read().await;
+    (layer.base.clone(), layer.addr, layer.group.clone())
+}
+
+async fn handle_connect(
     mgid: &GroupId,
     fgid: &GroupId,
     addr: &Peer,
     data: Vec<u8>,
-    layer: &mut Layer,
+    arc_layer: &Arc<RwLock<Layer>>,
     results: &mut HandleResult,
 ) -> Result<i64> {
+    let (base, self_addr, _) = layer_info(arc_layer).await;
+
     // 0. deserialize connect data.
     let proof: Proof = bincode::deserialize(&data)?;
 
     // 1. check verify.
-    proof.verify(fgid, &addr.id, &layer.addr)?;
+    if let Err(e) = proof.verify(fgid, &addr.id, &self_addr) {
+        key_changed(&base, mgid, fgid, &addr.id, results);
+        return Err(e);
+    }
 
     // 2. check friendship.
-    let friend = update_friend(&layer.base, mgid, fgid, &addr.id);
+    let friend = update_friend(&base, mgid, fgid, &addr.id);
     if friend.is_err() {
         return Err(anyhow!("not friend"));
     }
     let f = friend.unwrap(); // safe.
 
     // 3. get session.
-    let session_some = connect_session(&layer.base, mgid, &SessionType::Chat, &f.id, &addr.id)?;
+    let session_some = connect_session(&base, mgid, &SessionType::Chat, &f.id, &addr.id)?;
     if session_some.is_none() {
         return Err(anyhow!("not friend"));
     }
     let sid = session_some.unwrap().id;
 
     // 4. active this session.
-    layer
+    arc_layer
+        .write()
+        .await
         .running_mut(mgid)?
         .check_add_online(*fgid, Online::Direct(addr.id), sid, f.id)?;
 
@@ -197,37 +233,41 @@ impl LayerEvent {
     pub async fn handle(
         fgid: GroupId,
         mgid: GroupId,
-        layer: &mut Layer,
+        arc_layer: &Arc<RwLock<Layer>>,
         addr: PeerId,
         bytes: Vec<u8>,
     ) -> Result<HandleResult> {
         let event: LayerEvent = bincode::deserialize(&bytes)?;
         let mut results = HandleResult::new();
+        let (base, self_addr, group) = layer_info(arc_layer).await;
 
         match event {
             LayerEvent::Offline(_) => {
+                let mut layer = arc_layer.write().await;
                 let (sid, _fid) = layer.get_running_remote_id(&mgid, &fgid)?;
                 layer.running_mut(&mgid)?.check_offline(&fgid, &addr);
                 results.rpcs.push(session_lost(mgid, &sid));
             }
             LayerEvent::Suspend(_) => {
+                let mut layer = arc_layer.write().await;
                 let (sid, _fid) = layer.get_running_remote_id(&mgid, &fgid)?;
                 if layer.running_mut(&mgid)?.suspend(&fgid, false, false)? {
                     results.rpcs.push(session_suspend(mgid, &sid));
                 }
             }
             LayerEvent::Actived(_) => {
+                let mut layer = arc_layer.write().await;
                 let (sid, _fid) = layer.get_running_remote_id(&mgid, &fgid)?;
                 let _ = layer.running_mut(&mgid)?.active(&fgid, false);
                 results.rpcs.push(session_connect(mgid, &sid, &addr));
             }
             LayerEvent::Request(name, remark, proof) => {
                 // 1. check verify.
-                proof.verify(&fgid, &addr, &layer.addr)?;
+                proof.verify(&fgid, &addr, &self_addr)?;
 
-                if load_friend(&layer.base, &mgid, &fgid).is_err() {
+                if load_friend(&base, &mgid, &fgid).is_err() {
                     // check if exist request.
-                    let db = chat_db(&layer.base, &mgid)?;
+                    let db = chat_db(&base, &mgid)?;
                     if let Ok(req) = Request::get_id(&db, &fgid) {
                         Request::delete(&db, &req.id)?; // delete the old request.
                         results.rpcs.push(rpc::request_delete(mgid, req.id));
@@ -241,18 +281,89 @@ impl LayerEvent {
                     results.rpcs.push(notice_menu(mgid, &SessionType::Chat));
                     return Ok(results);
                 } else {
-                    let proof = layer.group.read().await.prove_addr(&mgid, &addr)?;
+                    let proof = group.read().await.prove_addr(&mgid, &addr)?;
                     let msg = agree_message(proof, addr)?;
                     results.layers.push((mgid, fgid, msg));
                 }
             }
+            LayerEvent::RequestInvitation(name, remark, proof, token) => {
+                // 1. check verify.
+                proof.verify(&fgid, &addr, &self_addr)?;
+
+                if load_friend(&base, &mgid, &fgid).is_err() {
+                    // check if exist request.
+                    let db = chat_db(&base, &mgid)?;
+                    if let Ok(req) = Request::get_id(&db, &fgid) {
+                        Request::delete(&db, &req.id)?; // delete the old request.
+                        results.rpcs.push(rpc::request_delete(mgid, req.id));
+                    }
+                    let mut request = Request::new(fgid, addr, name, remark, false, true);
+
+                    // 2. check pre-authorised invitation.
+                    let invitation = Invitation::get_by_token(&db, &token)
+                        .ok()
+                        .filter(|i| i.is_valid());
+                    if invitation.is_none() {
+                        request.insert(&db)?;
+                        drop(db);
+
+                        results.rpcs.push(rpc::request_create(mgid, &request));
+                        results.rpcs.push(notice_menu(mgid, &SessionType::Chat));
+                        return Ok(results);
+                    }
+                    Invitation::used(&db, &invitation.unwrap().id)?; // safe.
+
+                    // 3. agree request automatically.
+                    request.insert(&db)?;
+                    let remark = request.remark.clone();
+                    let user =
+                        User::new(fgid, addr, request.name.clone(), vec![], "".to_owned(), 0);
+                    let mut group_lock = group.write().await;
+                    // sync the request and agreement to my other devices.
+                    group_lock.broadcast(
+                        &mgid,
+                        InnerEvent::SessionRequestCreate(false, user, remark),
+                        REQUEST_TABLE_PATH,
+                        request.id,
+                        &mut results,
+                    )?;
+                    group_lock.broadcast(
+                        &mgid,
+                        InnerEvent::SessionRequestHandle(fgid, true, vec![]),
+                        REQUEST_TABLE_PATH,
+                        request.id,
+                        &mut results,
+                    )?;
+                    drop(group_lock);
+                    request.is_ok = true;
+                    request.is_over = true;
+                    request.update(&db)?;
+                    let friend =
+                        Friend::from_remote(&db, fgid, request.name.clone(), addr, "".to_owned())?;
+                    drop(db);
+                    results.rpcs.push(rpc::request_create(mgid, &request));
+                    results
+                        .rpcs
+                        .push(rpc::request_agree(mgid, request.id, &friend));
+
+                    // ADD NEW SESSION.
+                    let s_db = session_db(&base, &mgid)?;
+                    let mut session = friend.to_session();
+                    session.insert(&s_db)?;
+                    results.rpcs.push(session_create(mgid, &session));
+                }
+
+                let proof = group.read().await.prove_addr(&mgid, &addr)?;
+                let msg = agree_message(proof, addr)?;
+                results.layers.push((mgid, fgid, msg));
+            }
             LayerEvent::Agree(proof) => {
                 // 0. check verify.
-                proof.verify(&fgid, &addr, &layer.addr)?;
+                proof.verify(&fgid, &addr, &self_addr)?;
                 // 1. check friendship.
-                if load_friend(&layer.base, &mgid, &fgid).is_err() {
+                if load_friend(&base, &mgid, &fgid).is_err() {
                     // 2. agree request for friend.
-                    let db = chat_db(&layer.base, &mgid)?;
+                    let db = chat_db(&base, &mgid)?;
                     if let Ok(mut r) = Request::get_id(&db, &fgid) {
                         r.is_over = true;
                         r.is_ok = true;
@@ -261,7 +372,7 @@ impl LayerEvent {
                         results.rpcs.push(rpc::request_agree(mgid, r.id, &friend));
 
                         // ADD NEW SESSION.
-                        let s_db = session_db(&layer.base, &mgid)?;
+                        let s_db = session_db(&base, &mgid)?;
                         let mut session = friend.to_session();
                         session.insert(&s_db)?;
                         results.rpcs.push(session_create(mgid, &session));
@@ -270,9 +381,9 @@ impl LayerEvent {
                 }
             }
             LayerEvent::Reject => {
-                let db = chat_db(&layer.base, &mgid)?;
+                let db = chat_db(&base, &mgid)?;
                 if let Ok(mut request) = Request::get_id(&db, &fgid) {
-                    layer.group.write().await.broadcast(
+                    group.write().await.broadcast(
                         &mgid,
                         InnerEvent::SessionRequestHandle(request.gid, false, vec![]),
                         REQUEST_TABLE_PATH,
@@ -287,20 +398,12 @@ impl LayerEvent {
                 drop(db);
             }
             LayerEvent::Message(hash, m) => {
-                let (_sid, fid) = layer.get_running_remote_id(&mgid, &fgid)?;
-                let db = chat_db(&layer.base, &mgid)?;
+                let (_sid, fid) = arc_layer.read().await.get_running_remote_id(&mgid, &fgid)?;
+                let db = chat_db(&base, &mgid)?;
                 if !Message::exist(&db, &hash)? {
-                    let msg = handle_nmsg(
-                        m.clone(),
-                        false,
-                        mgid,
-                        &layer.base,
-                        &db,
-                        fid,
-                        hash,
-                        &mut results,
-                    )?;
-                    layer.group.write().await.broadcast(
+                    let msg =
+                        handle_nmsg(m.clone(), false, mgid, &base, &db, fid, hash, &mut results)?;
+                    group.write().await.broadcast(
                         &mgid,
                         InnerEvent::SessionMessageCreate(fgid, false, hash, m),
                         MESSAGE_TABLE_PATH,
@@ -310,12 +413,12 @@ impl LayerEvent {
                     results.rpcs.push(rpc::message_create(mgid, &msg));
 
                     // UPDATE SESSION.
-                    update_session(&layer.base, &mgid, &fid, &msg, &mut results);
+                    update_session(&base, &mgid, &fid, &msg, &mut results);
                 }
             }
             LayerEvent::InfoReq(height) => {
                 // check sync remote height.
-                if let Ok(account) = Account::get(&account_db(layer.base())?, &mgid) {
+                if let Ok(account) = Account::get(&account_db(&base)?, &mgid) {
                     if account.pub_height > height {
                         let info = LayerEvent::InfoRes(User::info(
                             account.name,
@@ -326,13 +429,18 @@ impl LayerEvent {
                         let data = bincode::serialize(&info).unwrap_or(vec![]);
                         let msg = SendType::Event(0, addr, data);
                         results.layers.push((mgid, fgid, msg));
+
+                        let profile = LayerEvent::InfoProfile(account.profile);
+                        let data = bincode::serialize(&profile).unwrap_or(vec![]);
+                        let msg = SendType::Event(0, addr, data);
+                        results.layers.push((mgid, fgid, msg));
                     }
                 }
             }
             LayerEvent::InfoRes(remote) => {
-                let (sid, fid) = layer.get_running_remote_id(&mgid, &fgid)?;
+                let (sid, fid) = arc_layer.read().await.get_running_remote_id(&mgid, &fgid)?;
                 let avatar = remote.avatar.clone();
-                let db = chat_db(&layer.base, &mgid)?;
+                let db = chat_db(&base, &mgid)?;
                 let mut f = Friend::get(&db, &fid)?;
                 let name = remote.name.clone();
                 f.name = remote.name;
@@ -340,12 +448,12 @@ impl LayerEvent {
                 f.height = remote.height;
                 f.remote_update(&db)?;
                 drop(db);
-                write_avatar_sync(&layer.base, &mgid, &remote.id, remote.avatar)?;
+                write_avatar_sync(&base, &mgid, &remote.id, remote.avatar)?;
                 results.rpcs.push(rpc::friend_info(mgid, &f));
-                let _ = Session::update_name(&session_db(&layer.base, &mgid)?, &sid, &name);
+                let _ = Session::update_name(&session_db(&base, &mgid)?, &sid, &name);
                 results.rpcs.push(session_update_name(mgid, &sid, &name));
 
-                layer.group.write().await.broadcast(
+                group.write().await.broadcast(
                     &mgid,
                     InnerEvent::SessionFriendInfo(f.gid, f.addr, f.name.clone(), avatar),
                     FRIEND_TABLE_PATH,
@@ -354,25 +462,96 @@ impl LayerEvent {
                 )?;
             }
             LayerEvent::Close => {
-                let (_sid, fid) = layer.get_running_remote_id(&mgid, &fgid)?;
-                layer.group.write().await.broadcast(
+                let (_sid, fid) = arc_layer.read().await.get_running_remote_id(&mgid, &fgid)?;
+                group.write().await.broadcast(
                     &mgid,
                     InnerEvent::SessionFriendClose(fgid),
                     FRIEND_TABLE_PATH,
                     fid,
                     &mut results,
                 )?;
-                layer.remove_online(&mgid, &fgid);
-                let db = chat_db(&layer.base, &mgid)?;
+                arc_layer.write().await.remove_online(&mgid, &fgid);
+                let db = chat_db(&base, &mgid)?;
                 Friend::id_close(&db, fid)?;
                 drop(db);
                 results.rpcs.push(rpc::friend_close(mgid, fid));
-                if !layer.is_addr_online(&addr) {
+                if !arc_layer.read().await.is_addr_online(&addr) {
                     results
                         .layers
                         .push((mgid, fgid, SendType::Disconnect(addr)))
                 }
             }
+            LayerEvent::InfoProfile(profile) => {
+                let (_sid, fid) = arc_layer.read().await.get_running_remote_id(&mgid, &fgid)?;
+                let db = chat_db(&base, &mgid)?;
+                let mut f = Friend::get(&db, &fid)?;
+                f.profile = profile;
+                f.remote_update(&db)?;
+                drop(db);
+                results.rpcs.push(rpc::friend_info(mgid, &f));
+
+                group.write().await.broadcast(
+                    &mgid,
+                    InnerEvent::SessionFriendProfile(f.gid, f.profile),
+                    FRIEND_TABLE_PATH,
+                    f.id,
+                    &mut results,
+                )?;
+            }
+            LayerEvent::RecoveryShare(lang, threshold, setup, holders, share) => {
+                // only hold friend's share, which I am one of the holders.
+                let f = load_friend(&base, &mgid, &fgid)?;
+                if !is_setup(&setup) || !holders.contains(&mgid) {
+                    return Err(anyhow!("recovery share invalid"));
+                }
+                // the share only can be read by my account.
+                let key = group.read().await.derive_key(&mgid, RECOVERY_KEY_CONTEXT)?;
+                let share = encrypt_with_key(&key, &share)?;
+                let db = chat_db(&base, &mgid)?;
+                let mut rshare = RecoveryShare::new(
+                    fgid,
+                    GroupId::default(),
+                    lang,
+                    threshold,
+                    setup,
+                    holders,
+                    share,
+                    false,
+                );
+                rshare.insert(&db)?;
+                drop(db);
+                results.rpcs.push(rpc::recovery_share(mgid, f.id, &rshare));
+            }
+            LayerEvent::RecoveryAsk(owner) => {
+                // the holder need check out-of-band, and release it manually.
+                let db = chat_db(&base, &mgid)?;
+                if let Ok(rshare) = RecoveryShare::get_hold(&db, &owner) {
+                    results
+                        .rpcs
+                        .push(rpc::recovery_ask(mgid, &fgid, &addr, &rshare));
+                }
+                drop(db);
+            }
+            LayerEvent::RecoveryReply(owner, lang, threshold, setup, holders, share) => {
+                // only accept the holder which I asked, and listed in the setup.
+                if !is_setup(&setup) || !holders.contains(&fgid) {
+                    return Err(anyhow!("recovery share invalid"));
+                }
+                let key = group.read().await.derive_key(&mgid, RECOVERY_KEY_CONTEXT)?;
+                let db = chat_db(&base, &mgid)?;
+                let mut rshare = RecoveryShare::get_collect(&db, &owner, &fgid)?;
+                rshare.lang = lang;
+                rshare.threshold = threshold;
+                rshare.setup = setup;
+                rshare.holders = holders;
+                rshare.share = encrypt_with_key(&key, &share)?;
+                rshare.collected(&db)?;
+                let collected = RecoveryShare::list_collect(&db, &owner)?.len();
+                drop(db);
+                results
+                    .rpcs
+                    .push(rpc::recovery_collect(mgid, &rshare, collected));
+            }
         }
 
         Ok(results)
@@ -402,15 +581,37 @@ fn load_friend(base: &PathBuf, mgid: &GroupId, fgid: &GroupId) -> Result<Friend>
 }
 
 #[inline]
+/// the addr is friend's device, it changes when friend use other device.
 fn update_friend(base: &PathBuf, mgid: &GroupId, fgid: &GroupId, addr: &PeerId) -> Result<Friend> {
     let db = chat_db(base, mgid)?;
-    let friend = Friend::get_id(&db, fgid)?;
+    let mut friend = Friend::get_id(&db, fgid)?;
     if &friend.addr != addr {
         let _ = Friend::addr_update(&db, friend.id, addr);
+        friend.addr = *addr;
     }
     Ok(friend)
 }
 
+/// the proof not match the verified friend's identity key, warning the user.
+fn key_changed(
+    base: &PathBuf,
+    mgid: &GroupId,
+    fgid: &GroupId,
+    addr: &PeerId,
+    results: &mut HandleResult,
+) {
+    if let Ok(friend) = chat_db(base, mgid).and_then(|db| Friend::get_id(&db, fgid)) {
+        if friend.is_verified {
+            results.rpcs.push(rpc::friend_key_changed(
+                *mgid,
+                friend.id,
+                &friend.addr,
+                addr,
+            ));
+        }
+    }
+}
+
 pub(super) fn req_message(
     layer: &mut Layer,
     gid: GroupId,
@@ -426,6 +627,22 @@ pub(super) fn req_message(
     SendType::Event(uid, request.addr, data)
 }
 
+pub(super) fn req_invitation_message(
+    layer: &mut Layer,
+    gid: GroupId,
+    name: String,
+    proof: Proof,
+    request: Request,
+    token: String,
+) -> SendType {
+    // update delivery.
+    let uid = layer.delivery.len() as u64 + 1;
+    layer.delivery.insert(uid, (gid, request.id));
+    let req = LayerEvent::RequestInvitation(name, request.remark, proof, token);
+    let data = bincode::serialize(&req).unwrap_or(vec![]);
+    SendType::Event(uid, request.addr, data)
+}
+
 pub(super) fn reject_message(layer: &mut Layer, tid: i64, addr: PeerId, gid: GroupId) -> SendType {
     let data = bincode::serialize(&LayerEvent::Reject).unwrap_or(vec![]);
     let uid = layer.delivery.len() as u64 + 1;
@@ -477,17 +694,23 @@ pub(crate) fn update_session(
     };
 
     if let Ok(s_db) = session_db(base, gid) {
-        if let Ok(sid) = Session::last(
+        if let Ok(s) = Session::last(
             &s_db,
             id,
             &SessionType::Chat,
             &msg.datetime,
             &scontent,
-            true,
+            msg.is_me,
+            true, // friend's message always mentioned me.
         ) {
-            results
-                .rpcs
-                .push(session_last(*gid, &sid, &msg.datetime, &scontent, false));
+            results.rpcs.push(session_last(
+                *gid,
+                &s.id,
+                &msg.datetime,
+                &scontent,
+                s.last_readed,
+                s.unread,
+            ));
         }
     }
 }
@@ -7,6 +7,7 @@ pub(crate) use layer::LayerEvent;
 pub(crate) use layer::{chat_conn, event_message, update_session};
 pub(crate) use models::{
     from_model, from_network_message, handle_nmsg, raw_to_network_message, to_network_message,
-    Friend, InviteType, Message, Request,
+    Friend, Invitation, InvitationCode, InviteType, Label, Message, RecoveryHolder, RecoveryShare,
+    Request,
 };
 pub(crate) use rpc::new_rpc_handler;
@@ -1,9 +1,15 @@
 mod friend;
+mod invitation;
+mod label;
 mod message;
+mod recovery;
 mod request;
 
 pub(crate) use self::friend::Friend;
+pub(crate) use self::invitation::{Invitation, InvitationCode};
+pub(crate) use self::label::Label;
 pub(crate) use self::message::{from_model, handle_nmsg, Message};
+pub(crate) use self::recovery::{is_setup, RecoveryHolder, RecoveryShare, RECOVERY_KEY_CONTEXT};
 pub(crate) use self::request::Request;
 
 use chat_types::{MessageType, NetworkMessage};
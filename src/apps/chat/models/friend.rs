@@ -6,9 +6,10 @@ use tdn::types::{
 };
 use tdn_storage::local::{DStorage, DsValue};
 
+use crate::account::{escape, Profile};
 use crate::session::{Session, SessionType};
 
-use super::Message;
+use super::{Label, Message};
 
 pub(crate) struct Friend {
     pub id: i64,
@@ -20,6 +21,10 @@ pub(crate) struct Friend {
     pub remark: String,
     pub is_closed: bool,
     pub datetime: i64,
+    /// safety number had been checked out-of-band.
+    pub is_verified: bool,
+    /// friend's public profile.
+    pub profile: Profile,
 }
 
 impl Friend {
@@ -47,6 +52,8 @@ impl Friend {
             remark,
             datetime,
             is_closed: false,
+            is_verified: false,
+            profile: Profile::default(),
         }
     }
 
@@ -57,6 +64,8 @@ impl Friend {
     /// here is zero-copy and unwrap is safe.
     fn from_values(mut v: Vec<DsValue>) -> Friend {
         Friend {
+            profile: Profile::from_values(&mut v),
+            is_verified: v.pop().unwrap().as_bool(),
             datetime: v.pop().unwrap().as_i64(),
             is_closed: v.pop().unwrap().as_bool(),
             remark: v.pop().unwrap().as_string(),
@@ -110,7 +119,12 @@ impl Friend {
             self.wallet,
             self.remark,
             self.is_closed,
-            self.datetime
+            self.datetime,
+            self.is_verified,
+            self.profile.bio,
+            self.profile.status,
+            self.profile.status_expire,
+            self.profile.domain
         ])
     }
 
@@ -124,12 +138,17 @@ impl Friend {
             self.remark,
             self.is_closed,
             self.datetime,
-            online
+            online,
+            self.is_verified,
+            self.profile.bio,
+            self.profile.status,
+            self.profile.status_expire,
+            self.profile.domain
         ])
     }
 
     pub fn get_id(db: &DStorage, gid: &GroupId) -> Result<Friend> {
-        let sql = format!("SELECT id, gid, addr, name, wallet, height, remark, is_closed, datetime FROM friends WHERE gid = '{}'", gid.to_hex());
+        let sql = format!("SELECT id, gid, addr, name, wallet, height, remark, is_closed, datetime, is_verified, bio, status, status_expire, domain FROM friends WHERE gid = '{}'", gid.to_hex());
         let mut matrix = db.query(&sql)?;
         if matrix.len() > 0 {
             Ok(Friend::from_values(matrix.pop().unwrap())) // safe unwrap()
@@ -139,7 +158,7 @@ impl Friend {
     }
 
     pub fn get(db: &DStorage, id: &i64) -> Result<Friend> {
-        let sql = format!("SELECT id, gid, addr, name, wallet, height, remark, is_closed, datetime FROM friends WHERE id = {}", id);
+        let sql = format!("SELECT id, gid, addr, name, wallet, height, remark, is_closed, datetime, is_verified, bio, status, status_expire, domain FROM friends WHERE id = {}", id);
         let mut matrix = db.query(&sql)?;
         if matrix.len() > 0 {
             Ok(Friend::from_values(matrix.pop().unwrap())) // safe unwrap()
@@ -151,7 +170,7 @@ impl Friend {
     /// use in rpc when load account friends.
     pub fn list(db: &DStorage) -> Result<Vec<Friend>> {
         let matrix = db.query(
-            "SELECT id, gid, addr, name, wallet, height, remark, is_closed, datetime FROM friends",
+            "SELECT id, gid, addr, name, wallet, height, remark, is_closed, datetime, is_verified, bio, status, status_expire, domain FROM friends",
         )?;
         let mut friends = vec![];
         for values in matrix {
@@ -160,6 +179,17 @@ impl Friend {
         Ok(friends)
     }
 
+    /// use in rpc when load friends in the label.
+    pub fn list_by_label(db: &DStorage, lid: &i64) -> Result<Vec<Friend>> {
+        let sql = format!("SELECT friends.id, friends.gid, friends.addr, friends.name, friends.wallet, friends.height, friends.remark, friends.is_closed, friends.datetime, friends.is_verified, friends.bio, friends.status, friends.status_expire, friends.domain FROM friends INNER JOIN friend_labels ON friend_labels.fid = friends.id WHERE friend_labels.lid = {}", lid);
+        let matrix = db.query(&sql)?;
+        let mut friends = vec![];
+        for values in matrix {
+            friends.push(Friend::from_values(values));
+        }
+        Ok(friends)
+    }
+
     pub fn insert(&mut self, db: &DStorage) -> Result<()> {
         let sql = format!("INSERT INTO friends (gid, addr, name, wallet, height, remark, is_closed, datetime) VALUES ('{}', '{}', '{}', '{}', {}, '{}', {}, {})",
             self.gid.to_hex(),
@@ -208,16 +238,25 @@ impl Friend {
 
     pub fn remote_update(&self, db: &DStorage) -> Result<usize> {
         let sql = format!(
-            "UPDATE friends SET addr='{}', name='{}', wallet='{}', height={}, is_closed = false WHERE id = {}",
+            "UPDATE friends SET addr='{}', name='{}', wallet='{}', height={}, {}, is_closed = false WHERE id = {}",
             self.addr.to_hex(),
-            self.name,
-            self.wallet,
+            escape(&self.name),
+            escape(&self.wallet),
             self.height,
+            self.profile.to_sql(),
             self.id,
         );
         db.update(&sql)
     }
 
+    pub fn verify(db: &DStorage, id: &i64, is_verified: bool) -> Result<usize> {
+        let sql = format!(
+            "UPDATE friends SET is_verified = {} WHERE id = {}",
+            is_verified, id
+        );
+        db.update(&sql)
+    }
+
     /// used in rpc, when what to delete a friend.
     pub fn close(&self, db: &DStorage) -> Result<usize> {
         let sql = format!("UPDATE friends SET is_closed = true WHERE id = {}", self.id);
@@ -231,6 +270,9 @@ impl Friend {
 
         // TODO delete friend avatar.
 
+        // delete from labels.
+        Label::remove_friend_all(&db, id)?;
+
         // delete messages;
         Message::delete_by_fid(&db, id)
     }
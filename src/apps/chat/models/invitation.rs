@@ -0,0 +1,244 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdn::types::{
+    group::GroupId,
+    primitive::{PeerId, Result},
+    rpc::{json, RpcParam},
+};
+use tdn_did::Proof;
+use tdn_storage::local::{DStorage, DsValue};
+
+/// invitation code's deep link prefix.
+const INVITATION_PREFIX: &str = "esse://invite/";
+/// invitation token's length, 16 random bytes in hex.
+const TOKEN_LENGTH: usize = 32;
+
+#[inline]
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|s| s.as_secs())
+        .unwrap_or(0) as i64 // safe for all life.
+}
+
+/// Invitation code, send to other one (text or QR).
+/// the proof is signed by gid's keypair for the addr and the whole payload's digest.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct InvitationCode {
+    pub gid: GroupId,
+    pub addr: PeerId,
+    pub name: String,
+    /// expire time, 0 is never expired.
+    pub expire: i64,
+    /// invitation token, checked by the inviter when request.
+    pub token: String,
+    pub proof: Proof,
+}
+
+impl InvitationCode {
+    /// the digest of the payload, which the proof signed.
+    pub fn digest(gid: &GroupId, addr: &PeerId, name: &str, expire: i64, token: &str) -> PeerId {
+        let bytes = bincode::serialize(&(gid, addr, name, expire, token)).unwrap_or(vec![]);
+        PeerId(*blake3::hash(&bytes).as_bytes())
+    }
+
+    pub fn to_code(&self) -> String {
+        let bytes = bincode::serialize(self).unwrap_or(vec![]);
+        format!(
+            "{}{}",
+            INVITATION_PREFIX,
+            base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    /// parse the code (with or without deep link prefix), and check it.
+    pub fn from_code(code: &str) -> Result<InvitationCode> {
+        let code = code.trim();
+        let code = code.strip_prefix(INVITATION_PREFIX).unwrap_or(code);
+        let bytes = base64::decode_config(code, base64::URL_SAFE_NO_PAD)
+            .or(Err(anyhow!("invitation code invalid.")))?;
+        let invitation: InvitationCode =
+            bincode::deserialize(&bytes).or(Err(anyhow!("invitation code invalid.")))?;
+
+        if invitation.expire > 0 && invitation.expire < now() {
+            return Err(anyhow!("invitation code expired."));
+        }
+        if !is_token(&invitation.token) {
+            return Err(anyhow!("invitation code invalid."));
+        }
+        let digest = InvitationCode::digest(
+            &invitation.gid,
+            &invitation.addr,
+            &invitation.name,
+            invitation.expire,
+            &invitation.token,
+        );
+        invitation
+            .proof
+            .verify(&invitation.gid, &invitation.addr, &digest)?;
+        Ok(invitation)
+    }
+}
+
+/// the token is 16 random bytes in hex, as generated in `Invitation::new`.
+pub(crate) fn is_token(token: &str) -> bool {
+    token.len() == TOKEN_LENGTH && token.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Invitation which I generated, use to pre-authorise the request.
+pub(crate) struct Invitation {
+    pub id: i64,
+    pub token: String,
+    /// expire time, 0 is never expired.
+    pub expire: i64,
+    /// only can be used once.
+    pub is_once: bool,
+    /// the request with this token will be agreed automatically.
+    pub is_auto: bool,
+    /// used times.
+    pub used: i64,
+    pub datetime: i64,
+}
+
+impl Invitation {
+    pub fn new(expire: i64, is_once: bool, is_auto: bool) -> Invitation {
+        let token = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+
+        Invitation {
+            id: 0,
+            token,
+            expire,
+            is_once,
+            is_auto,
+            used: 0,
+            datetime: now(),
+        }
+    }
+
+    /// here is zero-copy and unwrap is safe. checked.
+    fn from_values(mut v: Vec<DsValue>) -> Invitation {
+        Invitation {
+            datetime: v.pop().unwrap().as_i64(),
+            used: v.pop().unwrap().as_i64(),
+            is_auto: v.pop().unwrap().as_bool(),
+            is_once: v.pop().unwrap().as_bool(),
+            expire: v.pop().unwrap().as_i64(),
+            token: v.pop().unwrap().as_string(),
+            id: v.pop().unwrap().as_i64(),
+        }
+    }
+
+    pub fn to_rpc(&self) -> RpcParam {
+        json!([
+            self.id,
+            self.token,
+            self.expire,
+            self.is_once,
+            self.is_auto,
+            self.used,
+            self.datetime
+        ])
+    }
+
+    /// check the invitation can agree request automatically.
+    pub fn is_valid(&self) -> bool {
+        self.is_auto
+            && (self.expire == 0 || self.expire >= now())
+            && !(self.is_once && self.used > 0)
+    }
+
+    /// the token is from remote, check it before query.
+    pub fn get_by_token(db: &DStorage, token: &str) -> Result<Invitation> {
+        if !is_token(token) {
+            return Err(anyhow!("invitation token invalid."));
+        }
+        let sql = format!("SELECT id, token, expire, is_once, is_auto, used, datetime FROM invitations WHERE token = '{}'", token);
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(Invitation::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("invitation is missing."))
+        }
+    }
+
+    pub fn list(db: &DStorage) -> Result<Vec<Invitation>> {
+        let matrix = db.query("SELECT id, token, expire, is_once, is_auto, used, datetime FROM invitations ORDER BY id DESC")?;
+        let mut invitations = vec![];
+        for values in matrix {
+            invitations.push(Invitation::from_values(values));
+        }
+        Ok(invitations)
+    }
+
+    pub fn insert(&mut self, db: &DStorage) -> Result<()> {
+        let sql = format!("INSERT INTO invitations (token, expire, is_once, is_auto, used, datetime) VALUES ('{}', {}, {}, {}, {}, {})",
+            self.token,
+            self.expire,
+            self.is_once,
+            self.is_auto,
+            self.used,
+            self.datetime,
+        );
+        self.id = db.insert(&sql)?;
+        Ok(())
+    }
+
+    pub fn used(db: &DStorage, id: &i64) -> Result<usize> {
+        let sql = format!("UPDATE invitations SET used = used + 1 WHERE id = {}", id);
+        db.update(&sql)
+    }
+
+    pub fn delete(db: &DStorage, id: &i64) -> Result<usize> {
+        let sql = format!("DELETE FROM invitations WHERE id = {}", id);
+        db.delete(&sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db(name: &str) -> DStorage {
+        let mut path = std::env::temp_dir();
+        path.push(format!("esse-test-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = DStorage::open(path).unwrap();
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS invitations(
+              id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+              token TEXT NOT NULL,
+              expire INTEGER NOT NULL,
+              is_once INTEGER NOT NULL,
+              is_auto INTEGER NOT NULL,
+              used INTEGER NOT NULL,
+              datetime INTEGER NOT NULL);",
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn token_format() {
+        let invitation = Invitation::new(0, true, true);
+        assert!(is_token(&invitation.token));
+        assert!(!is_token(""));
+        assert!(!is_token(&invitation.token[1..]));
+        assert!(!is_token(&format!("{}0", invitation.token)));
+        assert!(!is_token("x' OR is_auto=1 --xxxxxxxxxxxxxxx"));
+    }
+
+    #[test]
+    fn token_injection_refused() {
+        let db = test_db("invitation");
+        let mut invitation = Invitation::new(0, false, true);
+        invitation.insert(&db).unwrap();
+
+        let got = Invitation::get_by_token(&db, &invitation.token).unwrap();
+        assert_eq!(got.id, invitation.id);
+        assert!(got.is_valid());
+
+        assert!(Invitation::get_by_token(&db, "x' OR is_auto=1 --").is_err());
+        assert!(Invitation::get_by_token(&db, "' OR '1'='1").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdn::types::{
+    group::GroupId,
+    primitive::Result,
+    rpc::{json, RpcParam},
+};
+use tdn_storage::local::{DStorage, DsValue};
+
+/// Friend's label (contact group), such as "team", "family".
+pub(crate) struct Label {
+    pub id: i64,
+    /// label's name, unique in all devices.
+    pub name: String,
+    /// deleted label is kept as tombstone, so the deletion can sync to other devices.
+    pub is_deleted: bool,
+    pub datetime: i64,
+}
+
+/// label's name is from user and other devices, escape it in SQL.
+fn escape(name: &str) -> String {
+    name.replace("'", "''")
+}
+
+impl Label {
+    pub fn new(name: String) -> Label {
+        let start = SystemTime::now();
+        let datetime = start
+            .duration_since(UNIX_EPOCH)
+            .map(|s| s.as_secs())
+            .unwrap_or(0) as i64; // safe for all life.
+
+        Label {
+            id: 0,
+            name,
+            is_deleted: false,
+            datetime,
+        }
+    }
+
+    /// here is zero-copy and unwrap is safe. checked.
+    fn from_values(mut v: Vec<DsValue>) -> Label {
+        Label {
+            datetime: v.pop().unwrap().as_i64(),
+            is_deleted: v.pop().unwrap().as_bool(),
+            name: v.pop().unwrap().as_string(),
+            id: v.pop().unwrap().as_i64(),
+        }
+    }
+
+    pub fn to_rpc(&self, friends: Vec<i64>) -> RpcParam {
+        json!([self.id, self.name, friends, self.datetime])
+    }
+
+    pub fn get(db: &DStorage, id: &i64) -> Result<Label> {
+        let sql = format!(
+            "SELECT id, name, is_deleted, datetime FROM labels WHERE id = {}",
+            id
+        );
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(Label::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("label is missing."))
+        }
+    }
+
+    pub fn get_by_name(db: &DStorage, name: &str) -> Result<Label> {
+        let sql = format!(
+            "SELECT id, name, is_deleted, datetime FROM labels WHERE name = '{}' AND is_deleted = false",
+            escape(name)
+        );
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(Label::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("label is missing."))
+        }
+    }
+
+    pub fn list(db: &DStorage) -> Result<Vec<Label>> {
+        let matrix =
+            db.query("SELECT id, name, is_deleted, datetime FROM labels WHERE is_deleted = false")?;
+        let mut labels = vec![];
+        for values in matrix {
+            labels.push(Label::from_values(values));
+        }
+        Ok(labels)
+    }
+
+    /// insert new label, if name exists, use the old one (restore it if deleted).
+    pub fn insert(&mut self, db: &DStorage) -> Result<()> {
+        let mut unique_check = db.query(&format!(
+            "SELECT id from labels WHERE name = '{}'",
+            escape(&self.name)
+        ))?;
+        if unique_check.len() > 0 {
+            self.id = unique_check.pop().unwrap().pop().unwrap().as_i64();
+            db.update(&format!(
+                "UPDATE labels SET is_deleted = false WHERE id = {}",
+                self.id
+            ))?;
+        } else {
+            let sql = format!(
+                "INSERT INTO labels (name, is_deleted, datetime) VALUES ('{}', false, {})",
+                escape(&self.name),
+                self.datetime,
+            );
+            self.id = db.insert(&sql)?;
+        }
+        Ok(())
+    }
+
+    /// delete the label's friends, and keep the label as tombstone.
+    pub fn delete(db: &DStorage, id: &i64) -> Result<usize> {
+        db.delete(&format!("DELETE FROM friend_labels WHERE lid = {}", id))?;
+        db.update(&format!(
+            "UPDATE labels SET is_deleted = true WHERE id = {}",
+            id
+        ))
+    }
+
+    /// all friends's db id in this label.
+    pub fn friends(db: &DStorage, id: &i64) -> Result<Vec<i64>> {
+        let matrix = db.query(&format!("SELECT fid FROM friend_labels WHERE lid = {}", id))?;
+        let mut fids = vec![];
+        for mut values in matrix {
+            fids.push(values.pop().unwrap().as_i64()); // safe unwrap.
+        }
+        Ok(fids)
+    }
+
+    /// all friends's gid in this label, used in sync with other devices.
+    pub fn friends_gid(db: &DStorage, id: &i64) -> Result<Vec<GroupId>> {
+        let matrix = db.query(&format!(
+            "SELECT friends.gid FROM friend_labels INNER JOIN friends ON friends.id = friend_labels.fid WHERE friend_labels.lid = {}",
+            id
+        ))?;
+        let mut gids = vec![];
+        for mut values in matrix {
+            // safe unwrap. skip the invalid gid, not sync it.
+            if let Ok(gid) = GroupId::from_hex(values.pop().unwrap().as_str()) {
+                gids.push(gid);
+            }
+        }
+        Ok(gids)
+    }
+
+    pub fn add_friend(db: &DStorage, id: &i64, fid: &i64) -> Result<()> {
+        let unique_check = db.query(&format!(
+            "SELECT id from friend_labels WHERE lid = {} AND fid = {}",
+            id, fid
+        ))?;
+        if unique_check.len() == 0 {
+            db.insert(&format!(
+                "INSERT INTO friend_labels (lid, fid) VALUES ({}, {})",
+                id, fid
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// replace the label's friends, used when sync the full membership from other devices.
+    pub fn set_friends(db: &DStorage, id: &i64, fids: &[i64]) -> Result<()> {
+        for fid in Label::friends(db, id)? {
+            if !fids.contains(&fid) {
+                Label::remove_friend(db, id, &fid)?;
+            }
+        }
+        for fid in fids {
+            Label::add_friend(db, id, fid)?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_friend(db: &DStorage, id: &i64, fid: &i64) -> Result<usize> {
+        db.delete(&format!(
+            "DELETE FROM friend_labels WHERE lid = {} AND fid = {}",
+            id, fid
+        ))
+    }
+
+    /// when friend deleted, remove it from all labels.
+    pub fn remove_friend_all(db: &DStorage, fid: &i64) -> Result<usize> {
+        db.delete(&format!("DELETE FROM friend_labels WHERE fid = {}", fid))
+    }
+}
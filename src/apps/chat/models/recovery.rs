@@ -0,0 +1,385 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdn::types::{
+    group::GroupId,
+    primitive::Result,
+    rpc::{json, RpcParam},
+};
+use tdn_storage::local::{DStorage, DsValue};
+
+#[inline]
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|s| s.as_secs())
+        .unwrap_or(0) as i64 // safe for all life.
+}
+
+/// the context of the key which encrypt the shares stored in my device.
+pub(crate) const RECOVERY_KEY_CONTEXT: &str = "esse recovery share";
+
+/// the setup's id, 16 random bytes in hex. it is from remote, check it before store.
+pub(crate) fn is_setup(setup: &str) -> bool {
+    setup.len() == 32 && setup.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// My mnemonic's share holder (friend), the audit of social recovery.
+pub(crate) struct RecoveryHolder {
+    pub id: i64,
+    /// friend's db id.
+    pub fid: i64,
+    /// share's index (x).
+    pub index: i64,
+    pub threshold: i64,
+    pub total: i64,
+    pub datetime: i64,
+}
+
+impl RecoveryHolder {
+    pub fn new(fid: i64, index: i64, threshold: i64, total: i64) -> RecoveryHolder {
+        RecoveryHolder {
+            id: 0,
+            fid,
+            index,
+            threshold,
+            total,
+            datetime: now(),
+        }
+    }
+
+    /// here is zero-copy and unwrap is safe. checked.
+    fn from_values(mut v: Vec<DsValue>) -> RecoveryHolder {
+        RecoveryHolder {
+            datetime: v.pop().unwrap().as_i64(),
+            total: v.pop().unwrap().as_i64(),
+            threshold: v.pop().unwrap().as_i64(),
+            index: v.pop().unwrap().as_i64(),
+            fid: v.pop().unwrap().as_i64(),
+            id: v.pop().unwrap().as_i64(),
+        }
+    }
+
+    pub fn to_rpc(&self) -> RpcParam {
+        json!([
+            self.id,
+            self.fid,
+            self.index,
+            self.threshold,
+            self.total,
+            self.datetime
+        ])
+    }
+
+    pub fn list(db: &DStorage) -> Result<Vec<RecoveryHolder>> {
+        let matrix = db.query(
+            "SELECT id, fid, idx, threshold, total, datetime FROM recovery_holders ORDER BY idx",
+        )?;
+        let mut holders = vec![];
+        for values in matrix {
+            holders.push(RecoveryHolder::from_values(values));
+        }
+        Ok(holders)
+    }
+
+    pub fn insert(&mut self, db: &DStorage) -> Result<()> {
+        let sql = format!(
+            "INSERT INTO recovery_holders (fid, idx, threshold, total, datetime) VALUES ({}, {}, {}, {}, {})",
+            self.fid, self.index, self.threshold, self.total, self.datetime,
+        );
+        self.id = db.insert(&sql)?;
+        Ok(())
+    }
+
+    /// when setup new shares, old holders is useless.
+    pub fn clear(db: &DStorage) -> Result<usize> {
+        db.delete("DELETE FROM recovery_holders")
+    }
+}
+
+/// Share of other's mnemonic.
+/// when is_collect is false, I am the holder of friend's share,
+/// when is_collect is true, I collect the share from the holder for recovery.
+pub(crate) struct RecoveryShare {
+    pub id: i64,
+    /// the account which mnemonic be split.
+    pub owner: GroupId,
+    /// holder: the account which the share released to.
+    /// collect: the account which hold the share.
+    pub peer: GroupId,
+    /// mnemonic's language.
+    pub lang: i64,
+    pub threshold: i64,
+    /// share bytes (include index), encrypted by my account's key.
+    /// when collecting, it is empty until the holder released it.
+    pub share: Vec<u8>,
+    pub is_collect: bool,
+    pub datetime: i64,
+    /// the setup's id, all shares of the setup have same id.
+    pub setup: String,
+    /// all holders of the setup.
+    pub holders: Vec<GroupId>,
+}
+
+impl RecoveryShare {
+    pub fn new(
+        owner: GroupId,
+        peer: GroupId,
+        lang: i64,
+        threshold: i64,
+        setup: String,
+        holders: Vec<GroupId>,
+        share: Vec<u8>,
+        is_collect: bool,
+    ) -> RecoveryShare {
+        RecoveryShare {
+            id: 0,
+            owner,
+            peer,
+            lang,
+            threshold,
+            share,
+            is_collect,
+            datetime: now(),
+            setup,
+            holders,
+        }
+    }
+
+    fn holders_to_str(&self) -> String {
+        self.holders
+            .iter()
+            .map(|gid| gid.to_hex())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    /// here is zero-copy and unwrap is safe. checked.
+    fn from_values(mut v: Vec<DsValue>) -> RecoveryShare {
+        let holders = v
+            .pop()
+            .unwrap()
+            .as_str()
+            .split(',')
+            .filter_map(|s| GroupId::from_hex(s).ok())
+            .collect();
+        RecoveryShare {
+            holders,
+            setup: v.pop().unwrap().as_string(),
+            datetime: v.pop().unwrap().as_i64(),
+            is_collect: v.pop().unwrap().as_bool(),
+            share: hex::decode(v.pop().unwrap().as_str()).unwrap_or(vec![]),
+            threshold: v.pop().unwrap().as_i64(),
+            lang: v.pop().unwrap().as_i64(),
+            peer: GroupId::from_hex(v.pop().unwrap().as_str()).unwrap_or(GroupId::default()),
+            owner: GroupId::from_hex(v.pop().unwrap().as_str()).unwrap_or(GroupId::default()),
+            id: v.pop().unwrap().as_i64(),
+        }
+    }
+
+    /// share bytes never send to UI.
+    pub fn to_rpc(&self) -> RpcParam {
+        json!([
+            self.id,
+            self.owner.to_hex(),
+            self.peer.to_hex(),
+            self.threshold,
+            self.is_collect,
+            self.datetime
+        ])
+    }
+
+    pub fn get(db: &DStorage, id: &i64) -> Result<RecoveryShare> {
+        let sql = format!("SELECT id, owner, peer, lang, threshold, share, is_collect, datetime, setup, holders FROM recovery_shares WHERE id = {}", id);
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(RecoveryShare::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("recovery share is missing."))
+        }
+    }
+
+    /// the share which I hold for the owner.
+    pub fn get_hold(db: &DStorage, owner: &GroupId) -> Result<RecoveryShare> {
+        let sql = format!("SELECT id, owner, peer, lang, threshold, share, is_collect, datetime, setup, holders FROM recovery_shares WHERE owner = '{}' AND is_collect = false", owner.to_hex());
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(RecoveryShare::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("recovery share is missing."))
+        }
+    }
+
+    pub fn list(db: &DStorage, is_collect: bool) -> Result<Vec<RecoveryShare>> {
+        let sql = format!("SELECT id, owner, peer, lang, threshold, share, is_collect, datetime, setup, holders FROM recovery_shares WHERE is_collect = {}", is_collect);
+        let matrix = db.query(&sql)?;
+        let mut shares = vec![];
+        for values in matrix {
+            shares.push(RecoveryShare::from_values(values));
+        }
+        Ok(shares)
+    }
+
+    /// the collecting share which I asked the holder.
+    pub fn get_collect(db: &DStorage, owner: &GroupId, peer: &GroupId) -> Result<RecoveryShare> {
+        let sql = format!("SELECT id, owner, peer, lang, threshold, share, is_collect, datetime, setup, holders FROM recovery_shares WHERE owner = '{}' AND peer = '{}' AND is_collect = true", owner.to_hex(), peer.to_hex());
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(RecoveryShare::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("recovery share is missing."))
+        }
+    }
+
+    /// all collected shares of the owner, which released by holders.
+    pub fn list_collect(db: &DStorage, owner: &GroupId) -> Result<Vec<RecoveryShare>> {
+        let sql = format!("SELECT id, owner, peer, lang, threshold, share, is_collect, datetime, setup, holders FROM recovery_shares WHERE owner = '{}' AND is_collect = true AND share != ''", owner.to_hex());
+        let matrix = db.query(&sql)?;
+        let mut shares = vec![];
+        for values in matrix {
+            shares.push(RecoveryShare::from_values(values));
+        }
+        Ok(shares)
+    }
+
+    /// insert the share, if had same owner & peer, replace it.
+    pub fn insert(&mut self, db: &DStorage) -> Result<()> {
+        let sql = if self.is_collect {
+            format!(
+                "DELETE FROM recovery_shares WHERE owner = '{}' AND peer = '{}' AND is_collect = true",
+                self.owner.to_hex(),
+                self.peer.to_hex()
+            )
+        } else {
+            format!(
+                "DELETE FROM recovery_shares WHERE owner = '{}' AND is_collect = false",
+                self.owner.to_hex()
+            )
+        };
+        db.delete(&sql)?;
+
+        let sql = format!("INSERT INTO recovery_shares (owner, peer, lang, threshold, share, is_collect, datetime, setup, holders) VALUES ('{}', '{}', {}, {}, '{}', {}, {}, '{}', '{}')",
+            self.owner.to_hex(),
+            self.peer.to_hex(),
+            self.lang,
+            self.threshold,
+            hex::encode(&self.share),
+            self.is_collect,
+            self.datetime,
+            self.setup,
+            self.holders_to_str(),
+        );
+        self.id = db.insert(&sql)?;
+        Ok(())
+    }
+
+    /// the holder released the share which I asked.
+    pub fn collected(&self, db: &DStorage) -> Result<usize> {
+        let sql = format!("UPDATE recovery_shares SET lang = {}, threshold = {}, share = '{}', setup = '{}', holders = '{}' WHERE id = {}",
+            self.lang,
+            self.threshold,
+            hex::encode(&self.share),
+            self.setup,
+            self.holders_to_str(),
+            self.id,
+        );
+        db.update(&sql)
+    }
+
+    /// holder released the share to the peer.
+    pub fn release(db: &DStorage, id: &i64, peer: &GroupId) -> Result<usize> {
+        let sql = format!(
+            "UPDATE recovery_shares SET peer = '{}' WHERE id = {}",
+            peer.to_hex(),
+            id
+        );
+        db.update(&sql)
+    }
+
+    /// check the collected shares are from one setup's different holders, and enough to combine.
+    pub fn check_collect(shares: &[RecoveryShare]) -> Result<()> {
+        let first = shares
+            .first()
+            .ok_or(anyhow!("recovery shares not enough."))?;
+        if first.threshold < 1 || first.threshold as usize > first.holders.len() {
+            return Err(anyhow!("recovery shares invalid."));
+        }
+        let mut peers = vec![];
+        for share in shares {
+            if share.setup != first.setup
+                || share.threshold != first.threshold
+                || share.lang != first.lang
+                || share.holders != first.holders
+                || !first.holders.contains(&share.peer)
+                || peers.contains(&share.peer)
+            {
+                return Err(anyhow!("recovery shares invalid."));
+            }
+            peers.push(share.peer);
+        }
+        if (peers.len() as i64) < first.threshold {
+            return Err(anyhow!("recovery shares not enough."));
+        }
+        Ok(())
+    }
+
+    /// when recovery finished, delete all collected shares.
+    pub fn delete_collect(db: &DStorage, owner: &GroupId) -> Result<usize> {
+        let sql = format!(
+            "DELETE FROM recovery_shares WHERE owner = '{}' AND is_collect = true",
+            owner.to_hex()
+        );
+        db.delete(&sql)
+    }
+
+    pub fn delete(db: &DStorage, id: &i64) -> Result<usize> {
+        let sql = format!("DELETE FROM recovery_shares WHERE id = {}", id);
+        db.delete(&sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collected(peer: u8, setup: &str, holders: &[GroupId]) -> RecoveryShare {
+        RecoveryShare::new(
+            GroupId([0u8; 32]),
+            GroupId([peer; 32]),
+            0,
+            2,
+            setup.to_owned(),
+            holders.to_vec(),
+            vec![peer],
+            true,
+        )
+    }
+
+    #[test]
+    fn collect_one_setup() {
+        let setup = "00112233445566778899aabbccddeeff";
+        let holders = vec![GroupId([1u8; 32]), GroupId([2u8; 32]), GroupId([3u8; 32])];
+        assert!(is_setup(setup));
+        assert!(!is_setup("x' OR 1=1 --"));
+
+        let ok = vec![collected(1, setup, &holders), collected(3, setup, &holders)];
+        assert!(RecoveryShare::check_collect(&ok).is_ok());
+
+        // below threshold.
+        assert!(RecoveryShare::check_collect(&ok[..1]).is_err());
+        assert!(RecoveryShare::check_collect(&[]).is_err());
+
+        // same holder twice.
+        let dup = vec![collected(1, setup, &holders), collected(1, setup, &holders)];
+        assert!(RecoveryShare::check_collect(&dup).is_err());
+
+        // not a holder of the setup.
+        let other = vec![collected(1, setup, &holders), collected(4, setup, &holders)];
+        assert!(RecoveryShare::check_collect(&other).is_err());
+
+        // different setups.
+        let mixed = vec![
+            collected(1, setup, &holders),
+            collected(2, "ffeeddccbbaa99887766554433221100", &holders),
+        ];
+        assert!(RecoveryShare::check_collect(&mixed).is_err());
+    }
+}
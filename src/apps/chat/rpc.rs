@@ -1,3 +1,4 @@
+use rand::Rng;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tdn::types::{
@@ -10,12 +11,22 @@ use tdn::types::{
 use chat_types::MessageType;
 
 use crate::event::InnerEvent;
-use crate::migrate::consensus::{FRIEND_TABLE_PATH, MESSAGE_TABLE_PATH, REQUEST_TABLE_PATH};
+use crate::migrate::consensus::{
+    FRIEND_TABLE_PATH, LABEL_TABLE_PATH, MESSAGE_TABLE_PATH, REQUEST_TABLE_PATH,
+};
+use crate::rpc::schema::{method, opt, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::rpc::{session_create, sleep_waiting_close_stable, RpcState};
 use crate::storage::{chat_db, delete_avatar, session_db};
+use crate::utils::crypto::{check_safety_qr, decrypt_with_key, safety_number, safety_qr};
+use crate::utils::shamir;
 
-use super::layer::{agree_message, reject_message, req_message, update_session, LayerEvent};
-use super::{Friend, Message, Request};
+use super::layer::{
+    agree_message, reject_message, req_invitation_message, req_message, update_session, LayerEvent,
+};
+use super::models::RECOVERY_KEY_CONTEXT;
+use super::{
+    Friend, Invitation, InvitationCode, Label, Message, RecoveryHolder, RecoveryShare, Request,
+};
 
 #[inline]
 pub(crate) fn friend_info(mgid: GroupId, friend: &Friend) -> RpcParam {
@@ -37,6 +48,16 @@ pub(crate) fn friend_delete(mgid: GroupId, fid: i64) -> RpcParam {
     rpc_response(0, "chat-friend-delete", json!([fid]), mgid)
 }
 
+#[inline]
+pub(crate) fn friend_key_changed(mgid: GroupId, fid: i64, old: &PeerId, new: &PeerId) -> RpcParam {
+    rpc_response(
+        0,
+        "chat-friend-key-changed",
+        json!([fid, old.to_hex(), new.to_hex()]),
+        mgid,
+    )
+}
+
 #[inline]
 pub(crate) fn request_create(mgid: GroupId, req: &Request) -> RpcParam {
     rpc_response(0, "chat-request-create", json!(req.to_rpc()), mgid)
@@ -77,6 +98,51 @@ pub(crate) fn message_delete(mgid: GroupId, id: i64) -> RpcParam {
     rpc_response(0, "chat-message-delete", json!([id]), mgid)
 }
 
+#[inline]
+pub(crate) fn label_create(mgid: GroupId, label: &Label, friends: Vec<i64>) -> RpcParam {
+    rpc_response(0, "chat-label-create", json!(label.to_rpc(friends)), mgid)
+}
+
+#[inline]
+pub(crate) fn label_delete(mgid: GroupId, id: i64) -> RpcParam {
+    rpc_response(0, "chat-label-delete", json!([id]), mgid)
+}
+
+#[inline]
+pub(crate) fn label_friend(mgid: GroupId, id: i64, fid: i64, is_add: bool) -> RpcParam {
+    rpc_response(0, "chat-label-friend", json!([id, fid, is_add]), mgid)
+}
+
+#[inline]
+pub(crate) fn recovery_share(mgid: GroupId, fid: i64, share: &RecoveryShare) -> RpcParam {
+    rpc_response(0, "chat-recovery-share", json!([fid, share.to_rpc()]), mgid)
+}
+
+#[inline]
+pub(crate) fn recovery_ask(
+    mgid: GroupId,
+    gid: &GroupId,
+    addr: &PeerId,
+    share: &RecoveryShare,
+) -> RpcParam {
+    rpc_response(
+        0,
+        "chat-recovery-ask",
+        json!([gid.to_hex(), addr.to_hex(), share.to_rpc()]),
+        mgid,
+    )
+}
+
+#[inline]
+pub(crate) fn recovery_collect(mgid: GroupId, share: &RecoveryShare, collected: usize) -> RpcParam {
+    rpc_response(
+        0,
+        "chat-recovery-collect",
+        json!([share.to_rpc(), collected]),
+        mgid,
+    )
+}
+
 #[inline]
 fn request_list(requests: Vec<Request>) -> RpcParam {
     let mut results = vec![];
@@ -105,18 +171,32 @@ fn detail_list(friend: Friend, messages: Vec<Message>) -> RpcParam {
 }
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method("chat-echo", |_, params, _| async move {
-        Ok(HandleResult::rpc(json!(params)))
-    });
+    rpc_method!(
+        handler,
+        method("chat-echo", "echo the params.", &[], "params"),
+        |_, params, _| async move { Ok(HandleResult::rpc(json!(params))) }
+    );
 
-    handler.add_method(
-        "chat-friend-list",
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-list",
+            "list the friends, with online status or filter by label.",
+            &[req("need_online", Bool), opt("label", Int)],
+            "[Friend]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let need_online = params[0].as_bool().ok_or(RpcError::ParseError)?;
+            // optional filter by label.
+            let label = params.get(1).and_then(|v| v.as_i64());
 
             let layer_lock = state.layer.read().await;
             let db = chat_db(&layer_lock.base, &gid)?;
-            let friends = Friend::list(&db)?;
+            let friends = if let Some(lid) = label {
+                Friend::list_by_label(&db, &lid)?
+            } else {
+                Friend::list(&db)?
+            };
 
             let mut results = vec![];
             if need_online {
@@ -135,8 +215,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-friend-update",
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-update",
+            "update the friend's remark.",
+            &[req("id", Int), req("remark", Str)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let remark = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -158,8 +244,79 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-friend-close",
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-safety",
+            "the safety number and QR of the friend.",
+            &[req("id", Int)],
+            "[id, number, qr, is_verified]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let f = Friend::get(&db, &id)?;
+            drop(db);
+
+            let number = safety_number(&gid, &f.gid);
+            let qr = safety_qr(&gid, &f.gid);
+            Ok(HandleResult::rpc(json!([id, number, qr, f.is_verified])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-verify",
+            "mark the friend verified or not.",
+            &[req("id", Int), req("is_verified", Bool)],
+            "Friend",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            let is_verified = params[1].as_bool().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let mut f = Friend::get(&db, &id)?;
+            Friend::verify(&db, &f.id, is_verified)?;
+            f.is_verified = is_verified;
+            drop(db);
+
+            Ok(HandleResult::rpc(f.to_rpc()))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-verify-qr",
+            "verify the friend by the scanned safety QR.",
+            &[req("qr", Str)],
+            "Friend",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let qr = params[0].as_str().ok_or(RpcError::ParseError)?;
+            let fgid = check_safety_qr(&gid, qr)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let mut f = Friend::get_id(&db, &fgid)?;
+            Friend::verify(&db, &f.id, true)?;
+            f.is_verified = true;
+            drop(db);
+
+            Ok(HandleResult::rpc(f.to_rpc()))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-close",
+            "close the friend.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -197,8 +354,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-friend-delete",
+    rpc_method!(
+        handler,
+        method(
+            "chat-friend-delete",
+            "delete the friend.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -237,8 +400,123 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-request-list",
+    rpc_method!(
+        handler,
+        method("chat-label-list", "list all labels.", &[], "[Label]"),
+        |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let labels = Label::list(&db)?;
+            let mut results = vec![];
+            for label in labels {
+                let friends = Label::friends(&db, &label.id)?;
+                results.push(label.to_rpc(friends));
+            }
+            drop(db);
+            Ok(HandleResult::rpc(json!(results)))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-label-create",
+            "create the label.",
+            &[req("name", Str)],
+            "Label",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let name = params[0].as_str().ok_or(RpcError::ParseError)?;
+            if name.len() == 0 {
+                return Err(RpcError::InvalidRequest);
+            }
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let mut label = Label::new(name.to_owned());
+            label.insert(&db)?;
+            let friends = Label::friends(&db, &label.id)?;
+            drop(db);
+
+            let mut results = HandleResult::rpc(label.to_rpc(friends));
+            state.group.write().await.broadcast(
+                &gid,
+                InnerEvent::LabelCreate(label.name),
+                LABEL_TABLE_PATH,
+                label.id,
+                &mut results,
+            )?;
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-label-delete",
+            "delete the label.",
+            &[req("id", Int)],
+            "[id]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let label = Label::get(&db, &id)?;
+            Label::delete(&db, &id)?;
+            drop(db);
+
+            let mut results = HandleResult::rpc(json!([id]));
+            state.group.write().await.broadcast(
+                &gid,
+                InnerEvent::LabelDelete(label.name),
+                LABEL_TABLE_PATH,
+                label.id,
+                &mut results,
+            )?;
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-label-friend",
+            "add/remove the friend to the label.",
+            &[req("id", Int), req("fid", Int), req("is_add", Bool)],
+            "[id, fid, is_add]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            let fid = params[1].as_i64().ok_or(RpcError::ParseError)?;
+            let is_add = params[2].as_bool().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let label = Label::get(&db, &id)?;
+            if label.is_deleted {
+                return Err(RpcError::Custom("Label is deleted!".to_owned()));
+            }
+            let friend = Friend::get(&db, &fid)?;
+            if is_add {
+                Label::add_friend(&db, &id, &fid)?;
+            } else {
+                Label::remove_friend(&db, &id, &fid)?;
+            }
+            drop(db);
+
+            let mut results = HandleResult::rpc(json!([id, fid, is_add]));
+            state.group.write().await.broadcast(
+                &gid,
+                InnerEvent::LabelFriend(label.name, friend.gid, is_add),
+                LABEL_TABLE_PATH,
+                label.id,
+                &mut results,
+            )?;
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method("chat-request-list", "list all requests.", &[], "[Request]"),
         |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let layer_lock = state.layer.read().await;
             let db = chat_db(layer_lock.base(), &gid)?;
@@ -249,8 +527,19 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-request-create",
+    rpc_method!(
+        handler,
+        method(
+            "chat-request-create",
+            "send the friend request.",
+            &[
+                req("gid", Hex),
+                req("addr", Hex),
+                req("name", Str),
+                req("remark", Str),
+            ],
+            "Request",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let remote_gid = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
             let remote_addr = PeerId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
@@ -300,8 +589,393 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-request-agree",
+    rpc_method!(
+        handler,
+        method(
+            "chat-invitation-list",
+            "list all invitations.",
+            &[],
+            "[Invitation]",
+        ),
+        |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let invitations = Invitation::list(&db)?;
+            drop(db);
+            let mut results = vec![];
+            for invitation in invitations {
+                results.push(invitation.to_rpc());
+            }
+            Ok(HandleResult::rpc(json!(results)))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-invitation-create",
+            "create the invitation code.",
+            &[
+                req("expire", Int),
+                req("is_once", Bool),
+                req("is_auto", Bool),
+            ],
+            "[Invitation, code]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let expire = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            let is_once = params[1].as_bool().ok_or(RpcError::ParseError)?;
+            let is_auto = params[2].as_bool().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let mut invitation = Invitation::new(expire, is_once, is_auto);
+            invitation.insert(&db)?;
+            drop(db);
+
+            let group_lock = state.group.read().await;
+            let addr = *group_lock.addr();
+            let name = group_lock.username(&gid)?;
+            let token = invitation.token.clone();
+            let digest = InvitationCode::digest(&gid, &addr, &name, expire, &token);
+            let proof = group_lock.prove_invitation(&gid, &digest)?;
+            drop(group_lock);
+            let code = InvitationCode {
+                gid,
+                addr,
+                name,
+                expire,
+                token,
+                proof,
+            };
+
+            Ok(HandleResult::rpc(json!([
+                invitation.to_rpc(),
+                code.to_code()
+            ])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-invitation-delete",
+            "delete the invitation.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            Invitation::delete(&db, &id)?;
+            drop(db);
+            Ok(HandleResult::new())
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-invitation-redeem",
+            "send the request by the invitation code.",
+            &[req("code", Str), req("remark", Str)],
+            "Request",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let code = params[0].as_str().ok_or(RpcError::ParseError)?;
+            let remark = params[1].as_str().ok_or(RpcError::ParseError)?.to_string();
+
+            let invitation = InvitationCode::from_code(code)?;
+            if invitation.gid == gid {
+                return Err(RpcError::InvalidRequest);
+            }
+
+            let mut request = Request::new(
+                invitation.gid,
+                invitation.addr,
+                invitation.name,
+                remark,
+                true,
+                false,
+            );
+
+            let group_lock = state.group.read().await;
+            let name = group_lock.username(&gid)?;
+            let proof = group_lock.prove_addr(&gid, &invitation.addr)?;
+            drop(group_lock);
+
+            let mut layer_lock = state.layer.write().await;
+            let db = chat_db(layer_lock.base(), &gid)?;
+            if Friend::is_friend(&db, &request.gid)? {
+                debug!("had friend.");
+                drop(layer_lock);
+                return Ok(HandleResult::new());
+            }
+
+            if let Ok(req) = Request::get_id(&db, &request.gid) {
+                debug!("Had this request.");
+                Request::delete(&db, &req.id)?;
+            }
+            request.insert(&db)?;
+            drop(db);
+
+            let mut results = HandleResult::rpc(json!(request.to_rpc()));
+
+            results.layers.push((
+                gid,
+                invitation.gid,
+                req_invitation_message(
+                    &mut layer_lock,
+                    gid,
+                    name,
+                    proof,
+                    request,
+                    invitation.token,
+                ),
+            ));
+
+            drop(layer_lock);
+
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-recovery-holders",
+            "list the holders of my mnemonic's shares.",
+            &[],
+            "[RecoveryHolder]",
+        ),
+        |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let holders = RecoveryHolder::list(&db)?;
+            drop(db);
+            let mut results = vec![];
+            for holder in holders {
+                results.push(holder.to_rpc());
+            }
+            Ok(HandleResult::rpc(json!(results)))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-recovery-setup",
+            "split the mnemonic to the friends.",
+            &[
+                req("lock", Str),
+                req("threshold", Int),
+                req("fids", IntList),
+            ],
+            "[RecoveryHolder]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let lock = params[0].as_str().ok_or(RpcError::ParseError)?;
+            let threshold = params[1].as_i64().ok_or(RpcError::ParseError)?;
+            let fids: Vec<i64> = params[2]
+                .as_array()
+                .ok_or(RpcError::ParseError)?
+                .iter()
+                .filter_map(|v| v.as_i64())
+                .collect();
+            // every holder only hold one share.
+            for (i, fid) in fids.iter().enumerate() {
+                if fids[..i].contains(fid) {
+                    return Err(RpcError::InvalidRequest);
+                }
+            }
+            if threshold < 1 || threshold as usize > fids.len() || fids.len() > 255 {
+                return Err(RpcError::InvalidRequest);
+            }
+
+            let group_lock = state.group.read().await;
+            let mnemonic = group_lock.mnemonic(&gid, lock)?;
+            let lang = group_lock.account(&gid)?.lang;
+            drop(group_lock);
+
+            let shares = shamir::split(mnemonic.as_bytes(), threshold as u8, fids.len() as u8)?;
+
+            let mut results = HandleResult::new();
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let mut friends = vec![];
+            for fid in &fids {
+                friends.push(Friend::get(&db, fid)?);
+            }
+
+            let setup = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+            let gids: Vec<GroupId> = friends.iter().map(|f| f.gid).collect();
+
+            RecoveryHolder::clear(&db)?;
+            let mut holders = vec![];
+            for (friend, share) in friends.into_iter().zip(shares) {
+                let mut holder =
+                    RecoveryHolder::new(friend.id, share[0] as i64, threshold, fids.len() as i64);
+                holder.insert(&db)?;
+                holders.push(holder.to_rpc());
+
+                // send in the friend's session, only the friend can read it.
+                let event =
+                    LayerEvent::RecoveryShare(lang, threshold, setup.clone(), gids.clone(), share);
+                let data = bincode::serialize(&event)?;
+                let msg = SendType::Event(0, friend.addr, data);
+                results.layers.push((gid, friend.gid, msg));
+            }
+            drop(db);
+
+            results.rpcs.push(json!(holders));
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-recovery-shares",
+            "list the shares which I hold or collected.",
+            &[req("is_collect", Bool)],
+            "[RecoveryShare]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let is_collect = params[0].as_bool().ok_or(RpcError::ParseError)?;
+
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let shares = RecoveryShare::list(&db, is_collect)?;
+            drop(db);
+            let mut results = vec![];
+            for share in shares {
+                results.push(share.to_rpc());
+            }
+            Ok(HandleResult::rpc(json!(results)))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-recovery-ask",
+            "ask the holder to release the share.",
+            &[
+                req("owner", Hex),
+                req("holder_gid", Hex),
+                req("holder_addr", Hex),
+            ],
+            NONE_RESULT,
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let owner = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
+            let holder_gid = GroupId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
+            let holder_addr = PeerId::from_hex(params[2].as_str().ok_or(RpcError::ParseError)?)?;
+
+            // record the asked holder, only accept the reply from it.
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            if RecoveryShare::get_collect(&db, &owner, &holder_gid).is_err() {
+                let mut rshare = RecoveryShare::new(
+                    owner,
+                    holder_gid,
+                    0,
+                    0,
+                    "".to_owned(),
+                    vec![],
+                    vec![],
+                    true,
+                );
+                rshare.insert(&db)?;
+            }
+            drop(db);
+
+            let data = bincode::serialize(&LayerEvent::RecoveryAsk(owner))?;
+            let msg = SendType::Event(0, holder_addr, data);
+            Ok(HandleResult::layer(gid, holder_gid, msg))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-recovery-release",
+            "release the share to the owner.",
+            &[req("id", Int), req("to_gid", Hex), req("to_addr", Hex)],
+            NONE_RESULT,
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            let to_gid = GroupId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
+            let to_addr = PeerId::from_hex(params[2].as_str().ok_or(RpcError::ParseError)?)?;
+
+            let key = state
+                .group
+                .read()
+                .await
+                .derive_key(&gid, RECOVERY_KEY_CONTEXT)?;
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let share = RecoveryShare::get(&db, &id)?;
+            if share.is_collect {
+                return Err(RpcError::InvalidRequest);
+            }
+            let bytes = decrypt_with_key(&key, &share.share)?;
+            // record who the share released to.
+            RecoveryShare::release(&db, &id, &to_gid)?;
+            drop(db);
+
+            let event = LayerEvent::RecoveryReply(
+                share.owner,
+                share.lang,
+                share.threshold,
+                share.setup,
+                share.holders,
+                bytes,
+            );
+            let data = bincode::serialize(&event)?;
+            let msg = SendType::Event(0, to_addr, data);
+            Ok(HandleResult::layer(gid, to_gid, msg))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-recovery-combine",
+            "combine the collected shares.",
+            &[req("owner", Hex)],
+            "[lang, mnemonic]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let owner = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
+
+            let key = state
+                .group
+                .read()
+                .await
+                .derive_key(&gid, RECOVERY_KEY_CONTEXT)?;
+            let db = chat_db(state.layer.read().await.base(), &gid)?;
+            let shares = RecoveryShare::list_collect(&db, &owner)?;
+            // all shares must be from one setup's different holders.
+            RecoveryShare::check_collect(&shares)?;
+            let lang = shares[0].lang;
+            let mut bytes = vec![];
+            for share in shares {
+                bytes.push(decrypt_with_key(&key, &share.share)?);
+            }
+            let mnemonic = String::from_utf8(shamir::combine(&bytes)?)
+                .or(Err(RpcError::Custom("Recovery shares invalid!".to_owned())))?;
+            RecoveryShare::delete_collect(&db, &owner)?;
+            drop(db);
+
+            // UI use it to account-restore.
+            Ok(HandleResult::rpc(json!([lang, mnemonic])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "chat-request-agree",
+            "agree the request.",
+            &[req("id", Int)],
+            "[id, Friend]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -339,8 +1013,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-request-reject",
+    rpc_method!(
+        handler,
+        method(
+            "chat-request-reject",
+            "reject the request.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -366,8 +1046,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-request-delete",
+    rpc_method!(
+        handler,
+        method(
+            "chat-request-delete",
+            "delete the request.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -396,8 +1082,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-detail",
+    rpc_method!(
+        handler,
+        method(
+            "chat-detail",
+            "the friend and messages.",
+            &[req("id", Int)],
+            "[Friend, [Message]]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -410,8 +1102,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-message-list",
+    rpc_method!(
+        handler,
+        method(
+            "chat-message-list",
+            "list the friend's messages.",
+            &[req("fid", Int)],
+            "[Message]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let fid = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -425,8 +1123,19 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-message-create",
+    rpc_method!(
+        handler,
+        method(
+            "chat-message-create",
+            "send the message to the friend.",
+            &[
+                req("fid", Int),
+                req("fgid", Hex),
+                req("m_type", Int),
+                req("content", Str),
+            ],
+            "Message",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let fid = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let fgid = GroupId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
@@ -465,8 +1174,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "chat-message-delete",
+    rpc_method!(
+        handler,
+        method(
+            "chat-message-delete",
+            "delete the message.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -5,11 +5,13 @@ use tdn::types::{
     rpc::{json, RpcHandler, RpcParam},
 };
 
+use crate::rpc::schema::{method, rpc_method};
 use crate::rpc::RpcState;
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method(
-        "cloud-echo",
+    rpc_method!(
+        handler,
+        method("cloud-echo", "echo the params.", &[], "params"),
         |_gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             Ok(HandleResult::rpc(json!(params)))
         },
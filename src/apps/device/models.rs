@@ -111,9 +111,9 @@ impl Device {
         db.update(&sql)
     }
 
-    /// used in rpc, when what to delete a friend.
-    pub fn _delete(&self, db: &DStorage) -> Result<usize> {
-        let sql = format!("DELETE FROM devices WHERE id = {}", self.id);
+    /// used when the device's account deleted.
+    pub fn delete(db: &DStorage, id: &i64) -> Result<usize> {
+        let sql = format!("DELETE FROM devices WHERE id = {}", id);
         db.update(&sql)
     }
 }
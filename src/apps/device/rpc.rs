@@ -1,11 +1,15 @@
 use std::sync::Arc;
 use tdn::types::{
     group::GroupId,
-    primitive::{HandleResult, Peer, PeerId},
+    primitive::{HandleResult, PeerId},
     rpc::{json, rpc_response, RpcError, RpcHandler, RpcParam},
 };
 
+use crate::audit::AuditKind;
+use crate::autologin;
+use crate::discovery;
 use crate::group::GroupEvent;
+use crate::rpc::schema::{method, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::rpc::RpcState;
 use crate::storage::consensus_db;
 use crate::utils::device_status::device_status as local_device_status;
@@ -18,7 +22,7 @@ pub(crate) fn device_create(mgid: GroupId, device: &Device) -> RpcParam {
 }
 
 #[inline]
-pub(crate) fn _device_remove(mgid: GroupId, id: i64) -> RpcParam {
+pub(crate) fn device_remove(mgid: GroupId, id: i64) -> RpcParam {
     rpc_response(0, "device-remove", json!([id]), mgid)
 }
 
@@ -44,11 +48,23 @@ pub(crate) fn device_status(
     swap_p: u16,
     disk_p: u16,
     uptime: u32,
+    is_autologin: bool,
 ) -> RpcParam {
     rpc_response(
         0,
         "device-status",
-        json!([cpu, memory, swap, disk, cpu_p, memory_p, swap_p, disk_p, uptime]),
+        json!([
+            cpu,
+            memory,
+            swap,
+            disk,
+            cpu_p,
+            memory_p,
+            swap_p,
+            disk_p,
+            uptime,
+            is_autologin
+        ]),
         mgid,
     )
 }
@@ -63,12 +79,15 @@ fn device_list(devices: Vec<Device>) -> RpcParam {
 }
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method("device-echo", |_, params, _| async move {
-        Ok(HandleResult::rpc(json!(params)))
-    });
+    rpc_method!(
+        handler,
+        method("device-echo", "echo the params.", &[], "params"),
+        |_, params, _| async move { Ok(HandleResult::rpc(json!(params))) }
+    );
 
-    handler.add_method(
-        "device-list",
+    rpc_method!(
+        handler,
+        method("device-list", "list all devices.", &[], "[Device]"),
         |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let db = consensus_db(state.layer.read().await.base(), &gid)?;
             let devices = Device::list(&db)?;
@@ -78,18 +97,34 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "device-status",
+    rpc_method!(
+        handler,
+        method(
+            "device-status",
+            "the device's status, remote device's is pushed.",
+            &[req("addr", Hex)],
+            "[cpu, memory, swap, disk, cpu_p, memory_p, swap_p, disk_p, uptime, is_autologin]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let addr = PeerId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
 
             let group_lock = state.group.read().await;
             if &addr == group_lock.addr() {
                 let uptime = group_lock.uptime(&gid)?;
+                let is_autologin = autologin::is_enabled(&gid);
                 let (cpu, memory, swap, disk, cpu_p, memory_p, swap_p, disk_p) =
                     local_device_status();
                 return Ok(HandleResult::rpc(json!([
-                    cpu, memory, swap, disk, cpu_p, memory_p, swap_p, disk_p, uptime
+                    cpu,
+                    memory,
+                    swap,
+                    disk,
+                    cpu_p,
+                    memory_p,
+                    swap_p,
+                    disk_p,
+                    uptime,
+                    is_autologin
                 ])));
             }
             drop(group_lock);
@@ -104,40 +139,67 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "device-create",
+    rpc_method!(
+        handler,
+        method(
+            "device-create",
+            "pair the new device.",
+            &[req("addr", Hex)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let addr = PeerId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
 
-            let msg = state
-                .group
-                .read()
-                .await
-                .create_message(&gid, Peer::peer(addr))?;
-            Ok(HandleResult::group(gid, msg))
+            let mut group_lock = state.group.write().await;
+            let msg = group_lock.create_message(&gid, discovery::peer(addr))?;
+            let mut results = HandleResult::group(gid, msg);
+            group_lock.audit(&gid, AuditKind::DeviceCreate, addr.to_hex(), &mut results)?;
+            Ok(results)
         },
     );
 
-    handler.add_method(
-        "device-connect",
+    rpc_method!(
+        handler,
+        method(
+            "device-connect",
+            "connect the device.",
+            &[req("addr", Hex)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let addr = PeerId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
 
-            let msg = state
-                .group
-                .read()
-                .await
-                .connect_message(&gid, Peer::peer(addr))?;
-            Ok(HandleResult::group(gid, msg))
+            let mut group_lock = state.group.write().await;
+            let msg = group_lock.connect_message(&gid, discovery::peer(addr))?;
+            let mut results = HandleResult::group(gid, msg);
+            group_lock.audit(&gid, AuditKind::DeviceConnect, addr.to_hex(), &mut results)?;
+            Ok(results)
         },
     );
 
-    handler.add_method(
-        "device-delete",
+    rpc_method!(
+        handler,
+        method(
+            "device-delete",
+            "delete the device.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |_gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             let _id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             // TODO delete a device.
             Ok(HandleResult::new())
         },
     );
+
+    rpc_method!(
+        handler,
+        method(
+            "device-discover",
+            "list my devices found in local network.",
+            &[],
+            "[[addr, socket, lasttime]]",
+        ),
+        |_, _, _| async move { Ok(HandleResult::rpc(discovery::list())) }
+    );
 }
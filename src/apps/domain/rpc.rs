@@ -11,6 +11,7 @@ use super::{
     add_layer,
     models::{Name, Provider},
 };
+use crate::rpc::schema::{method, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::{rpc::RpcState, storage::domain_db};
 
 #[inline]
@@ -68,8 +69,14 @@ pub(crate) fn search_none(mgid: GroupId, name: &str) -> RpcParam {
 }
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method(
-        "domain-list",
+    rpc_method!(
+        handler,
+        method(
+            "domain-list",
+            "list the providers and names.",
+            &[],
+            "[[Provider], [Name]]",
+        ),
         |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let db = domain_db(state.layer.read().await.base(), &gid)?;
 
@@ -84,8 +91,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-provider-add",
+    rpc_method!(
+        handler,
+        method(
+            "domain-provider-add",
+            "add the domain provider.",
+            &[req("provider", Hex)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let provider = PeerId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
 
@@ -99,8 +112,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-provider-default",
+    rpc_method!(
+        handler,
+        method(
+            "domain-provider-default",
+            "set the default provider.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -118,8 +137,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-provider-remove",
+    rpc_method!(
+        handler,
+        method(
+            "domain-provider-remove",
+            "remove the provider.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -133,8 +158,19 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-register",
+    rpc_method!(
+        handler,
+        method(
+            "domain-register",
+            "register the name to the provider.",
+            &[
+                req("provider", Int),
+                req("addr", Hex),
+                req("name", Str),
+                req("bio", Str),
+            ],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let provider = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let addr = PeerId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
@@ -156,8 +192,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-active",
+    rpc_method!(
+        handler,
+        method(
+            "domain-active",
+            "active/suspend the name.",
+            &[req("name", Str), req("provider", Hex), req("active", Bool)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             let name = params[0].as_str().ok_or(RpcError::ParseError)?.to_owned();
             let provider = PeerId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
@@ -175,8 +217,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-remove",
+    rpc_method!(
+        handler,
+        method(
+            "domain-remove",
+            "remove the name.",
+            &[req("name", Str), req("provider", Hex)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             let name = params[0].as_str().ok_or(RpcError::ParseError)?.to_owned();
             let provider = PeerId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
@@ -189,8 +237,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "domain-search",
+    rpc_method!(
+        handler,
+        method(
+            "domain-search",
+            "search the name in the provider.",
+            &[req("addr", Hex), req("name", Str)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             let addr = PeerId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
             let name = params[1].as_str().ok_or(RpcError::ParseError)?.to_owned();
@@ -6,18 +6,27 @@ use tdn::types::{
     rpc::{json, RpcError, RpcHandler, RpcParam},
 };
 
+use crate::rpc::schema::{method, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::rpc::RpcState;
 use crate::storage::{copy_file, file_db, write_file};
 
 use super::models::{File, RootDirectory};
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method("dc-echo", |_, params, _| async move {
-        Ok(HandleResult::rpc(json!(params)))
-    });
+    rpc_method!(
+        handler,
+        method("dc-echo", "echo the params.", &[], "params"),
+        |_, params, _| async move { Ok(HandleResult::rpc(json!(params))) }
+    );
 
-    handler.add_method(
-        "dc-list",
+    rpc_method!(
+        handler,
+        method(
+            "dc-list",
+            "list the files in the directory.",
+            &[req("root", Int), req("parent", Int)],
+            "[File]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let root = RootDirectory::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let parent = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -32,8 +41,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-file-create",
+    rpc_method!(
+        handler,
+        method(
+            "dc-file-create",
+            "create the empty file.",
+            &[req("root", Int), req("parent", Int), req("name", Str)],
+            "File",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let root = RootDirectory::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let parent = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -51,8 +66,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-file-upload",
+    rpc_method!(
+        handler,
+        method(
+            "dc-file-upload",
+            "upload the local file.",
+            &[req("root", Int), req("parent", Int), req("path", Str)],
+            "File",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let root = RootDirectory::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let parent = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -76,8 +97,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-folder-create",
+    rpc_method!(
+        handler,
+        method(
+            "dc-folder-create",
+            "create the folder.",
+            &[req("root", Int), req("parent", Int), req("name", Str)],
+            "File",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let root = RootDirectory::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let parent = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -92,8 +119,19 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-file-update",
+    rpc_method!(
+        handler,
+        method(
+            "dc-file-update",
+            "move or rename the file.",
+            &[
+                req("id", Int),
+                req("root", Int),
+                req("parent", Int),
+                req("name", Str),
+            ],
+            "File",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let root = RootDirectory::from_i64(params[1].as_i64().ok_or(RpcError::ParseError)?);
@@ -111,8 +149,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-file-star",
+    rpc_method!(
+        handler,
+        method(
+            "dc-file-star",
+            "star the file.",
+            &[req("id", Int), req("starred", Bool)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let starred = params[1].as_bool().ok_or(RpcError::ParseError)?;
@@ -123,8 +167,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-file-trash",
+    rpc_method!(
+        handler,
+        method(
+            "dc-file-trash",
+            "move the file to trash.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -136,8 +186,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "dc-file-delete",
+    rpc_method!(
+        handler,
+        method(
+            "dc-file-delete",
+            "delete the file.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
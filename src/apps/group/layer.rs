@@ -603,18 +603,30 @@ pub(crate) fn update_session(
         _ => format!("{}:", msg.m_type.to_int()),
     };
 
+    // check session's notification rule.
+    let mentioned = msg.is_mentioned(gid);
     if let Ok(s_db) = session_db(base, gid) {
-        if let Ok(sid) = Session::last(
+        if let Ok(s) = Session::last(
             &s_db,
             id,
             &SessionType::Group,
             &msg.datetime,
             &scontent,
-            true,
+            msg.is_me,
+            mentioned,
         ) {
-            results
-                .rpcs
-                .push(session_last(*gid, &sid, &msg.datetime, &scontent, false));
+            results.rpcs.push(session_last(
+                *gid,
+                &s.id,
+                &msg.datetime,
+                &scontent,
+                s.last_readed,
+                s.unread,
+            ));
+
+            if mentioned && !s.last_readed {
+                results.rpcs.push(rpc::message_mention(*gid, &s.id, msg));
+            }
         }
     }
 }
@@ -15,6 +15,28 @@ use crate::storage::group_db;
 
 use super::Member;
 
+/// Mention member in group string message, format: `@[member_gid_hex]`.
+const MENTION_PREFIX: &'static str = "@[";
+const MENTION_SUFFIX: &'static str = "]";
+
+/// parse all mentioned members in group string message's content.
+pub(crate) fn parse_mentions(content: &str) -> Vec<GroupId> {
+    let mut mentions = vec![];
+    for (i, _) in content.match_indices(MENTION_PREFIX) {
+        let start = i + MENTION_PREFIX.len();
+        let end = start + 64; // GroupId hex length.
+        if content.get(end..end + 1) != Some(MENTION_SUFFIX) {
+            continue;
+        }
+        if let Some(Ok(mgid)) = content.get(start..end).map(|s| GroupId::from_hex(s)) {
+            if !mentions.contains(&mgid) {
+                mentions.push(mgid);
+            }
+        }
+    }
+    mentions
+}
+
 /// Group Chat Message Model.
 pub(crate) struct Message {
     /// db auto-increment id.
@@ -26,7 +48,7 @@ pub(crate) struct Message {
     /// member's db id.
     pub mid: i64,
     /// message is mine.
-    is_me: bool,
+    pub is_me: bool,
     /// message type.
     pub m_type: MessageType,
     /// message content.
@@ -60,6 +82,18 @@ impl Message {
         }
     }
 
+    /// check if the member is mentioned in this message. self message always false.
+    pub fn is_mentioned(&self, mgid: &GroupId) -> bool {
+        if self.is_me {
+            return false;
+        }
+
+        match self.m_type {
+            MessageType::String => parse_mentions(&self.content).contains(mgid),
+            _ => false,
+        }
+    }
+
     /// here is zero-copy and unwrap is safe. checked.
     fn from_values(mut v: Vec<DsValue>) -> Message {
         Message {
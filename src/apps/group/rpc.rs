@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use tdn::types::{
     group::GroupId,
-    message::{NetworkType, SendMessage, SendType},
+    message::{NetworkType, SendType},
     primitive::{HandleResult, PeerId},
     rpc::{json, rpc_response, RpcError, RpcHandler, RpcParam},
 };
@@ -11,7 +11,9 @@ use group_types::{Event, LayerEvent};
 
 use crate::apps::chat::{Friend, InviteType};
 use crate::layer::Online;
+use crate::rpc::schema::{method, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::rpc::{session_create, session_delete, session_update_name, RpcState};
+use crate::server::push_rpc;
 use crate::session::{Session, SessionType};
 use crate::storage::{chat_db, group_db, read_avatar, session_db, write_avatar};
 
@@ -54,6 +56,11 @@ pub(crate) fn message_create(mgid: GroupId, msg: &Message) -> RpcParam {
     rpc_response(0, "group-message-create", json!(msg.to_rpc()), mgid)
 }
 
+#[inline]
+pub(crate) fn message_mention(mgid: GroupId, sid: &i64, msg: &Message) -> RpcParam {
+    rpc_response(0, "group-message-mention", json!([sid, msg.to_rpc()]), mgid)
+}
+
 #[inline]
 fn group_list(groups: Vec<GroupChat>) -> RpcParam {
     let mut results = vec![];
@@ -80,8 +87,9 @@ fn detail_list(group: GroupChat, members: Vec<Member>, messages: Vec<Message>) -
 }
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method(
-        "group-list",
+    rpc_method!(
+        handler,
+        method("group-list", "list all groups.", &[], "[GroupChat]"),
         |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let layer_lock = state.layer.read().await;
             let db = group_db(&layer_lock.base, &gid)?;
@@ -89,8 +97,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "group-detail",
+    rpc_method!(
+        handler,
+        method(
+            "group-detail",
+            "the group's members and messages.",
+            &[req("id", Int)],
+            "[GroupChat, [Member], [Message]]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let db = group_db(state.layer.read().await.base(), &gid)?;
@@ -101,8 +115,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "group-create",
+    rpc_method!(
+        handler,
+        method(
+            "group-create",
+            "create the group.",
+            &[req("name", Str)],
+            "[sid, id]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let name = params[0].as_str().ok_or(RpcError::ParseError)?.to_owned();
 
@@ -135,9 +155,7 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
             session.insert(&s_db)?;
             let sid = session.id;
             tokio::spawn(async move {
-                let _ = sender
-                    .send(SendMessage::Rpc(0, session_create(gid, &session), true))
-                    .await;
+                let _ = push_rpc(&sender, session_create(gid, &session)).await;
             });
 
             // add to rpcs.
@@ -167,8 +185,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "group-member-join",
+    rpc_method!(
+        handler,
+        method(
+            "group-member-join",
+            "invite the friend to the group.",
+            &[req("id", Int), req("fid", Int)],
+            "Member",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let fid = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -230,8 +254,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "group-message-create",
+    rpc_method!(
+        handler,
+        method(
+            "group-message-create",
+            "send the message to the group.",
+            &[req("id", Int), req("m_type", Int), req("content", Str)],
+            "Message",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let m_type = MessageType::from_int(params[1].as_i64().ok_or(RpcError::ParseError)?);
@@ -278,8 +308,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "group-name",
+    rpc_method!(
+        handler,
+        method(
+            "group-name",
+            "change the group's name.",
+            &[req("id", Int), req("name", Str)],
+            "[id, name]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let name = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -316,8 +352,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "group-delete",
+    rpc_method!(
+        handler,
+        method(
+            "group-delete",
+            "leave and delete the group.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
 
@@ -14,7 +14,9 @@ use chat_types::MessageType;
 
 use crate::account::lang_from_i64;
 use crate::apps::chat::raw_to_network_message;
+use crate::rpc::schema::{method, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::rpc::RpcState;
+use crate::server::push_rpc;
 use crate::storage::jarvis_db;
 use crate::utils::answer::load_answer;
 
@@ -45,13 +47,14 @@ async fn reply(
     reply.insert(&db)?;
 
     let res = rpc_response(0, "jarvis-create", reply.to_rpc(), gid);
-    sender.send(SendMessage::Rpc(0, res, true)).await?;
+    push_rpc(&sender, res).await?;
     Ok(())
 }
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method(
-        "jarvis-list",
+    rpc_method!(
+        handler,
+        method("jarvis-list", "list all messages.", &[], "[Message]"),
         |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let db = jarvis_db(state.layer.read().await.base(), &gid)?;
             let devices = Message::list(&db)?;
@@ -64,8 +67,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "jarvis-create",
+    rpc_method!(
+        handler,
+        method(
+            "jarvis-create",
+            "send the message to jarvis.",
+            &[req("lang", Int), req("m_type", Int), req("content", Str)],
+            "Message",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let lang = lang_from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let m_type = MessageType::from_int(params[1].as_i64().ok_or(RpcError::ParseError)?);
@@ -88,8 +97,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "jarvis-delete",
+    rpc_method!(
+        handler,
+        method(
+            "jarvis-delete",
+            "delete the message.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let db = jarvis_db(state.layer.read().await.base(), &gid)?;
@@ -16,8 +16,11 @@ use web3::{
     Web3,
 };
 
+use crate::rpc::schema::{method, opt, req, rpc_method, ParamType::*, NONE_RESULT};
 use crate::{
+    audit::AuditKind,
     rpc::RpcState,
+    server::push_rpc,
     storage::{account_db, wallet_db},
 };
 
@@ -87,7 +90,7 @@ async fn loop_token(
         let web3 = Web3::new(transport);
         let balance = token_balance(&web3, &token.contract, &address, &token.chain).await?;
         let res = res_balance(gid, &address, &network, &balance, Some(&token));
-        sender.send(SendMessage::Rpc(0, res, true)).await?;
+        push_rpc(&sender, res).await?;
     } else {
         match chain {
             ChainToken::ETH => {
@@ -97,7 +100,7 @@ async fn loop_token(
                 let balance = balance.to_string();
                 let _ = Address::update_balance(&db, &address, &network, &balance);
                 let res = res_balance(gid, &address, &network, &balance, None);
-                sender.send(SendMessage::Rpc(0, res, true)).await?;
+                push_rpc(&sender, res).await?;
 
                 for token in tokens {
                     //tokio::time::sleep(std::time::Duration::from_secs(1)).await;
@@ -108,7 +111,7 @@ async fn loop_token(
                     // update & clean balances.
                     // TODO
 
-                    sender.send(SendMessage::Rpc(0, res, true)).await?;
+                    push_rpc(&sender, res).await?;
                 }
             }
             ChainToken::BTC => {
@@ -164,7 +167,7 @@ async fn token_check(
         .await?;
     let balance = balance.to_string();
     let res = res_balance(gid, &address, &network, &balance, Some(&token));
-    sender.send(SendMessage::Rpc(0, res, true)).await?;
+    push_rpc(&sender, res).await?;
 
     Ok(())
 }
@@ -324,12 +327,15 @@ async fn nft_check(node: &str, c_str: &str, hash: &str) -> Result<String> {
 }
 
 pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
-    handler.add_method("wallet-echo", |_, params, _| async move {
-        Ok(HandleResult::rpc(json!(params)))
-    });
+    rpc_method!(
+        handler,
+        method("wallet-echo", "echo the params.", &[], "params"),
+        |_, params, _| async move { Ok(HandleResult::rpc(json!(params))) }
+    );
 
-    handler.add_method(
-        "wallet-list",
+    rpc_method!(
+        handler,
+        method("wallet-list", "list all addresses.", &[], "[Address]"),
         |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let db = wallet_db(state.layer.read().await.base(), &gid)?;
             let addresses = Address::list(&db)?;
@@ -337,8 +343,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-generate",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-generate",
+            "generate the address from the mnemonic.",
+            &[req("chain", Int), req("lock", Str)],
+            "Address",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let chain = ChainToken::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let lock = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -392,8 +404,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-import",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-import",
+            "import the address by the secret key.",
+            &[req("chain", Int), req("secret", Str), req("lock", Str)],
+            "Address",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let chain = ChainToken::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let secret = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -402,19 +420,33 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
             let sk: SecretKey = secret.parse().or(Err(RpcError::ParseError))?;
             let addr = format!("{:?}", (&sk).address());
 
-            let group_lock = state.group.read().await;
+            let mut group_lock = state.group.write().await;
             let cbytes = group_lock.encrypt(&gid, lock, sk.as_ref())?;
             let db = wallet_db(group_lock.base(), &gid)?;
-            drop(group_lock);
 
             let mut address = Address::import(chain, addr, cbytes);
             address.insert(&db)?;
-            Ok(HandleResult::rpc(address.to_rpc()))
+            let mut results = HandleResult::rpc(address.to_rpc());
+            let info = address.address.clone();
+            group_lock.audit(&gid, AuditKind::WalletImport, info, &mut results)?;
+            drop(group_lock);
+            Ok(results)
         },
     );
 
-    handler.add_method(
-        "wallet-token",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-token",
+            "list the tokens, the balances are pushed.",
+            &[
+                req("network", Int),
+                req("address", Str),
+                opt("token", Int),
+                opt("reserved", Any),
+            ],
+            "[network, [Token]]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let network = Network::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let address = params[1].as_str().ok_or(RpcError::ParseError)?.to_owned();
@@ -438,8 +470,19 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-token-import",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-token-import",
+            "import the token by the contract.",
+            &[
+                req("chain", Int),
+                req("network", Int),
+                req("address", Str),
+                req("contract", Str),
+            ],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let chain = ChainToken::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let network = Network::from_i64(params[1].as_i64().ok_or(RpcError::ParseError)?);
@@ -457,8 +500,21 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-gas-price",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-gas-price",
+            "the gas price of the transfer.",
+            &[
+                req("chain", Int),
+                req("network", Int),
+                req("from", Str),
+                req("to", Str),
+                req("amount", Str),
+                req("contract", Str),
+            ],
+            "[price, gas]",
+        ),
         |_gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             let chain = ChainToken::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let network = Network::from_i64(params[1].as_i64().ok_or(RpcError::ParseError)?);
@@ -472,8 +528,22 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-transfer",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-transfer",
+            "transfer the token.",
+            &[
+                req("chain", Int),
+                req("network", Int),
+                req("from", Int),
+                req("to", Str),
+                req("amount", Str),
+                req("contract", Str),
+                req("lock", Str),
+            ],
+            "[from, network, [hash, to]]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let chain = ChainToken::from_i64(params[0].as_i64().ok_or(RpcError::ParseError)?);
             let network = Network::from_i64(params[1].as_i64().ok_or(RpcError::ParseError)?);
@@ -546,16 +616,30 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
                 }
             }
 
-            Ok(HandleResult::rpc(json!([
-                from,
-                network.to_i64(),
-                [hash, to],
-            ])))
+            let mut results = HandleResult::rpc(json!([from, network.to_i64(), [hash, to],]));
+            let info = format!("{} -> {}: {} ({})", address.address, to, amount, hash);
+            // the transfer had sent, not return the audit failure.
+            if let Err(e) =
+                state
+                    .group
+                    .write()
+                    .await
+                    .audit(&gid, AuditKind::WalletTransfer, info, &mut results)
+            {
+                error!("Wallet transfer audit failure: {}", e);
+            }
+            Ok(results)
         },
     );
 
-    handler.add_method(
-        "wallet-nft",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-nft",
+            "list the NFTs of the address.",
+            &[req("address", Int), req("token", Int)],
+            "[address, token, [hash]]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let address = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let token = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -571,8 +655,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-nft-add",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-nft-add",
+            "add the NFT by the hash.",
+            &[req("address", Int), req("token", Int), req("hash", Str)],
+            "[address, token, hash]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let address = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let token = params[1].as_i64().ok_or(RpcError::ParseError)?;
@@ -599,8 +689,14 @@ pub(crate) fn new_rpc_handler(handler: &mut RpcHandler<RpcState>) {
         },
     );
 
-    handler.add_method(
-        "wallet-main",
+    rpc_method!(
+        handler,
+        method(
+            "wallet-main",
+            "set the main address.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let base = state.layer.read().await.base().clone();
@@ -0,0 +1,189 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdn::types::{
+    primitive::{PeerId, Result},
+    rpc::{json, RpcParam},
+};
+use tdn_storage::local::{DStorage, DsValue};
+
+/// Sensitive action which need audit.
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub(crate) enum AuditKind {
+    Login,
+    Logout,
+    Pin,
+    Mnemonic,
+    DeviceCreate,
+    DeviceConnect,
+    WalletImport,
+    WalletTransfer,
+    /// unattended auto-login enabled/disabled, the lock is stored in plain.
+    AutoLogin,
+}
+
+impl AuditKind {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            AuditKind::Login => 0,
+            AuditKind::Logout => 1,
+            AuditKind::Pin => 2,
+            AuditKind::Mnemonic => 3,
+            AuditKind::DeviceCreate => 4,
+            AuditKind::DeviceConnect => 5,
+            AuditKind::WalletImport => 6,
+            AuditKind::WalletTransfer => 7,
+            AuditKind::AutoLogin => 8,
+        }
+    }
+
+    pub fn from_i64(i: i64) -> Self {
+        match i {
+            0 => AuditKind::Login,
+            1 => AuditKind::Logout,
+            2 => AuditKind::Pin,
+            3 => AuditKind::Mnemonic,
+            4 => AuditKind::DeviceCreate,
+            5 => AuditKind::DeviceConnect,
+            6 => AuditKind::WalletImport,
+            7 => AuditKind::WalletTransfer,
+            8 => AuditKind::AutoLogin,
+            _ => AuditKind::Login,
+        }
+    }
+}
+
+/// the context of the key which used in audit chain's keyed hash.
+pub(crate) const AUDIT_KEY_CONTEXT: &str = "esse audit chain";
+
+/// Append-only audit log, every entry link to previous entry's hash.
+/// the hash is keyed by account's secret, so the chain can not be rebuilt
+/// without the account, the entry from other devices is re-chained with same key.
+pub(crate) struct Audit {
+    pub id: i64,
+    pub kind: AuditKind,
+    /// action's description (no secret in it).
+    pub info: String,
+    /// the device which action happened.
+    pub device: PeerId,
+    /// previous entry's hash, genesis is empty.
+    pub prev: String,
+    pub hash: String,
+    pub datetime: i64,
+}
+
+impl Audit {
+    pub fn new(kind: AuditKind, info: String, device: PeerId) -> Audit {
+        let start = SystemTime::now();
+        let datetime = start
+            .duration_since(UNIX_EPOCH)
+            .map(|s| s.as_secs())
+            .unwrap_or(0) as i64; // safe for all life.
+
+        Audit::from_remote(kind, info, device, datetime)
+    }
+
+    pub fn from_remote(kind: AuditKind, info: String, device: PeerId, datetime: i64) -> Audit {
+        Audit {
+            id: 0,
+            kind,
+            info,
+            device,
+            prev: String::new(),
+            hash: String::new(),
+            datetime,
+        }
+    }
+
+    /// here is zero-copy and unwrap is safe. checked.
+    fn from_values(mut v: Vec<DsValue>) -> Audit {
+        Audit {
+            datetime: v.pop().unwrap().as_i64(),
+            hash: v.pop().unwrap().as_string(),
+            prev: v.pop().unwrap().as_string(),
+            device: PeerId::from_hex(v.pop().unwrap().as_str()).unwrap_or(PeerId::default()),
+            info: v.pop().unwrap().as_string(),
+            kind: AuditKind::from_i64(v.pop().unwrap().as_i64()),
+            id: v.pop().unwrap().as_i64(),
+        }
+    }
+
+    pub fn to_rpc(&self) -> RpcParam {
+        json!([
+            self.id,
+            self.kind.to_i64(),
+            self.info,
+            self.device.to_hex(),
+            self.hash,
+            self.datetime
+        ])
+    }
+
+    fn digest(&self, key: &[u8; 32]) -> String {
+        let mut hasher = blake3::Hasher::new_keyed(key);
+        hasher.update(self.prev.as_bytes());
+        hasher.update(&self.kind.to_i64().to_le_bytes());
+        hasher.update(self.info.as_bytes());
+        hasher.update(self.device.to_hex().as_bytes());
+        hasher.update(&self.datetime.to_le_bytes());
+        hex::encode(hasher.finalize().as_bytes())
+    }
+
+    pub fn get(db: &DStorage, id: &i64) -> Result<Audit> {
+        let sql = format!(
+            "SELECT id, kind, info, device, prev, hash, datetime FROM audits WHERE id = {}",
+            id
+        );
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(Audit::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("audit is missing."))
+        }
+    }
+
+    pub fn list(db: &DStorage, from: i64, limit: i64) -> Result<Vec<Audit>> {
+        let sql = format!("SELECT id, kind, info, device, prev, hash, datetime FROM audits WHERE id > {} ORDER BY id LIMIT {}", from, limit);
+        let matrix = db.query(&sql)?;
+        let mut audits = vec![];
+        for values in matrix {
+            audits.push(Audit::from_values(values));
+        }
+        Ok(audits)
+    }
+
+    /// check all chain, return the first broken entry's id.
+    pub fn verify(db: &DStorage, key: &[u8; 32]) -> Result<Option<i64>> {
+        let matrix = db
+            .query("SELECT id, kind, info, device, prev, hash, datetime FROM audits ORDER BY id")?;
+        let mut prev = String::new();
+        for values in matrix {
+            let audit = Audit::from_values(values);
+            if audit.prev != prev || audit.digest(key) != audit.hash {
+                return Ok(Some(audit.id));
+            }
+            prev = audit.hash;
+        }
+        Ok(None)
+    }
+
+    /// link to the last entry, and append it.
+    pub fn insert(&mut self, db: &DStorage, key: &[u8; 32]) -> Result<()> {
+        let mut matrix = db.query("SELECT hash FROM audits ORDER BY id DESC LIMIT 1")?;
+        self.prev = if let Some(mut values) = matrix.pop() {
+            values.pop().unwrap().as_string() // safe unwrap.
+        } else {
+            String::new()
+        };
+        self.hash = self.digest(key);
+
+        let sql = format!("INSERT INTO audits (kind, info, device, prev, hash, datetime) VALUES ({}, '{}', '{}', '{}', '{}', {})",
+            self.kind.to_i64(),
+            self.info.replace("'", "''"),
+            self.device.to_hex(),
+            self.prev,
+            self.hash,
+            self.datetime,
+        );
+        self.id = db.insert(&sql)?;
+        Ok(())
+    }
+}
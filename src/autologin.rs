@@ -0,0 +1,223 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock as StdRwLock;
+use tdn::types::{group::GroupId, primitive::Result};
+
+/// the unattended accounts' locks, every line is: gid lock.
+/// it MUST only can be read by the user which running daemon (mode 0600).
+const AUTOLOGIN_FILE: &'static str = "autologin.secret";
+/// the unattended accounts' locks in env, e.g. `ESSE_AUTOLOGIN="gid1:lock1,gid2:lock2"`.
+/// the gid is hex, so the lock can contain `:` and `,`, only `,gid:` starts next account.
+const AUTOLOGIN_ENV: &'static str = "ESSE_AUTOLOGIN";
+
+/// unattended accounts, gid => is configured in env.
+static AUTOLOGIN: Lazy<StdRwLock<HashMap<GroupId, bool>>> =
+    Lazy::new(|| StdRwLock::new(HashMap::new()));
+
+/// check the secret file only can be accessed by the owner.
+#[cfg(unix)]
+fn is_private(path: &PathBuf) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o077 == 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_private(_path: &PathBuf) -> bool {
+    true
+}
+
+fn load_file(path: &PathBuf) -> Vec<(GroupId, String)> {
+    let mut locks = vec![];
+    let content = std::fs::read_to_string(path).unwrap_or(String::new());
+    for line in content.lines() {
+        let mut items = line.trim().splitn(2, ' ');
+        let gid = items.next().map(|g| GroupId::from_hex(g));
+        if let (Some(Ok(gid)), Some(lock)) = (gid, items.next()) {
+            locks.push((gid, lock.to_owned()));
+        }
+    }
+    locks
+}
+
+fn save_file(path: &PathBuf, locks: &[(GroupId, String)]) -> Result<()> {
+    use std::io::Write;
+
+    let mut content = String::new();
+    for (gid, lock) in locks {
+        content.push_str(&format!("{} {}\n", gid.to_hex(), lock));
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // fix the mode when file created by others.
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options.open(path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+fn parse_env(value: &str) -> Vec<(GroupId, String)> {
+    let mut locks: Vec<(GroupId, String)> = vec![];
+    for item in value.split(',') {
+        let mut items = item.splitn(2, ':');
+        let gid = items.next().map(|g| GroupId::from_hex(g.trim()));
+        match (gid, items.next()) {
+            (Some(Ok(gid)), Some(lock)) => locks.push((gid, lock.to_owned())),
+            // not start with gid, it is a part of previous lock.
+            _ => {
+                if let Some((_, lock)) = locks.last_mut() {
+                    lock.push(',');
+                    lock.push_str(item);
+                }
+            }
+        }
+    }
+    locks
+}
+
+fn load_env() -> Vec<(GroupId, String)> {
+    parse_env(&std::env::var(AUTOLOGIN_ENV).unwrap_or(String::new()))
+}
+
+/// load the unattended accounts, return the (gid, lock) which need login when start.
+pub(crate) fn init(base: &PathBuf) -> Vec<(GroupId, String)> {
+    let path = base.join(AUTOLOGIN_FILE);
+    let mut locks = vec![];
+    if path.exists() {
+        if is_private(&path) {
+            locks = load_file(&path);
+        } else {
+            error!("Auto-login file {:?} can be read by others, ignored.", path);
+        }
+    }
+
+    // the env is prior to the file.
+    let env_locks = load_env();
+    locks.retain(|(gid, _)| !env_locks.iter().any(|(g, _)| g == gid));
+
+    if let Ok(mut accounts) = AUTOLOGIN.write() {
+        accounts.clear();
+        for (gid, _) in &locks {
+            accounts.insert(*gid, false);
+        }
+        for (gid, _) in &env_locks {
+            accounts.insert(*gid, true);
+        }
+    }
+
+    locks.extend(env_locks);
+    locks
+}
+
+/// enable the account's auto-login, save the lock to the secret file.
+pub(crate) fn enable(base: &PathBuf, gid: &GroupId, lock: &str) -> Result<()> {
+    let path = base.join(AUTOLOGIN_FILE);
+    let mut locks = load_file(&path);
+    locks.retain(|(g, _)| g != gid);
+    locks.push((*gid, lock.to_owned()));
+    save_file(&path, &locks)?;
+
+    let mut accounts = AUTOLOGIN
+        .write()
+        .map_err(|_| anyhow!("auto-login lock failure."))?;
+    accounts.entry(*gid).or_insert(false);
+    Ok(())
+}
+
+/// disable the account's auto-login, remove the lock from the secret file.
+pub(crate) fn disable(base: &PathBuf, gid: &GroupId) -> Result<()> {
+    let mut accounts = AUTOLOGIN
+        .write()
+        .map_err(|_| anyhow!("auto-login lock failure."))?;
+    if accounts.get(gid) == Some(&true) {
+        return Err(anyhow!(
+            "auto-login is configured in env {}.",
+            AUTOLOGIN_ENV
+        ));
+    }
+
+    let path = base.join(AUTOLOGIN_FILE);
+    let mut locks = load_file(&path);
+    locks.retain(|(g, _)| g != gid);
+    save_file(&path, &locks)?;
+    accounts.remove(gid);
+    Ok(())
+}
+
+/// the account's pin changed, update the stored lock if auto-login enabled.
+pub(crate) fn update(base: &PathBuf, gid: &GroupId, lock: &str) -> Result<()> {
+    let in_env = match AUTOLOGIN.read() {
+        Ok(accounts) => match accounts.get(gid) {
+            Some(in_env) => *in_env,
+            None => return Ok(()),
+        },
+        Err(_) => return Err(anyhow!("auto-login lock failure.")),
+    };
+
+    if in_env {
+        warn!(
+            "Auto-login lock of {} in env {} is expired, need update.",
+            gid.to_hex(),
+            AUTOLOGIN_ENV
+        );
+        Ok(())
+    } else {
+        enable(base, gid, lock)
+    }
+}
+
+/// the account deleted, always remove the lock from the secret file.
+pub(crate) fn remove(base: &PathBuf, gid: &GroupId) -> Result<()> {
+    let path = base.join(AUTOLOGIN_FILE);
+    if path.exists() {
+        let mut locks = load_file(&path);
+        locks.retain(|(g, _)| g != gid);
+        save_file(&path, &locks)?;
+    }
+
+    let mut accounts = AUTOLOGIN
+        .write()
+        .map_err(|_| anyhow!("auto-login lock failure."))?;
+    if accounts.remove(gid) == Some(true) {
+        warn!(
+            "Deleted account {} is still in env {}.",
+            gid.to_hex(),
+            AUTOLOGIN_ENV
+        );
+    }
+    Ok(())
+}
+
+/// the account is unattended, the risk shows in device status.
+pub(crate) fn is_enabled(gid: &GroupId) -> bool {
+    AUTOLOGIN
+        .read()
+        .map(|accounts| accounts.contains_key(gid))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_locks() {
+        let g1 = GroupId([1u8; 32]);
+        let g2 = GroupId([2u8; 32]);
+        let value = format!("{}:a,b:c, {}:d:e,", g1.to_hex(), g2.to_hex());
+        let locks = parse_env(&value);
+        assert_eq!(locks.len(), 2);
+        assert_eq!(locks[0], (g1, "a,b:c".to_owned()));
+        assert_eq!(locks[1], (g2, "d:e,".to_owned()));
+    }
+}
@@ -0,0 +1,216 @@
+use once_cell::sync::Lazy;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::RwLock as StdRwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdn::types::{
+    primitive::{Peer, Result},
+    rpc::{json, RpcParam},
+};
+
+use crate::primitives::network_seeds;
+
+/// the seeds which configured by user or `add-bootstrap`, every line is: socket transport.
+const SEEDS_FILE: &'static str = "seeds.list";
+/// the peers which connected successfully, every line is: socket transport lasttime.
+const PEERS_CACHE_FILE: &'static str = "peers.cache";
+/// max number of cached peers.
+const PEERS_CACHE_MAX: usize = 32;
+/// not rewrite the cache when the peer had cached in this seconds.
+const PEERS_CACHE_REFRESH: i64 = 3600;
+
+/// Bootstrap peers, loaded when start, and changed by rpc & connections.
+struct Bootstrap {
+    base: PathBuf,
+    private: bool,
+    /// (socket, transport).
+    seeds: Vec<(SocketAddr, String)>,
+    /// (socket, transport, lasttime), the newest first.
+    cache: Vec<(SocketAddr, String, i64)>,
+}
+
+static BOOTSTRAP: Lazy<StdRwLock<Bootstrap>> = Lazy::new(|| {
+    StdRwLock::new(Bootstrap {
+        base: PathBuf::new(),
+        private: false,
+        seeds: vec![],
+        cache: vec![],
+    })
+});
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|s| s.as_secs())
+        .unwrap_or(0) as i64 // safe for all life.
+}
+
+fn load_lines(path: PathBuf) -> Vec<Vec<String>> {
+    std::fs::read_to_string(path)
+        .unwrap_or(String::new())
+        .lines()
+        .map(|line| line.split_whitespace().map(|s| s.to_owned()).collect())
+        .collect()
+}
+
+impl Bootstrap {
+    fn save_seeds(&self) -> Result<()> {
+        let mut content = String::new();
+        for (socket, transport) in &self.seeds {
+            content.push_str(&format!("{} {}\n", socket, transport));
+        }
+        std::fs::write(self.base.join(SEEDS_FILE), content)?;
+        Ok(())
+    }
+
+    fn save_cache(&self) -> Result<()> {
+        let mut content = String::new();
+        for (socket, transport, lasttime) in &self.cache {
+            content.push_str(&format!("{} {} {}\n", socket, transport, lasttime));
+        }
+        std::fs::write(self.base.join(PEERS_CACHE_FILE), content)?;
+        Ok(())
+    }
+
+    /// all bootstrap peers, cached peers first, then seeds, then public seeds.
+    fn peers(&self) -> Vec<Peer> {
+        let public = network_seeds();
+        // private mode, also skip the cached public seeds.
+        let mut sockets: Vec<SocketAddr> = if self.private {
+            public.iter().map(|p| p.socket).collect()
+        } else {
+            vec![]
+        };
+        let mut peers = vec![];
+        let iter = self
+            .cache
+            .iter()
+            .map(|(s, t, _)| (s, t))
+            .chain(self.seeds.iter().map(|(s, t)| (s, t)));
+        for (socket, transport) in iter {
+            if !sockets.contains(socket) {
+                sockets.push(*socket);
+                peers.push(Peer::socket_transport(*socket, transport));
+            }
+        }
+
+        if !self.private {
+            for peer in public {
+                if !sockets.contains(&peer.socket) {
+                    sockets.push(peer.socket);
+                    peers.push(peer);
+                }
+            }
+        }
+        peers
+    }
+}
+
+/// load the seeds & cached peers, return the bootstrap peers.
+pub(crate) fn init(base: &PathBuf, private: bool) -> Vec<Peer> {
+    let mut seeds = vec![];
+    for items in load_lines(base.join(SEEDS_FILE)) {
+        if items.len() > 1 {
+            if let Ok(socket) = items[0].parse() {
+                seeds.push((socket, items[1].clone()));
+            }
+        }
+    }
+
+    let mut cache = vec![];
+    for items in load_lines(base.join(PEERS_CACHE_FILE)) {
+        if items.len() > 2 {
+            if let (Ok(socket), Ok(lasttime)) = (items[0].parse(), items[2].parse()) {
+                cache.push((socket, items[1].clone(), lasttime));
+            }
+        }
+    }
+    cache.sort_by(|a, b| b.2.cmp(&a.2));
+
+    if private {
+        info!("P2P private mode, public seeds disabled.");
+    }
+
+    let mut bootstrap = match BOOTSTRAP.write() {
+        Ok(bootstrap) => bootstrap,
+        Err(_) => return network_seeds(),
+    };
+    bootstrap.base = base.clone();
+    bootstrap.private = private;
+    bootstrap.seeds = seeds;
+    bootstrap.cache = cache;
+    bootstrap.peers()
+}
+
+/// add the seed and save it.
+pub(crate) fn add_seed(socket: SocketAddr, transport: &str) -> Result<()> {
+    let mut bootstrap = BOOTSTRAP
+        .write()
+        .map_err(|_| anyhow!("bootstrap lock failure."))?;
+    if bootstrap.seeds.iter().any(|(s, _)| s == &socket) {
+        return Ok(());
+    }
+    bootstrap.seeds.push((socket, transport.to_owned()));
+    bootstrap.save_seeds()
+}
+
+/// remove the seed (and the cached peer).
+pub(crate) fn remove_seed(socket: &SocketAddr) -> Result<()> {
+    let mut bootstrap = BOOTSTRAP
+        .write()
+        .map_err(|_| anyhow!("bootstrap lock failure."))?;
+    bootstrap.seeds.retain(|(s, _)| s != socket);
+    bootstrap.cache.retain(|(s, _, _)| s != socket);
+    bootstrap.save_seeds()?;
+    bootstrap.save_cache()
+}
+
+/// the peer connected successfully, cache it.
+pub(crate) fn connected(peer: &Peer) {
+    // relay or unknown socket.
+    if peer.socket.ip().is_unspecified() || peer.socket.port() == 0 {
+        return;
+    }
+
+    let mut bootstrap = match BOOTSTRAP.write() {
+        Ok(bootstrap) => bootstrap,
+        Err(_) => return,
+    };
+    let now = now();
+    if let Some(p) = bootstrap.cache.iter().find(|p| p.0 == peer.socket) {
+        if now - p.2 < PEERS_CACHE_REFRESH {
+            return;
+        }
+    }
+
+    bootstrap.cache.retain(|p| p.0 != peer.socket);
+    let transport = peer.transport.to_str().to_owned();
+    bootstrap.cache.insert(0, (peer.socket, transport, now));
+    bootstrap.cache.truncate(PEERS_CACHE_MAX);
+    if let Err(e) = bootstrap.save_cache() {
+        error!("Peers cache save failure: {}", e);
+    }
+}
+
+/// list all bootstrap peers: [private, [[socket, transport, source]]].
+pub(crate) fn list() -> RpcParam {
+    let bootstrap = match BOOTSTRAP.read() {
+        Ok(bootstrap) => bootstrap,
+        Err(_) => return json!([false, []]),
+    };
+
+    let mut peers = vec![];
+    for (socket, transport, _) in &bootstrap.cache {
+        peers.push(json!([socket.to_string(), transport, "cache"]));
+    }
+    for (socket, transport) in &bootstrap.seeds {
+        peers.push(json!([socket.to_string(), transport, "seed"]));
+    }
+    if !bootstrap.private {
+        for peer in network_seeds() {
+            let transport = peer.transport.to_str();
+            peers.push(json!([peer.socket.to_string(), transport, "public"]));
+        }
+    }
+    json!([bootstrap.private, peers])
+}
@@ -0,0 +1,253 @@
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use tdn::types::{
+    primitive::Result,
+    rpc::{json, RpcParam},
+};
+
+use crate::rpc::unix::DEFAULT_UNIX_FILE;
+
+/// all sub-commands, if first argument not in it, it is daemon.
+const COMMANDS: [&str; 8] = [
+    "account", "friend", "chat", "session", "device", "wallet", "call", "watch",
+];
+
+const USAGE: &str = "Usage: esse [OPTIONS] <COMMAND>
+
+Commands:
+  account list                     list all accounts
+  account login <gid>              login the account (PIN from env ESSE_PIN or stdin)
+  friend list                      list all friends
+  chat send <fid> <text>           send a text message to friend
+  session list                     list all sessions
+  device list                      list all devices
+  device status <addr>             show the device status
+  wallet balance <network> <addr>  show the balance of the address
+  call <method> [params-json]      call the raw rpc method
+  watch [topic...]                 stream the pushed events
+
+Options:
+  --data <path>     daemon data directory [default: ./.tdn]
+  --socket <path>   daemon unix socket, always open [default: <data>/esse.sock]
+  --gid <gid>       current account (or env ESSE_GID)
+  --json            output raw JSON
+  --watch           stream the pushed events after command";
+
+/// Command-line options.
+pub(crate) struct Options {
+    data: PathBuf,
+    socket: Option<PathBuf>,
+    gid: String,
+    json: bool,
+    watch: bool,
+    args: Vec<String>,
+}
+
+impl Options {
+    /// parse the command line, return none if it is not a client command.
+    pub fn parse(args: Vec<String>) -> Option<Options> {
+        let mut options = Options {
+            data: PathBuf::from("./.tdn"),
+            socket: None,
+            gid: std::env::var("ESSE_GID").unwrap_or(String::new()),
+            json: false,
+            watch: false,
+            args: vec![],
+        };
+
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--data" => options.data = PathBuf::from(iter.next()?),
+                "--socket" => options.socket = Some(PathBuf::from(iter.next()?)),
+                "--gid" => options.gid = iter.next()?,
+                "--json" => options.json = true,
+                "--watch" => options.watch = true,
+                _ => options.args.push(arg),
+            }
+        }
+
+        if options.args.len() > 0 && COMMANDS.contains(&options.args[0].as_str()) {
+            Some(options)
+        } else {
+            None
+        }
+    }
+
+    fn socket(&self) -> PathBuf {
+        self.socket
+            .clone()
+            .unwrap_or(self.data.join(DEFAULT_UNIX_FILE))
+    }
+
+    fn arg(&self, i: usize) -> Result<&str> {
+        self.args
+            .get(i)
+            .map(|s| s.as_str())
+            .ok_or(anyhow!("missing arguments.\n\n{}", USAGE))
+    }
+
+    fn print(&self, value: &RpcParam) {
+        if self.json {
+            println!("{}", value);
+            return;
+        }
+
+        // table for list of rows.
+        if let Some(rows) = value.as_array() {
+            if rows.len() > 0 && rows.iter().all(|r| r.is_array()) {
+                for row in rows {
+                    let cells: Vec<String> = row
+                        .as_array()
+                        .unwrap() // safe, checked.
+                        .iter()
+                        .map(|c| match c.as_str() {
+                            Some(s) => s.to_owned(),
+                            None => c.to_string(),
+                        })
+                        .collect();
+                    println!("{}", cells.join("\t"));
+                }
+                return;
+            }
+        }
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(value).unwrap_or(value.to_string())
+        );
+    }
+}
+
+/// read the PIN from env `ESSE_PIN`, or prompt in terminal, or the line of stdin.
+/// not in the arguments, which are visible to other users in process list.
+fn read_pin() -> Result<String> {
+    if let Ok(pin) = std::env::var("ESSE_PIN") {
+        return Ok(pin);
+    }
+
+    let pin = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password("PIN: ")?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line.trim_end_matches(&['\r', '\n'][..]).to_owned()
+    };
+    Ok(pin)
+}
+
+/// run the client command, return the exit code.
+pub(crate) async fn run(options: Options) -> i32 {
+    match command(&options).await {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn command(_options: &Options) -> Result<()> {
+    Err(anyhow!("the client only support unix socket."))
+}
+
+#[cfg(unix)]
+async fn command(options: &Options) -> Result<()> {
+    let mut client = esse_sdk::Client::connect(&options.socket(), options.gid.clone()).await?;
+
+    let mut topics = vec![];
+    match (options.arg(0)?, options.args.get(1).map(|s| s.as_str())) {
+        ("account", Some("list")) => {
+            options.print(&client.call_raw("account-list", json!([])).await?);
+        }
+        ("account", Some("login")) => {
+            let gid = options.arg(2)?;
+            let pin = read_pin()?;
+            client.gid = gid.to_owned();
+            options.print(&client.call_raw("account-login", json!([gid, pin])).await?);
+        }
+        ("friend", Some("list")) => {
+            options.print(&client.call_raw("chat-friend-list", json!([false])).await?);
+        }
+        ("chat", Some("send")) => {
+            let fid: i64 = options.arg(2)?.parse()?;
+            let text = options.args[3..].join(" ");
+            if text.is_empty() {
+                return Err(anyhow!("message is empty."));
+            }
+
+            // find the friend's gid.
+            let friends = client.call_raw("chat-friend-list", json!([false])).await?;
+            let fgid = friends
+                .as_array()
+                .and_then(|fs| fs.iter().find(|f| f[0].as_i64() == Some(fid)))
+                .and_then(|f| f[1].as_str().map(|s| s.to_owned()))
+                .ok_or(anyhow!("friend is missing."))?;
+
+            // 0 is string message.
+            let params = json!([fid, fgid, 0, text]);
+            options.print(&client.call_raw("chat-message-create", params).await?);
+        }
+        ("session", Some("list")) => {
+            options.print(&client.call_raw("session-list", json!([])).await?);
+        }
+        ("device", Some("list")) => {
+            options.print(&client.call_raw("device-list", json!([])).await?);
+        }
+        ("device", Some("status")) => {
+            let addr = options.arg(2)?;
+            options.print(&client.call_raw("device-status", json!([addr])).await?);
+        }
+        ("wallet", Some("balance")) => {
+            let network: i64 = options.arg(2)?.parse()?;
+            let address = options.arg(3)?;
+            client
+                .call("wallet-token", json!([network, address]))
+                .await?;
+            // balance is pushed after query from chain.
+            loop {
+                let event = client.next_event_raw().await?;
+                if event["method"].as_str() == Some("wallet-balance") {
+                    options.print(&event["result"]);
+                    break;
+                }
+            }
+        }
+        ("call", Some(method)) => {
+            let params = match options.args.get(2) {
+                Some(p) => serde_json::from_str(p)?,
+                None => json!([]),
+            };
+            options.print(&client.call_raw(method, params).await?);
+        }
+        ("watch", _) => {
+            topics = options.args[1..].to_vec();
+        }
+        _ => return Err(anyhow!("unknown command.\n\n{}", USAGE)),
+    }
+
+    if options.watch || options.arg(0)? == "watch" {
+        let gids = if client.gid.is_empty() {
+            vec![]
+        } else {
+            vec![client.gid.clone()]
+        };
+        client.subscribe(gids, topics).await?;
+        loop {
+            let event = client.next_event_raw().await?;
+            if options.json {
+                println!("{}", event);
+            } else {
+                println!(
+                    "[{}] {} {}",
+                    event["gid"].as_str().unwrap_or(""),
+                    event["method"].as_str().unwrap_or(""),
+                    event["result"]
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+use tdn::prelude::Config;
+
+/// ESSE's options in the config.toml of data directory, besides TDN's options.
+/// loaded once when start, by the TDN's config loader.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub(crate) struct EsseConfig {
+    /// unix socket rpc, e.g. `rpc_unix = "/run/esse.sock"`, empty is the default file.
+    pub rpc_unix: Option<String>,
+    /// `p2p_private = true` will disable the public seeds.
+    pub p2p_private: bool,
+    /// seconds of checking the suspended sessions.
+    pub presence_heartbeat: Option<i64>,
+    /// seconds of keep-alive when the session suspended by both.
+    pub presence_suspend: Option<i64>,
+    /// `lan_discovery = true` will enable the local network discovery, default is disabled.
+    pub lan_discovery: bool,
+    /// `lan_discovery_friends = true` will also connect the friends.
+    pub lan_discovery_friends: bool,
+}
+
+impl EsseConfig {
+    pub async fn load(base: &PathBuf) -> Self {
+        Config::load_custom::<EsseConfig>(base.clone())
+            .await
+            .unwrap_or_default()
+    }
+}
@@ -7,13 +7,23 @@ extern crate anyhow;
 use std::env::args;
 
 mod account;
+mod actor;
 mod apps;
+mod audit;
+mod autologin;
+mod bootstrap;
+mod cli;
+mod config;
 mod consensus;
+mod discovery;
 mod event;
 mod group;
 mod layer;
 mod migrate;
+mod presence;
 mod primitives;
+mod reconnect;
+mod relay;
 mod rpc;
 mod server;
 mod session;
@@ -22,11 +32,38 @@ mod utils;
 
 #[tokio::main]
 async fn main() {
+    // client commands, e.g. `esse account list`.
+    if let Some(options) = cli::Options::parse(args().skip(1).collect()) {
+        std::process::exit(cli::run(options).await);
+    }
+
     let db_path = args().nth(1).unwrap_or("./.tdn".to_owned());
 
     if std::fs::metadata(&db_path).is_err() {
         std::fs::create_dir(&db_path).unwrap();
     }
 
+    tokio::spawn(async {
+        shutdown_signal().await;
+        server::stop();
+    });
+
     let _ = server::start(db_path).await;
 }
+
+/// waiting SIGINT (Ctrl-C) or SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {},
+                _ = term.recv() => {},
+            }
+            return;
+        }
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,428 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket as StdUdpSocket};
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+use tdn::types::{
+    group::GroupId,
+    message::SendMessage,
+    primitive::{Peer, PeerId, Result},
+    rpc::{json, rpc_response, RpcParam},
+};
+use tokio::{
+    net::UdpSocket,
+    sync::{mpsc::Sender, RwLock},
+};
+
+use crate::apps::chat::{chat_conn, Friend};
+use crate::config::EsseConfig;
+use crate::group::Group;
+use crate::layer::Layer;
+use crate::presence::now;
+use crate::server::push_rpc;
+use crate::storage::chat_db;
+
+/// the multicast group of the local network discovery.
+const MULTICAST_ADDR: Ipv4Addr = Ipv4Addr::new(239, 255, 83, 69);
+/// the multicast port of the local network discovery.
+const MULTICAST_PORT: u16 = 7365;
+/// the announce message prefix, every message is: ESSE peer_id port transport time macs...
+/// every mac is keyed by one of the running accounts, only my devices can verify it.
+const ANNOUNCE_PREFIX: &'static str = "ESSE";
+/// the context of the account's key which authenticate the announce.
+const DISCOVERY_KEY_CONTEXT: &'static str = "esse lan discovery";
+/// seconds of announcing this daemon.
+const ANNOUNCE_INTERVAL: u64 = 30;
+/// the peer is removed when not announced in these seconds.
+const PEER_EXPIRE: i64 = 90;
+/// the max peers saved, the untrusted peer is dropped when full.
+const DISCOVERED_MAX: usize = 256;
+/// the max untrusted announces handled in every announce interval, others are dropped.
+const UNTRUSTED_LIMIT: usize = 64;
+
+/// the peer found in the local network.
+struct Discovered {
+    peer: Peer,
+    /// the last time of received.
+    lasttime: i64,
+    /// the announce is authenticated by my account, it is my device.
+    trusted: bool,
+    /// the time in the last trusted announce, the older one is replayed.
+    announced: i64,
+}
+
+/// the peers found in the local network, peer_id => discovered.
+static DISCOVERED: Lazy<StdRwLock<HashMap<PeerId, Discovered>>> =
+    Lazy::new(|| StdRwLock::new(HashMap::new()));
+
+/// bind the multicast port, reuse it so other daemons in this host also can bind.
+fn bind() -> Result<UdpSocket> {
+    use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    socket.set_reuse_port(true)?;
+    let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, MULTICAST_PORT);
+    socket.bind(&SockAddr::from(addr))?;
+    socket.join_multicast_v4(&MULTICAST_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_nonblocking(true)?;
+    Ok(UdpSocket::from_std(StdUdpSocket::from(socket))?)
+}
+
+/// the keys of running accounts, which authenticate the announce.
+async fn keys(group: &Arc<RwLock<Group>>) -> Vec<[u8; 32]> {
+    let group_lock = group.read().await;
+    group_lock
+        .list_running_user()
+        .iter()
+        .filter_map(|gid| group_lock.derive_key(gid, DISCOVERY_KEY_CONTEXT).ok())
+        .collect()
+}
+
+fn mac(key: &[u8; 32], body: &str) -> String {
+    hex::encode(&blake3::keyed_hash(key, body.as_bytes()).as_bytes()[..16])
+}
+
+/// the announce message of this daemon, with the macs of running accounts.
+fn announce_message(self_id: &PeerId, p2p: &Peer, keys: &[[u8; 32]]) -> String {
+    let mut message = format!(
+        "{} {} {} {} {}",
+        ANNOUNCE_PREFIX,
+        self_id.to_hex(),
+        p2p.socket.port(),
+        p2p.transport.to_str(),
+        now()
+    );
+    let body = message.clone();
+    for key in keys {
+        message.push(' ');
+        message.push_str(&mac(key, &body));
+    }
+    message
+}
+
+/// parse the announce message, the peer's ip is the sender's ip.
+/// return the peer, announce time and it is trusted.
+fn parse(bytes: &[u8], from: SocketAddr, keys: &[[u8; 32]]) -> Option<(Peer, i64, bool)> {
+    let message = std::str::from_utf8(bytes).ok()?;
+    let mut items = message.split_whitespace();
+    if items.next() != Some(ANNOUNCE_PREFIX) {
+        return None;
+    }
+    let id = PeerId::from_hex(items.next()?).ok()?;
+    let port: u16 = items.next()?.parse().ok()?;
+    let transport = items.next()?;
+    let time: i64 = items.next()?.parse().ok()?;
+
+    let body = format!(
+        "{} {} {} {} {}",
+        ANNOUNCE_PREFIX,
+        id.to_hex(),
+        port,
+        transport,
+        time
+    );
+    let macs: Vec<&str> = items.collect();
+    let trusted = (now() - time).abs() < PEER_EXPIRE
+        && keys.iter().any(|key| {
+            let mac = mac(key, &body);
+            macs.iter().any(|m| *m == mac)
+        });
+
+    let mut peer = Peer::socket_transport(SocketAddr::new(from.ip(), port), transport);
+    peer.id = id;
+    Some((peer, time, trusted))
+}
+
+/// save the peer, return true if it is new, expired or becomes trusted.
+/// the untrusted announce never replaces my device's socket.
+fn discovered(peer: &Peer, time: i64, trusted: bool) -> bool {
+    let mut peers = match DISCOVERED.write() {
+        Ok(peers) => peers,
+        Err(_) => return false,
+    };
+    let now = now();
+    peers.retain(|_, d| now - d.lasttime < PEER_EXPIRE);
+
+    let was_trusted = match peers.get(&peer.id) {
+        Some(d) if d.trusted && (!trusted || time <= d.announced) => return false,
+        Some(d) => Some(d.trusted),
+        None => None,
+    };
+    // full, only my device can replace the oldest untrusted peer.
+    if was_trusted.is_none() && peers.len() >= DISCOVERED_MAX {
+        let oldest = peers
+            .iter()
+            .filter(|(_, d)| !d.trusted)
+            .min_by_key(|(_, d)| d.lasttime)
+            .map(|(id, _)| *id);
+        match oldest {
+            Some(id) if trusted => {
+                peers.remove(&id);
+            }
+            _ => return false,
+        }
+    }
+    peers.insert(
+        peer.id,
+        Discovered {
+            peer: peer.clone(),
+            lasttime: now,
+            trusted,
+            announced: time,
+        },
+    );
+    was_trusted.map(|t| !t && trusted).unwrap_or(true)
+}
+
+/// count the untrusted announce in the window (start time, count),
+/// return false if it is over the limit in this interval.
+fn untrusted_allowed(window: &mut (i64, usize), now: i64) -> bool {
+    if now - window.0 >= ANNOUNCE_INTERVAL as i64 {
+        *window = (now, 0);
+    }
+    window.1 += 1;
+    window.1 <= UNTRUSTED_LIMIT
+}
+
+/// the peer with the local network socket if discovered as my device, e.g. pairing the new device.
+pub(crate) fn peer(id: PeerId) -> Peer {
+    DISCOVERED
+        .read()
+        .ok()
+        .and_then(|peers| peers.get(&id).filter(|d| d.trusted).map(|d| d.peer.clone()))
+        .unwrap_or(Peer::peer(id))
+}
+
+/// list my devices found in local network: [[addr, socket, lasttime]].
+pub(crate) fn list() -> RpcParam {
+    let now = now();
+    let mut peers = vec![];
+    if let Ok(discovered) = DISCOVERED.read() {
+        for (id, d) in discovered.iter() {
+            if d.trusted && now - d.lasttime < PEER_EXPIRE {
+                peers.push(json!([id.to_hex(), d.peer.socket.to_string(), d.lasttime]));
+            }
+        }
+    }
+    json!(peers)
+}
+
+/// start the local network discovery, if it is enabled in config.toml.
+pub(crate) fn start(
+    config: &EsseConfig,
+    self_id: PeerId,
+    p2p: &Peer,
+    group: Arc<RwLock<Group>>,
+    layer: Arc<RwLock<Layer>>,
+    sender: Sender<SendMessage>,
+) {
+    if !config.lan_discovery {
+        info!("LAN discovery disabled.");
+        return;
+    }
+    let friends = config.lan_discovery_friends;
+
+    let socket = match bind() {
+        Ok(socket) => Arc::new(socket),
+        Err(e) => {
+            error!("LAN discovery failure: {}", e);
+            return;
+        }
+    };
+    info!(
+        "LAN discovery   : {}:{}, friends {}",
+        MULTICAST_ADDR, MULTICAST_PORT, friends
+    );
+
+    tokio::spawn(announcing(
+        socket.clone(),
+        self_id,
+        p2p.clone(),
+        group.clone(),
+    ));
+    tokio::spawn(listen(
+        socket,
+        self_id,
+        p2p.clone(),
+        friends,
+        group,
+        layer,
+        sender,
+    ));
+}
+
+async fn announce(socket: &UdpSocket, self_id: &PeerId, p2p: &Peer, group: &Arc<RwLock<Group>>) {
+    let target = SocketAddrV4::new(MULTICAST_ADDR, MULTICAST_PORT);
+    let message = announce_message(self_id, p2p, &keys(group).await);
+    if let Err(e) = socket.send_to(message.as_bytes(), target).await {
+        debug!("LAN discovery announce failure: {}", e);
+    }
+}
+
+async fn announcing(socket: Arc<UdpSocket>, self_id: PeerId, p2p: Peer, group: Arc<RwLock<Group>>) {
+    loop {
+        announce(&socket, &self_id, &p2p, &group).await;
+        tokio::time::sleep(Duration::from_secs(ANNOUNCE_INTERVAL)).await;
+    }
+}
+
+async fn listen(
+    socket: Arc<UdpSocket>,
+    self_id: PeerId,
+    p2p: Peer,
+    friends: bool,
+    group: Arc<RwLock<Group>>,
+    layer: Arc<RwLock<Layer>>,
+    sender: Sender<SendMessage>,
+) {
+    let mut buf = [0u8; 1024];
+    let mut untrusted_window = (now(), 0);
+    loop {
+        let (size, from) = match socket.recv_from(&mut buf).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("LAN discovery receive failure: {}", e);
+                tokio::time::sleep(Duration::from_secs(ANNOUNCE_INTERVAL)).await;
+                continue;
+            }
+        };
+        let keys = keys(&group).await;
+        let (peer, time, trusted) = match parse(&buf[..size], from, &keys) {
+            Some((peer, time, trusted)) if peer.id != self_id => (peer, time, trusted),
+            _ => continue,
+        };
+        // only my devices and my friends (if enabled) are needed.
+        if !trusted && !friends {
+            continue;
+        }
+        if !trusted && !untrusted_allowed(&mut untrusted_window, now()) {
+            continue;
+        }
+        if !discovered(&peer, time, trusted) {
+            continue;
+        }
+
+        debug!(
+            "LAN discovery found: {} {}, trusted {}",
+            peer.id.to_hex(),
+            peer.socket,
+            trusted
+        );
+        if trusted {
+            // announce again, so my new device find me quickly.
+            announce(&socket, &self_id, &p2p, &group).await;
+            let param = json!([[peer.id.to_hex(), peer.socket.to_string(), now()]]);
+            let _ = push_rpc(
+                &sender,
+                rpc_response(0, "device-discover", param, GroupId::default()),
+            )
+            .await;
+        }
+
+        match connect_messages(&group, &layer, peer, trusted, friends).await {
+            Ok(messages) => {
+                for msg in messages {
+                    let _ = sender.send(msg).await;
+                }
+            }
+            Err(e) => error!("LAN discovery connect failure: {}", e),
+        }
+    }
+}
+
+/// connect the peer directly when it is my offline device, or my friend if enabled.
+/// the friend's connect is verified by the friend's proof, so it need not trusted.
+async fn connect_messages(
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    peer: Peer,
+    trusted: bool,
+    friends: bool,
+) -> Result<Vec<SendMessage>> {
+    // the friends which not online, lock the layer before the group.
+    let mut offlines = vec![];
+    if friends {
+        let layer_lock = layer.read().await;
+        let base = layer_lock.base().clone();
+        for (mgid, running) in layer_lock.runnings.iter() {
+            // only the account's layer, not the services'.
+            if &running.owner_height_id().0 != mgid {
+                continue;
+            }
+            let db = chat_db(&base, mgid)?;
+            for friend in Friend::list(&db)? {
+                if friend.addr == peer.id && !friend.is_closed && !running.is_online(&friend.gid) {
+                    offlines.push((*mgid, friend.gid));
+                }
+            }
+        }
+    }
+
+    let mut messages = vec![];
+    let group_lock = group.read().await;
+    if trusted {
+        for gid in group_lock.list_running_user() {
+            let running = group_lock.running(&gid)?;
+            if let Some((_, _, online)) = running.distributes.get(&peer.id) {
+                if !online {
+                    let msg = group_lock.connect_message(&gid, peer.clone())?;
+                    messages.push(SendMessage::Group(gid, msg));
+                }
+            }
+        }
+    }
+    for (mgid, fgid) in offlines {
+        if let Ok(proof) = group_lock.prove_addr(&mgid, &peer.id) {
+            messages.push(SendMessage::Layer(
+                mgid,
+                fgid,
+                chat_conn(proof, peer.clone()),
+            ));
+        }
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announce_trusted_by_account_key() {
+        let id = PeerId::default();
+        let p2p = Peer::socket_transport("127.0.0.1:7364".parse().unwrap(), "quic");
+        let key = [2u8; 32];
+        let other = [3u8; 32];
+        let from: SocketAddr = "192.168.1.2:7365".parse().unwrap();
+
+        let message = announce_message(&id, &p2p, &[key]);
+        let (peer, _, trusted) = parse(message.as_bytes(), from, &[other, key]).unwrap();
+        assert!(trusted);
+        assert_eq!(peer.id, id);
+        assert_eq!(peer.socket, "192.168.1.2:7364".parse().unwrap());
+
+        let (_, _, trusted) = parse(message.as_bytes(), from, &[other]).unwrap();
+        assert!(!trusted);
+
+        // changed port not match the mac.
+        let forged = message.replacen(" 7364 ", " 7000 ", 1);
+        let (_, _, trusted) = parse(forged.as_bytes(), from, &[key]).unwrap();
+        assert!(!trusted);
+    }
+
+    #[test]
+    fn untrusted_rate_limited() {
+        let mut window = (100, 0);
+        for _ in 0..UNTRUSTED_LIMIT {
+            assert!(untrusted_allowed(&mut window, 110));
+        }
+        assert!(!untrusted_allowed(&mut window, 110));
+        // next interval.
+        assert!(untrusted_allowed(
+            &mut window,
+            100 + ANNOUNCE_INTERVAL as i64
+        ));
+    }
+}
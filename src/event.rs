@@ -13,21 +13,25 @@ use tokio::sync::{mpsc::Sender, RwLock};
 
 use chat_types::NetworkMessage;
 
-use crate::account::{Account, User};
+use crate::account::{Account, Profile, User};
 use crate::apps::chat::LayerEvent;
+use crate::audit::{Audit, AuditKind, AUDIT_KEY_CONTEXT};
 use crate::consensus::Event;
 use crate::group::{Group, GroupEvent};
 use crate::layer::Layer;
 use crate::migrate::consensus::{
-    ACCOUNT_TABLE_PATH, FILE_TABLE_PATH, FRIEND_TABLE_PATH, MESSAGE_TABLE_PATH, REQUEST_TABLE_PATH,
+    ACCOUNT_TABLE_PATH, AUDIT_TABLE_PATH, FILE_TABLE_PATH, FRIEND_TABLE_PATH, LABEL_TABLE_PATH,
+    MESSAGE_TABLE_PATH, REQUEST_TABLE_PATH, SESSION_TABLE_PATH,
 };
 
 use crate::apps::chat::rpc as chat_rpc;
-use crate::apps::chat::{from_model, handle_nmsg, Friend, Message, Request};
+use crate::apps::chat::{from_model, handle_nmsg, Friend, Label, Message, Request};
 use crate::apps::file::{FileDid, RootDirectory};
 use crate::rpc;
+use crate::session::{Session, SessionNotify};
 use crate::storage::{
-    account_db, chat_db, consensus_db, delete_avatar_sync, read_avatar_sync, write_avatar_sync,
+    account_db, chat_db, consensus_db, delete_avatar_sync, read_avatar_sync, session_db,
+    write_avatar_sync,
 };
 
 /// Event that will update data.
@@ -70,6 +74,27 @@ pub(crate) enum InnerEvent {
     FileBackup(FileDid, PeerId),
     /// delete a file.
     FileDelete(FileDid),
+    /// Session's preferences update.
+    /// params: session's remote gid, is_top, notify, is_archived, mute_until.
+    SessionUpdate(GroupId, bool, SessionNotify, bool, i64),
+    /// create a friend's label.
+    /// params: label's name.
+    LabelCreate(String),
+    /// delete a friend's label.
+    /// params: label's name.
+    LabelDelete(String),
+    /// add/remove a friend to/from a label.
+    /// params: label's name, friend's gid, is_add.
+    LabelFriend(String, GroupId, bool),
+    /// account's profile update.
+    /// params: profile.
+    UserProfile(Profile),
+    /// Session's friend profile update by friend.
+    /// params: f_gid, profile.
+    SessionFriendProfile(GroupId, Profile),
+    /// sensitive action audit.
+    /// params: kind, info, device, datetime.
+    Audit(i64, String, PeerId, i64),
 }
 
 /// Event that not update status. only change UI.
@@ -106,6 +131,14 @@ pub(crate) enum SyncEvent {
     /// eid, friend_gid, msg_id, is_me, message.
     Message(EventId, GroupId, EventId, bool, NetworkMessage),
     None,
+    /// eid, session's remote gid, is_top, notify, is_archived, mute_until.
+    Session(EventId, GroupId, bool, SessionNotify, bool, i64),
+    /// eid, label's name, all friends's gid, is_deleted.
+    Label(EventId, String, Vec<GroupId>, bool),
+    /// eid, name, avatar, profile.
+    AccountProfile(EventId, String, Vec<u8>, Profile),
+    /// eid, kind, info, device, datetime.
+    Audit(EventId, i64, String, PeerId, i64),
 }
 
 impl InnerEvent {
@@ -450,6 +483,90 @@ impl InnerEvent {
                 // TODO
                 (FILE_TABLE_PATH, 0)
             }
+            InnerEvent::SessionUpdate(sgid, is_top, notify, is_archived, mute_until) => {
+                let db = session_db(group.base(), &gid)?;
+                if let Ok(mut s) = Session::get_by_gid(&db, &sgid) {
+                    s.is_top = is_top;
+                    s.notify = notify;
+                    s.is_archived = is_archived;
+                    s.mute_until = mute_until;
+                    Session::update(
+                        &db,
+                        &s.id,
+                        s.is_top,
+                        s.is_close,
+                        s.is_archived,
+                        s.mute_until,
+                    )?;
+                    Session::update_notify(&db, &s.id, &s.notify)?;
+                    results.rpcs.push(rpc::session_update(gid, &s));
+                    (SESSION_TABLE_PATH, s.id)
+                } else {
+                    return Ok(());
+                }
+            }
+            InnerEvent::UserProfile(profile) => {
+                results.rpcs.push(rpc::account_profile(gid, &profile));
+                group.update_profile(gid, profile)?;
+                (ACCOUNT_TABLE_PATH, 0)
+            }
+            InnerEvent::SessionFriendProfile(rgid, profile) => {
+                let db = chat_db(group.base(), &gid)?;
+                if let Ok(mut f) = Friend::get_id(&db, &rgid) {
+                    f.profile = profile;
+                    f.remote_update(&db)?;
+                    results.rpcs.push(chat_rpc::friend_info(gid, &f));
+                    (FRIEND_TABLE_PATH, f.id)
+                } else {
+                    return Ok(());
+                }
+            }
+            InnerEvent::Audit(kind, info, device, datetime) => {
+                let kind = AuditKind::from_i64(kind);
+                let key = group.derive_key(&gid, AUDIT_KEY_CONTEXT)?;
+                let mut audit = Audit::from_remote(kind, info, device, datetime);
+                audit.insert(&db, &key)?;
+                results.rpcs.push(rpc::audit_create(gid, &audit));
+                (AUDIT_TABLE_PATH, audit.id)
+            }
+            InnerEvent::LabelCreate(name) => {
+                let db = chat_db(group.base(), &gid)?;
+                let mut label = Label::new(name);
+                label.insert(&db)?;
+                let friends = Label::friends(&db, &label.id)?;
+                results
+                    .rpcs
+                    .push(chat_rpc::label_create(gid, &label, friends));
+                (LABEL_TABLE_PATH, label.id)
+            }
+            InnerEvent::LabelDelete(name) => {
+                let db = chat_db(group.base(), &gid)?;
+                if let Ok(label) = Label::get_by_name(&db, &name) {
+                    Label::delete(&db, &label.id)?;
+                    results.rpcs.push(chat_rpc::label_delete(gid, label.id));
+                    (LABEL_TABLE_PATH, label.id)
+                } else {
+                    return Ok(());
+                }
+            }
+            InnerEvent::LabelFriend(name, fgid, is_add) => {
+                let db = chat_db(group.base(), &gid)?;
+                if let Ok(f) = Friend::get_id(&db, &fgid) {
+                    let mut label = Label::new(name);
+                    label.insert(&db)?;
+                    if is_add {
+                        Label::add_friend(&db, &label.id, &f.id)?;
+                    } else {
+                        Label::remove_friend(&db, &label.id, &f.id)?;
+                    }
+                    results
+                        .rpcs
+                        .push(chat_rpc::label_friend(gid, label.id, f.id, is_add));
+                    (LABEL_TABLE_PATH, label.id)
+                } else {
+                    return Ok(());
+                }
+            }
         };
 
         Event::merge(&db, eid, path, id, merge_height)?;
@@ -543,7 +660,8 @@ impl SyncEvent {
 
                     let name = account.name.clone();
                     let avatar = account.avatar.clone();
-                    events.push(SyncEvent::Account(hash, name, avatar));
+                    let profile = account.profile.clone();
+                    events.push(SyncEvent::AccountProfile(hash, name, avatar, profile));
                 }
                 REQUEST_TABLE_PATH => {
                     let db = chat_db(base, gid)?;
@@ -633,6 +751,44 @@ impl SyncEvent {
                 FILE_TABLE_PATH => {
                     //
                 }
+                SESSION_TABLE_PATH => {
+                    let db = session_db(base, gid)?;
+                    let event = if let Ok(s) = Session::get(&db, &row) {
+                        SyncEvent::Session(
+                            hash,
+                            s.gid,
+                            s.is_top,
+                            s.notify,
+                            s.is_archived,
+                            s.mute_until,
+                        )
+                    } else {
+                        SyncEvent::None
+                    };
+
+                    events.push(event);
+                }
+                LABEL_TABLE_PATH => {
+                    let db = chat_db(base, gid)?;
+                    let event = if let Ok(label) = Label::get(&db, &row) {
+                        let friends = Label::friends_gid(&db, &label.id)?;
+                        SyncEvent::Label(hash, label.name, friends, label.is_deleted)
+                    } else {
+                        SyncEvent::None
+                    };
+
+                    events.push(event);
+                }
+                AUDIT_TABLE_PATH => {
+                    let db = consensus_db(base, gid)?;
+                    let event = if let Ok(a) = Audit::get(&db, &row) {
+                        SyncEvent::Audit(hash, a.kind.to_i64(), a.info, a.device, a.datetime)
+                    } else {
+                        SyncEvent::None
+                    };
+
+                    events.push(event);
+                }
                 _ => {}
             }
         }
@@ -644,12 +800,14 @@ impl SyncEvent {
         Ok(events)
     }
 
-    pub fn handle(
+    /// handle the synced events, the group is locked only when read and update the account,
+    /// so a large sync not block other accounts.
+    pub async fn handle(
         gid: GroupId,
         from: u64,
         to: u64,
         events: Vec<SyncEvent>,
-        group: &mut Group,
+        group: &Arc<RwLock<Group>>,
         layer: &Arc<RwLock<Layer>>,
         results: &mut HandleResult,
         addr: PeerId,
@@ -657,8 +815,65 @@ impl SyncEvent {
         if events.len() as u64 != to + 1 - from {
             return Ok(());
         }
-        let base = group.base().clone();
-        let consensus_db = consensus_db(&base, &gid)?;
+
+        let group_lock = group.read().await;
+        let base = group_lock.base().clone();
+        let sender = group_lock.sender();
+        let key = group_lock.derive_key(&gid, AUDIT_KEY_CONTEXT).ok();
+        let account = group_lock.account(&gid)?;
+        let mut height = (account.own_height, account.event);
+        drop(group_lock);
+
+        let mut info = None;
+        let mut profile = None;
+        let res = SyncEvent::apply(
+            gid,
+            from,
+            events,
+            &base,
+            &key,
+            &sender,
+            layer,
+            results,
+            addr,
+            &mut height,
+            &mut info,
+            &mut profile,
+        );
+
+        // update the account even if failure, the applied events had merged.
+        let mut group_lock = group.write().await;
+        if let Some((name, avatar)) = info {
+            group_lock.update_account(gid, &name, avatar)?;
+        }
+        if let Some(profile) = profile {
+            group_lock.update_profile(gid, profile)?;
+        }
+        let account = group_lock.account_mut(&gid)?;
+        if (account.own_height, account.event) != height {
+            let account_db = account_db(&base)?;
+            account.update_consensus(&account_db, height.0, height.1)?;
+            account_db.close()?;
+        }
+        res
+    }
+
+    /// apply the synced events to the storage, without the group.
+    fn apply(
+        gid: GroupId,
+        from: u64,
+        events: Vec<SyncEvent>,
+        base: &PathBuf,
+        key: &Option<[u8; 32]>,
+        sender: &Sender<SendMessage>,
+        layer: &Arc<RwLock<Layer>>,
+        results: &mut HandleResult,
+        addr: PeerId,
+        own: &mut (u64, EventId),
+        info: &mut Option<(String, Vec<u8>)>,
+        profile: &mut Option<Profile>,
+    ) -> Result<()> {
+        let consensus_db = consensus_db(base, &gid)?;
 
         let mut next = from;
         for event in events {
@@ -672,7 +887,11 @@ impl SyncEvent {
                 | SyncEvent::RequestHad(eid, ..)
                 | SyncEvent::Friend(eid, ..)
                 | SyncEvent::FriendHad(eid, ..)
-                | SyncEvent::Message(eid, ..) => {
+                | SyncEvent::Message(eid, ..)
+                | SyncEvent::Session(eid, ..)
+                | SyncEvent::Label(eid, ..)
+                | SyncEvent::AccountProfile(eid, ..)
+                | SyncEvent::Audit(eid, ..) => {
                     if Event::contains_hash(&consensus_db, eid)? {
                         continue;
                     }
@@ -687,10 +906,19 @@ impl SyncEvent {
                     results
                         .rpcs
                         .push(rpc::account_update(gid, &name, base64::encode(&avatar)));
-                    group.update_account(gid, &name, avatar)?;
+                    *info = Some((name, avatar));
                     (eid, ACCOUNT_TABLE_PATH, 0)
                 }
                 SyncEvent::AccountHad(eid) => (eid, ACCOUNT_TABLE_PATH, 0),
+                SyncEvent::AccountProfile(eid, name, avatar, p) => {
+                    results
+                        .rpcs
+                        .push(rpc::account_update(gid, &name, base64::encode(&avatar)));
+                    results.rpcs.push(rpc::account_profile(gid, &p));
+                    *info = Some((name, avatar));
+                    *profile = Some(p);
+                    (eid, ACCOUNT_TABLE_PATH, 0)
+                }
                 SyncEvent::Request(
                     eid,
                     rgid,
@@ -767,7 +995,7 @@ impl SyncEvent {
                             let layer_lock = layer.clone();
                             let ggid = gid.clone();
                             let fgid = friend.gid;
-                            let sender = group.sender();
+                            let sender = sender.clone();
                             tokio::spawn(async move {
                                 let online = layer_lock.write().await.remove_online(&ggid, &fgid);
                                 if let Some(faddr) = online {
@@ -815,26 +1043,82 @@ impl SyncEvent {
 
                     (eid, MESSAGE_TABLE_PATH, id)
                 }
+                SyncEvent::Session(eid, sgid, is_top, notify, is_archived, mute_until) => {
+                    let session_db = session_db(&base, &gid)?;
+                    let id = if let Ok(mut s) = Session::get_by_gid(&session_db, &sgid) {
+                        s.is_top = is_top;
+                        s.notify = notify;
+                        s.is_archived = is_archived;
+                        s.mute_until = mute_until;
+                        Session::update(
+                            &session_db,
+                            &s.id,
+                            s.is_top,
+                            s.is_close,
+                            s.is_archived,
+                            s.mute_until,
+                        )?;
+                        Session::update_notify(&session_db, &s.id, &s.notify)?;
+                        results.rpcs.push(rpc::session_update(gid, &s));
+                        s.id
+                    } else {
+                        -1
+                    };
+
+                    (eid, SESSION_TABLE_PATH, id)
+                }
+                SyncEvent::Label(eid, name, fgids, is_deleted) => {
+                    let chat_db = chat_db(&base, &gid)?;
+                    let mut label = Label::new(name);
+                    label.insert(&chat_db)?;
+                    if is_deleted {
+                        Label::delete(&chat_db, &label.id)?;
+                        results.rpcs.push(chat_rpc::label_delete(gid, label.id));
+                    } else {
+                        // the full membership, so removed friends also sync.
+                        let mut fids = vec![];
+                        for fgid in fgids {
+                            if let Ok(f) = Friend::get_id(&chat_db, &fgid) {
+                                fids.push(f.id);
+                            }
+                        }
+                        Label::set_friends(&chat_db, &label.id, &fids)?;
+                        results.rpcs.push(chat_rpc::label_create(gid, &label, fids));
+                    }
+
+                    (eid, LABEL_TABLE_PATH, label.id)
+                }
+                SyncEvent::Audit(eid, kind, info, device, datetime) => {
+                    // the locked account has no key, skip the audit, not break the sync.
+                    let id = if let Some(key) = key {
+                        let kind = AuditKind::from_i64(kind);
+                        let mut audit = Audit::from_remote(kind, info, device, datetime);
+                        audit.insert(&consensus_db, key)?;
+                        results.rpcs.push(rpc::audit_create(gid, &audit));
+                        audit.id
+                    } else {
+                        warn!("Account is locked, skip the synced audit.");
+                        -1
+                    };
+
+                    (eid, AUDIT_TABLE_PATH, id)
+                }
                 SyncEvent::None => {
                     continue;
                 }
             };
 
-            let account = group.account_mut(&gid)?;
             let (merge_height, next_height, next_eid) = InnerEvent::merge_event(
                 &consensus_db,
                 &addr,
                 results,
-                account.own_height,
-                account.event,
+                own.0,
+                own.1,
                 height,
                 eid,
                 None,
             )?;
-
-            let account_db = account_db(&base)?;
-            account.update_consensus(&account_db, next_height, next_eid)?;
-            account_db.close()?;
+            *own = (next_height, next_eid);
 
             Event::merge(&consensus_db, eid, path, id, merge_height)?;
         }
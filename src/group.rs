@@ -10,14 +10,19 @@ use tdn::types::{
 use tdn_did::Proof;
 use tokio::sync::{mpsc::Sender, RwLock};
 
-use crate::account::{Account, User};
+use crate::account::{Account, AccountMeta, Profile, User};
+use crate::apps::chat::Friend;
 use crate::apps::device::rpc as device_rpc;
 use crate::apps::device::Device;
+use crate::audit::{Audit, AuditKind, AUDIT_KEY_CONTEXT};
+use crate::autologin;
 use crate::consensus::Event;
 use crate::event::{InnerEvent, StatusEvent, SyncEvent};
 use crate::layer::Layer;
+use crate::migrate::{account_migrate, consensus::AUDIT_TABLE_PATH};
+use crate::relay;
 use crate::rpc;
-use crate::storage::{account_db, account_init, consensus_db, write_avatar};
+use crate::storage::{account_db, account_init, chat_db, consensus_db, write_avatar};
 use crate::utils::crypto::{decrypt, encrypt};
 use crate::utils::device_status::{device_info, device_status as local_device_status};
 
@@ -25,6 +30,19 @@ pub(crate) mod running;
 
 use running::RunningAccount;
 
+/// the methods which can be called when the account is locked (restricted mode).
+const LOCKED_METHODS: [&str; 9] = [
+    "echo",
+    "rpc-discover",
+    "account-system-info",
+    "account-list",
+    "account-pin-check",
+    "account-login",
+    "account-logout",
+    "account-lock",
+    "account-unlock",
+];
+
 /// Esse group.
 pub(crate) struct Group {
     /// storage base path.
@@ -35,7 +53,9 @@ pub(crate) struct Group {
     sender: Sender<SendMessage>,
     /// current address.
     addr: PeerId,
-    /// all accounts.
+    /// all accounts' metadata, the newest first.
+    metas: Vec<AccountMeta>,
+    /// the loaded accounts, others are loaded when login.
     accounts: HashMap<GroupId, Account>,
     /// distributed devices.
     runnings: HashMap<GroupId, RunningAccount>,
@@ -74,6 +94,121 @@ pub(crate) enum GroupEvent {
     SyncRequest(u64, u64),
     /// Sync height from..last_to, to, response.
     SyncResponse(u64, u64, u64, Vec<SyncEvent>),
+    /// ask the device to relay the session. params: friend's gid.
+    RelayRequest(GroupId),
+    /// relay response. params: friend's gid, is_ok.
+    RelayResponse(GroupId, bool),
+    /// the layer event which need relay to friend. params: friend's gid, event.
+    RelaySend(GroupId, Vec<u8>),
+    /// the layer event which friend sent. params: friend's gid, friend's addr, event.
+    RelayRecv(GroupId, PeerId, Vec<u8>),
+    /// Device status response with auto-login.
+    /// (cpu_num, memory_space, swap_space, disk_space, cpu%, memory%, swap%, disk%, uptime,
+    /// is_autologin).
+    StatusAutologinResponse(u32, u32, u32, u32, u16, u16, u16, u16, u32, bool),
+    /// stop the relay, the session connected directly or closed. params: friend's gid.
+    RelayStop(GroupId),
+}
+
+/// handle the group message, the group is locked only when needed,
+/// so the large sync and relay not block other accounts.
+pub(crate) async fn group_handle(
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    gid: GroupId,
+    msg: RecvType,
+    uid: u64,
+) -> Result<HandleResult> {
+    // check account is online, if not online, nothing.
+    if group.read().await.running(&gid).is_err() {
+        return Ok(HandleResult::new());
+    }
+
+    let (addr, event): (PeerId, GroupEvent) = match msg {
+        RecvType::Event(addr, bytes) => (addr, bincode::deserialize(&bytes)?),
+        msg => return group.write().await.handle(gid, msg, layer, uid).await,
+    };
+
+    match event {
+        GroupEvent::SyncRequest(from, to) => {
+            let group_lock = group.read().await;
+            let base = group_lock.base.clone();
+            let account = group_lock.account(&gid)?.clone();
+            drop(group_lock);
+
+            let mut results = HandleResult::new();
+            let msg = sync_response(&base, &gid, &account, addr, from, to).await?;
+            results.groups.push((gid, msg));
+            Ok(results)
+        }
+        GroupEvent::SyncResponse(from, last_to, to, events) => {
+            debug!("Sync Response: from: {} last {}, to {}", from, last_to, to);
+            let mut results = HandleResult::new();
+            if last_to < to {
+                let event = GroupEvent::SyncRequest(last_to + 1, to);
+                let data = bincode::serialize(&event).unwrap_or(vec![]);
+                results.groups.push((gid, SendType::Event(0, addr, data)));
+            }
+            SyncEvent::handle(gid, from, last_to, events, group, layer, &mut results, addr).await?;
+            Ok(results)
+        }
+        GroupEvent::RelayRequest(..)
+        | GroupEvent::RelayResponse(..)
+        | GroupEvent::RelaySend(..)
+        | GroupEvent::RelayRecv(..)
+        | GroupEvent::RelayStop(..) => relay::handle(group, layer, gid, addr, event).await,
+        event => {
+            let mut group_lock = group.write().await;
+            GroupEvent::handle(&mut group_lock, event, gid, addr, layer, uid).await
+        }
+    }
+}
+
+/// restricted mode, when account is locked, queue the layer event of friends until unlock.
+/// the group is write locked only when the account is locked.
+pub(crate) async fn check_locked(
+    group: &Arc<RwLock<Group>>,
+    gid: &GroupId,
+    fgid: GroupId,
+    msg: RecvType,
+) -> Option<RecvType> {
+    let group_lock = group.read().await;
+    if !group_lock.is_locked(gid) {
+        return Some(msg);
+    }
+
+    // only queue the friend's events, others are dropped.
+    let is_friend = match chat_db(group_lock.base(), gid) {
+        Ok(db) => {
+            let is_friend = Friend::get_id(&db, &fgid).is_ok();
+            let _ = db.close();
+            is_friend
+        }
+        Err(_) => false,
+    };
+    drop(group_lock);
+    if !is_friend {
+        return None;
+    }
+
+    group.write().await.queue_locked(gid, fgid, msg)
+}
+
+/// the sync events of the request, every time sync MAX is 100.
+async fn sync_response(
+    base: &PathBuf,
+    gid: &GroupId,
+    account: &Account,
+    addr: PeerId,
+    from: u64,
+    to: u64,
+) -> Result<SendType> {
+    debug!("Sync Request: from: {} to {}", from, to);
+    let last_to = if to - from > 100 { to - 100 } else { to };
+    let sync_events = SyncEvent::sync(base, gid, account, from, last_to).await?;
+    let event = GroupEvent::SyncResponse(from, last_to, to, sync_events);
+    let data = bincode::serialize(&event).unwrap_or(vec![]);
+    Ok(SendType::Event(0, addr, data))
 }
 
 impl Group {
@@ -251,6 +386,7 @@ impl Group {
         secret: [u8; 32],
         sender: Sender<SendMessage>,
         addr: PeerId,
+        metas: Vec<AccountMeta>,
         accounts: HashMap<GroupId, Account>,
         base: PathBuf,
     ) -> Result<Group> {
@@ -258,6 +394,7 @@ impl Group {
             secret,
             sender,
             addr,
+            metas,
             accounts,
             base,
             runnings: HashMap::new(),
@@ -279,11 +416,38 @@ impl Group {
     pub fn check_lock(&self, gid: &GroupId, lock: &str) -> bool {
         if let Some(account) = self.accounts.get(gid) {
             account.check_lock(&self.secret, lock).is_ok()
+        } else if self.is_account(gid) {
+            // not loaded account, check it in db, not load it.
+            account_db(&self.base)
+                .and_then(|db| Account::get(&db, gid))
+                .map(|account| account.check_lock(&self.secret, lock).is_ok())
+                .unwrap_or(false)
         } else {
             false
         }
     }
 
+    fn is_account(&self, gid: &GroupId) -> bool {
+        self.metas.iter().any(|m| &m.gid == gid)
+    }
+
+    /// load the full account, and migrate its databases, when it login first time.
+    pub fn load_account(&mut self, gid: &GroupId) -> Result<()> {
+        if self.accounts.contains_key(gid) {
+            return Ok(());
+        }
+        if !self.is_account(gid) {
+            return Err(anyhow!("user missing."));
+        }
+
+        account_migrate(&self.base, gid)?;
+        let account_db = account_db(&self.base)?;
+        let account = Account::get(&account_db, gid)?;
+        account_db.close()?;
+        self.accounts.insert(*gid, account);
+        Ok(())
+    }
+
     pub fn account(&self, gid: &GroupId) -> Result<&Account> {
         if let Some(account) = self.accounts.get(gid) {
             Ok(account)
@@ -318,7 +482,18 @@ impl Group {
 
     pub fn prove_addr(&self, mgid: &GroupId, raddr: &PeerId) -> Result<Proof> {
         let running = self.running(mgid)?;
-        Ok(Proof::prove(&running.keypair, &self.addr, raddr))
+        Ok(Proof::prove(running.keypair()?, &self.addr, raddr))
+    }
+
+    /// the account's local key, e.g. encrypt the data stored for others.
+    pub fn derive_key(&self, mgid: &GroupId, context: &str) -> Result<[u8; 32]> {
+        self.running(mgid)?.derive_key(context)
+    }
+
+    /// prove the addr is mine and sign the invitation code's digest.
+    pub fn prove_invitation(&self, mgid: &GroupId, digest: &PeerId) -> Result<Proof> {
+        let running = self.running(mgid)?;
+        Ok(Proof::prove(running.keypair()?, &self.addr, digest))
     }
 
     pub fn uptime(&self, gid: &GroupId) -> Result<u32> {
@@ -343,14 +518,15 @@ impl Group {
         vecs
     }
 
-    pub fn all_distribute_conns(&self) -> HashMap<GroupId, Vec<SendType>> {
+    /// all devices' connect messages, (addr, connect).
+    pub fn all_distribute_conns(&self) -> HashMap<GroupId, Vec<(PeerId, SendType)>> {
         let mut conns = HashMap::new();
         for (mgid, running) in &self.runnings {
             let mut vecs = vec![];
             for (addr, (peer, _, _)) in &running.distributes {
                 if addr != &self.addr {
                     if let Ok(s) = self.connect_message(mgid, peer.clone()) {
-                        vecs.push(s);
+                        vecs.push((*addr, s));
                     }
                 }
             }
@@ -411,6 +587,7 @@ impl Group {
     }
 
     pub fn add_running(&mut self, gid: &GroupId, lock: &str) -> Result<(i64, bool)> {
+        self.load_account(gid)?;
         if let Some(u) = self.accounts.get(gid) {
             let keypair = u.secret(&self.secret, lock)?;
             if !self.runnings.contains_key(gid) {
@@ -426,6 +603,84 @@ impl Group {
         }
     }
 
+    /// lock the account, wipe the keypair in memory.
+    pub fn lock(&mut self, gid: &GroupId) -> Result<()> {
+        self.running_mut(gid)?.lock();
+        Ok(())
+    }
+
+    /// unlock the account, return the queued events when locked.
+    pub fn unlock(&mut self, gid: &GroupId, lock: &str) -> Result<Vec<(GroupId, RecvType)>> {
+        let keypair = self.account(gid)?.secret(&self.secret, lock)?;
+        let running = self.running_mut(gid)?;
+        if !running.is_locked() {
+            return Ok(vec![]);
+        }
+        let events = running
+            .unlock(keypair)
+            .into_iter()
+            .map(|(fgid, addr, data)| (fgid, RecvType::Event(addr, data)))
+            .collect();
+        Ok(events)
+    }
+
+    /// update the account active time, return false when the account is locked
+    /// and the method not allowed in restricted mode.
+    pub fn active(&self, gid: &GroupId, method: &str) -> bool {
+        if let Ok(running) = self.running(gid) {
+            if running.is_locked() {
+                return LOCKED_METHODS.contains(&method);
+            }
+            running.active();
+        }
+        true
+    }
+
+    /// check all running accounts, lock the idle accounts.
+    pub fn check_autolock(&mut self) -> Vec<GroupId> {
+        let mut locked = vec![];
+        for (gid, running) in self.runnings.iter_mut() {
+            if let Some(account) = self.accounts.get(gid) {
+                if account.autolock > 0 && running.is_idle(account.autolock as u64) {
+                    running.lock();
+                    locked.push(*gid);
+                }
+            }
+        }
+        locked
+    }
+
+    /// check the account is locked (restricted mode).
+    pub fn is_locked(&self, gid: &GroupId) -> bool {
+        self.running(gid)
+            .map(|running| running.is_locked())
+            .unwrap_or(false)
+    }
+
+    /// restricted mode, when account is locked, queue the event until unlock.
+    fn queue_locked(&mut self, gid: &GroupId, fgid: GroupId, msg: RecvType) -> Option<RecvType> {
+        if let Ok(running) = self.running_mut(gid) {
+            if running.is_locked() {
+                if let RecvType::Event(addr, data) = &msg {
+                    if let Err(e) = running.queue(fgid, *addr, data) {
+                        warn!("Queue the event failure: {}", e);
+                    }
+                    return None;
+                }
+            }
+        }
+        Some(msg)
+    }
+
+    pub fn update_autolock(&mut self, gid: &GroupId, autolock: i64) -> Result<()> {
+        let account_db = account_db(&self.base)?;
+        let account = self.account_mut(gid)?;
+        account.autolock = autolock;
+        account.update_autolock(&account_db)?;
+        account_db.close()?;
+        Ok(())
+    }
+
     pub fn clone_user(&self, gid: &GroupId) -> Result<User> {
         if let Some(u) = self.accounts.get(gid) {
             Ok(User::new(
@@ -449,8 +704,30 @@ impl Group {
         }
     }
 
-    pub fn list_users(&self) -> &HashMap<GroupId, Account> {
-        &self.accounts
+    /// delete the account from accounts and account db, need running removed.
+    pub fn delete_account(&mut self, gid: &GroupId, lock: &str) -> Result<()> {
+        let account_db = account_db(&self.base)?;
+        let account = match self.accounts.get(gid) {
+            Some(account) => account.clone(),
+            None => Account::get(&account_db, gid)?,
+        };
+        account.check_lock(&self.secret, lock)?;
+        account.delete(&account_db)?;
+        account_db.close()?;
+        self.accounts.remove(gid);
+        self.metas.retain(|m| &m.gid != gid);
+        Ok(())
+    }
+
+    /// all accounts (gid, name, avatar), the newest first.
+    pub fn list_users(&self) -> Vec<(&GroupId, &String, &Vec<u8>)> {
+        self.metas
+            .iter()
+            .map(|m| match self.accounts.get(&m.gid) {
+                Some(u) => (&m.gid, &u.name, &u.avatar),
+                None => (&m.gid, &m.name, &m.avatar),
+            })
+            .collect()
     }
 
     pub async fn add_account(
@@ -462,7 +739,7 @@ impl Group {
         lock: &str,
         avatar_bytes: Vec<u8>,
     ) -> Result<(i64, GroupId)> {
-        let account_index = self.accounts.len() as u32;
+        let account_index = self.metas.len() as u32;
         let (mut account, sk) = Account::generate(
             account_index,
             &self.secret,
@@ -475,6 +752,9 @@ impl Group {
         )?;
         let account_id = account.gid;
 
+        if self.is_account(&account_id) {
+            self.load_account(&account_id)?;
+        }
         if let Some(u) = self.accounts.get(&account_id) {
             let running = RunningAccount::init(sk, &self.base, &account_id)?;
             self.runnings.insert(account_id, running);
@@ -488,6 +768,14 @@ impl Group {
         account_db.close()?;
         let account_did = account.id;
         let _ = write_avatar(&self.base, &account_id, &account_id, &account.avatar).await;
+        self.metas.insert(
+            0,
+            AccountMeta {
+                gid: account.gid,
+                name: account.name.clone(),
+                avatar: account.avatar.clone(),
+            },
+        );
         self.accounts.insert(account.gid, account);
 
         let (device_name, device_info) = device_info();
@@ -516,6 +804,14 @@ impl Group {
         account_db.close()
     }
 
+    pub fn update_profile(&mut self, gid: GroupId, profile: Profile) -> Result<()> {
+        let account_db = account_db(&self.base)?;
+        let account = self.account_mut(&gid)?;
+        account.profile = profile;
+        account.update_info(&account_db)?;
+        account_db.close()
+    }
+
     pub fn mnemonic(&self, gid: &GroupId, lock: &str) -> Result<String> {
         if let Some(u) = self.accounts.get(gid) {
             u.mnemonic(&self.secret, lock)
@@ -684,6 +980,24 @@ impl Group {
         Ok(())
     }
 
+    /// record the sensitive action to audit log, and sync to other devices.
+    pub fn audit(
+        &mut self,
+        gid: &GroupId,
+        kind: AuditKind,
+        info: String,
+        results: &mut HandleResult,
+    ) -> Result<()> {
+        let key = self.derive_key(gid, AUDIT_KEY_CONTEXT)?;
+        let db = consensus_db(&self.base, gid)?;
+        let mut audit = Audit::new(kind, info, self.addr);
+        audit.insert(&db, &key)?;
+        db.close()?;
+
+        let event = InnerEvent::Audit(kind.to_i64(), audit.info, audit.device, audit.datetime);
+        self.broadcast(gid, event, AUDIT_TABLE_PATH, audit.id, results)
+    }
+
     pub fn _status(
         &mut self,
         gid: &GroupId,
@@ -716,8 +1030,14 @@ impl GroupEvent {
             GroupEvent::DeviceUpdate(_at, _name) => {
                 // TODO
             }
-            GroupEvent::DeviceDelete(_at) => {
-                // TODO
+            GroupEvent::DeviceDelete(at) => {
+                let v = group.running_mut(&gid)?;
+                if let Some((_peer, did, _online)) = v.distributes.remove(&at) {
+                    let db = consensus_db(group.base(), &gid)?;
+                    Device::delete(&db, &did)?;
+                    db.close()?;
+                    results.rpcs.push(device_rpc::device_remove(gid, did));
+                }
             }
             GroupEvent::DeviceOffline => {
                 let v = group.running_mut(&gid)?;
@@ -732,7 +1052,7 @@ impl GroupEvent {
                     SendType::Event(
                         0,
                         addr,
-                        bincode::serialize(&GroupEvent::StatusResponse(
+                        bincode::serialize(&GroupEvent::StatusAutologinResponse(
                             cpu_n,
                             mem_s,
                             swap_s,
@@ -742,6 +1062,7 @@ impl GroupEvent {
                             swap_p,
                             disk_p,
                             group.uptime(&gid)?,
+                            autologin::is_enabled(&gid),
                         ))
                         .unwrap_or(vec![]),
                     ),
@@ -758,7 +1079,31 @@ impl GroupEvent {
                 disk_p,
                 uptime,
             ) => results.rpcs.push(device_rpc::device_status(
-                gid, cpu_n, mem_s, swap_s, disk_s, cpu_p, mem_p, swap_p, disk_p, uptime,
+                gid, cpu_n, mem_s, swap_s, disk_s, cpu_p, mem_p, swap_p, disk_p, uptime, false,
+            )),
+            GroupEvent::StatusAutologinResponse(
+                cpu_n,
+                mem_s,
+                swap_s,
+                disk_s,
+                cpu_p,
+                mem_p,
+                swap_p,
+                disk_p,
+                uptime,
+                is_autologin,
+            ) => results.rpcs.push(device_rpc::device_status(
+                gid,
+                cpu_n,
+                mem_s,
+                swap_s,
+                disk_s,
+                cpu_p,
+                mem_p,
+                swap_p,
+                disk_p,
+                uptime,
+                is_autologin,
             )),
             GroupEvent::Event(eheight, eid, pre, inner_event) => {
                 inner_event.handle(group, gid, addr, eheight, eid, pre, &mut results, layer)?;
@@ -837,27 +1182,16 @@ impl GroupEvent {
                 }
             }
             GroupEvent::SyncRequest(from, to) => {
-                println!("====== DEBUG Sync Request: from: {} to {}", from, to);
-                // every time sync MAX is 100.
-                let last_to = if to - from > 100 { to - 100 } else { to };
-                let sync_events =
-                    SyncEvent::sync(&group.base, &gid, group.account(&gid)?, from, last_to).await?;
-                let event = GroupEvent::SyncResponse(from, last_to, to, sync_events);
-                let data = bincode::serialize(&event).unwrap_or(vec![]);
-                results.groups.push((gid, SendType::Event(0, addr, data)));
-            }
-            GroupEvent::SyncResponse(from, last_to, to, events) => {
-                println!(
-                    "====== DEBUG Sync Response: from: {} last {}, to {}",
-                    from, last_to, to
-                );
-                if last_to < to {
-                    let event = GroupEvent::SyncRequest(last_to + 1, to);
-                    let data = bincode::serialize(&event).unwrap_or(vec![]);
-                    results.groups.push((gid, SendType::Event(0, addr, data)));
-                }
-                SyncEvent::handle(gid, from, last_to, events, group, layer, &mut results, addr)?;
+                let account = group.account(&gid)?;
+                let msg = sync_response(&group.base, &gid, account, addr, from, to).await?;
+                results.groups.push((gid, msg));
             }
+            GroupEvent::SyncResponse(..) => {} // handled in `group_handle`.
+            GroupEvent::RelayRequest(..)
+            | GroupEvent::RelayResponse(..)
+            | GroupEvent::RelaySend(..)
+            | GroupEvent::RelayRecv(..)
+            | GroupEvent::RelayStop(..) => {} // handled in `relay::handle`.
         }
 
         Ok(results)
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tdn::types::{
     group::GroupId,
@@ -10,10 +11,30 @@ use tdn_did::Keypair;
 
 use crate::apps::device::Device;
 use crate::storage::consensus_db;
+use crate::utils::crypto::{open, seal, seal_public};
+
+/// the context of the key which seal the queued events when locked.
+const QUEUE_KEY_CONTEXT: &'static str = "esse locked queue";
+/// the max queued events when locked, the others are dropped.
+const QUEUE_MAX: usize = 1000;
+
+#[inline]
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|s| s.as_secs())
+        .unwrap_or(0) // safe for all life.
+}
 
 pub(crate) struct RunningAccount {
-    /// secret keypair.
-    pub keypair: Keypair,
+    /// secret keypair, none when account is locked.
+    keypair: Option<Keypair>,
+    /// last active time (rpc from UI), updated without the group's write lock.
+    active: AtomicU64,
+    /// the public key which seal the queued events, the secret derived from keypair.
+    queue_public: [u8; 32],
+    /// queued events in memory when account is locked, (friend's gid, addr, sealed bytes).
+    queue: Vec<(GroupId, PeerId, Vec<u8>)>,
     /// device's name.
     pub device_name: String,
     /// device's info.
@@ -38,8 +59,13 @@ impl RunningAccount {
             .map(|s| s.as_secs())
             .unwrap_or(0) as u32; // safe for all life.
 
+        let queue_public = seal_public(&blake3::derive_key(QUEUE_KEY_CONTEXT, &keypair.to_bytes()));
+
         Ok(Self {
-            keypair,
+            queue_public,
+            keypair: Some(keypair),
+            active: AtomicU64::new(now()),
+            queue: vec![],
             distributes,
             device_name,
             device_info,
@@ -47,6 +73,58 @@ impl RunningAccount {
         })
     }
 
+    pub fn keypair(&self) -> Result<&Keypair> {
+        self.keypair.as_ref().ok_or(anyhow!("account is locked"))
+    }
+
+    /// derive the account's local key from the secret keypair, the context is the key's usage.
+    pub fn derive_key(&self, context: &str) -> Result<[u8; 32]> {
+        Ok(blake3::derive_key(context, &self.keypair()?.to_bytes()))
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.keypair.is_none()
+    }
+
+    pub fn active(&self) {
+        self.active.store(now(), Ordering::Relaxed);
+    }
+
+    /// check if account idle timeout, return true when need lock.
+    pub fn is_idle(&self, timeout: u64) -> bool {
+        !self.is_locked() && now() > self.active.load(Ordering::Relaxed) + timeout
+    }
+
+    /// wipe the keypair in memory (keypair zeroize when drop).
+    pub fn lock(&mut self) {
+        self.keypair = None;
+    }
+
+    /// reset keypair, and return all queued events.
+    pub fn unlock(&mut self, keypair: Keypair) -> Vec<(GroupId, PeerId, Vec<u8>)> {
+        let secret = blake3::derive_key(QUEUE_KEY_CONTEXT, &keypair.to_bytes());
+        self.keypair = Some(keypair);
+        self.active();
+
+        let mut events = vec![];
+        for (fgid, addr, bytes) in self.queue.drain(..) {
+            if let Ok(data) = open(&secret, &bytes) {
+                events.push((fgid, addr, data));
+            }
+        }
+        events
+    }
+
+    /// queue the event sealed when account is locked, failure if it is full.
+    pub fn queue(&mut self, fgid: GroupId, addr: PeerId, data: &[u8]) -> Result<()> {
+        if self.queue.len() >= QUEUE_MAX {
+            return Err(anyhow!("locked queue is full"));
+        }
+        let bytes = seal(&self.queue_public, data)?;
+        self.queue.push((fgid, addr, bytes));
+        Ok(())
+    }
+
     pub fn add_online(&mut self, addr: &PeerId) -> Result<i64> {
         if let Some(v) = self.distributes.get_mut(addr) {
             v.2 = true;
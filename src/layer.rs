@@ -9,10 +9,12 @@ use tdn::types::{
 };
 use tokio::sync::RwLock;
 
-use crate::account::User;
+use crate::account::{Profile, User};
 use crate::apps::chat::{chat_conn, LayerEvent as ChatLayerEvent};
 use crate::apps::group::{group_conn, GROUP_ID};
 use crate::group::Group;
+use crate::presence;
+use crate::relay;
 use crate::session::{Session, SessionType};
 use crate::storage::session_db;
 
@@ -73,8 +75,10 @@ impl Layer {
         consensus: i64,
     ) -> Result<()> {
         if !self.runnings.contains_key(gid) {
-            self.runnings
-                .insert(*gid, RunningLayer::init(owner, id, consensus));
+            self.runnings.insert(
+                *gid,
+                RunningLayer::init(owner, id, consensus, gid == &owner),
+            );
         }
 
         Ok(())
@@ -123,7 +127,10 @@ impl Layer {
         self.running_mut(gid).ok()?.remove_online(fgid)
     }
 
-    pub async fn all_layer_conns(&self) -> Result<HashMap<GroupId, Vec<(GroupId, SendType)>>> {
+    /// all sessions' connect messages, (tgid, addr, connect).
+    pub async fn all_layer_conns(
+        &self,
+    ) -> Result<HashMap<GroupId, Vec<(GroupId, PeerId, SendType)>>> {
         let mut conns = HashMap::new();
         let group_lock = self.group.read().await;
         for mgid in self.runnings.keys() {
@@ -137,11 +144,15 @@ impl Layer {
                 match s.s_type {
                     SessionType::Chat => {
                         let proof = group_lock.prove_addr(mgid, &s.addr)?;
-                        vecs.push((s.gid, chat_conn(proof, Peer::peer(s.addr))));
+                        vecs.push((s.gid, s.addr, chat_conn(proof, Peer::peer(s.addr))));
                     }
                     SessionType::Group => {
                         let proof = group_lock.prove_addr(mgid, &s.addr)?;
-                        vecs.push((GROUP_ID, group_conn(proof, Peer::peer(s.addr), s.gid)));
+                        vecs.push((
+                            GROUP_ID,
+                            s.addr,
+                            group_conn(proof, Peer::peer(s.addr), s.gid),
+                        ));
                     }
                     _ => {}
                 }
@@ -173,7 +184,17 @@ impl Layer {
     pub fn broadcast(&self, user: User, results: &mut HandleResult) {
         let gid = user.id;
         let info = ChatLayerEvent::InfoRes(user);
-        let data = bincode::serialize(&info).unwrap_or(vec![]);
+        self.broadcast_event(gid, &info, results);
+    }
+
+    /// send the profile to all online friends.
+    pub fn broadcast_profile(&self, gid: GroupId, profile: Profile, results: &mut HandleResult) {
+        let info = ChatLayerEvent::InfoProfile(profile);
+        self.broadcast_event(gid, &info, results);
+    }
+
+    fn broadcast_event(&self, gid: GroupId, event: &ChatLayerEvent, results: &mut HandleResult) {
+        let data = bincode::serialize(event).unwrap_or(vec![]);
         if let Some(running) = self.runnings.get(&gid) {
             for (fgid, online) in &running.sessions {
                 let msg = SendType::Event(0, *online.online.addr(), data.clone());
@@ -188,14 +209,14 @@ impl Layer {
 pub(crate) enum Online {
     /// connected to this device.
     Direct(PeerId),
-    /// connected to other device.
-    _Relay(PeerId),
+    /// connected to other device, relay by it.
+    Relay(PeerId),
 }
 
 impl Online {
     fn addr(&self) -> &PeerId {
         match self {
-            Online::Direct(ref addr) | Online::_Relay(ref addr) => addr,
+            Online::Direct(ref addr) | Online::Relay(ref addr) => addr,
         }
     }
 }
@@ -208,7 +229,8 @@ pub(crate) struct OnlineSession {
     pub db_fid: i64,
     pub suspend_me: bool,
     pub suspend_remote: bool,
-    pub remain: u16, // keep-alive remain minutes
+    /// the time when suspended by both, keep-alive until presence's suspend timeout.
+    pub suspend_at: i64,
 }
 
 impl OnlineSession {
@@ -219,21 +241,12 @@ impl OnlineSession {
             db_fid,
             suspend_me: false,
             suspend_remote: false,
-            remain: 0,
+            suspend_at: 0,
         }
     }
 
-    fn close_suspend(&mut self) -> bool {
-        if self.suspend_me && self.suspend_remote {
-            if self.remain == 0 {
-                true
-            } else {
-                self.remain -= 1;
-                false
-            }
-        } else {
-            false
-        }
+    fn close_suspend(&self, now: i64, timeout: i64) -> bool {
+        self.suspend_me && self.suspend_remote && now - self.suspend_at >= timeout
     }
 }
 
@@ -245,18 +258,33 @@ pub(crate) struct RunningLayer {
     consensus: i64,
     /// online group (friends/services) => (group's address, group's db id)
     sessions: HashMap<GroupId, OnlineSession>,
+    /// record the sessions' presence, only account's layer, services' db id is not session.
+    presence: bool,
 }
 
 impl RunningLayer {
-    pub fn init(owner: GroupId, id: i64, consensus: i64) -> Self {
+    pub fn init(owner: GroupId, id: i64, consensus: i64, presence: bool) -> Self {
         RunningLayer {
             owner,
             id,
             consensus,
             sessions: HashMap::new(),
+            presence,
+        }
+    }
+
+    fn record(&self, db_id: i64, is_online: bool) {
+        if self.presence {
+            presence::record(&self.owner, db_id, is_online);
         }
     }
 
+    /// the session is closed, record the presence and stop the relays.
+    fn closed(&self, gid: &GroupId, db_id: i64) {
+        self.record(db_id, false);
+        relay::closed(&self.owner, gid);
+    }
+
     pub fn owner_height_id(&self) -> (GroupId, i64, i64) {
         (self.owner, self.consensus, self.id)
     }
@@ -274,7 +302,7 @@ impl RunningLayer {
                 online.suspend_remote = false;
             }
 
-            online.remain = 0;
+            online.suspend_at = 0;
             Some(*online.online.addr())
         } else {
             None
@@ -295,7 +323,7 @@ impl RunningLayer {
             }
 
             if online.suspend_remote && online.suspend_me {
-                online.remain = 6; // keep-alive 10~11 minutes 120s/time
+                online.suspend_at = presence::now();
                 Ok(true)
             } else {
                 Ok(false)
@@ -331,6 +359,14 @@ impl RunningLayer {
         Err(anyhow!("no direct online"))
     }
 
+    /// check the session is relayed by my other device.
+    pub fn is_relayed(&self, gid: &GroupId) -> bool {
+        self.sessions
+            .get(gid)
+            .map(|online| matches!(online.online, Online::Relay(..)))
+            .unwrap_or(false)
+    }
+
     /// get all online peer.
     pub fn onlines(&self) -> Vec<(&GroupId, &PeerId)> {
         self.sessions
@@ -353,7 +389,8 @@ impl RunningLayer {
     ) -> Result<()> {
         if let Some(o) = self.sessions.get(&gid) {
             match (&o.online, &online) {
-                (Online::_Relay(..), Online::Direct(..)) => {
+                (Online::Relay(..), Online::Direct(..)) => {
+                    relay::remove_route(&self.owner, &gid);
                     self.sessions
                         .insert(gid, OnlineSession::new(online, id, fid));
                     Ok(())
@@ -361,8 +398,12 @@ impl RunningLayer {
                 _ => Err(anyhow!("remote had online")),
             }
         } else {
+            if let Online::Relay(device) = online {
+                relay::add_route(self.owner, gid, device);
+            }
             self.sessions
                 .insert(gid, OnlineSession::new(online, id, fid));
+            self.record(id, true);
             Ok(())
         }
     }
@@ -370,6 +411,7 @@ impl RunningLayer {
     /// check offline, and return is direct.
     pub fn check_offline(&mut self, gid: &GroupId, addr: &PeerId) -> bool {
         if let Some(online) = self.sessions.remove(gid) {
+            self.closed(gid, online.db_id);
             if online.online.addr() != addr {
                 return false;
             }
@@ -385,14 +427,17 @@ impl RunningLayer {
     }
 
     pub fn remove_online(&mut self, gid: &GroupId) -> Option<PeerId> {
-        self.sessions
-            .remove(gid)
-            .map(|online| *online.online.addr())
+        let online = self.sessions.remove(gid)?;
+        self.closed(gid, online.db_id);
+        Some(*online.online.addr())
     }
 
     /// remove all onlines peer.
     pub fn remove_onlines(self) -> Vec<(PeerId, GroupId)> {
         let mut peers = vec![];
+        for (fgid, online) in &self.sessions {
+            self.closed(fgid, online.db_id);
+        }
         for (fgid, online) in self.sessions {
             match online.online {
                 Online::Direct(addr) => peers.push((addr, fgid)),
@@ -422,29 +467,35 @@ impl RunningLayer {
                 deletes.push(*fgid);
             }
         }
-        for i in &deletes {
-            self.sessions.remove(&i);
+        for (i, id) in deletes.iter().zip(peers.iter()) {
+            self.sessions.remove(i);
+            self.closed(i, *id);
         }
 
         peers
     }
 
-    /// list all onlines groups.
-    pub fn close_suspend(&mut self, self_addr: &PeerId) -> Vec<(GroupId, PeerId, i64)> {
+    /// close the sessions which suspended longer than presence's suspend timeout.
+    /// return (gid, addr, session id, is_direct).
+    pub fn close_suspend(&mut self, self_addr: &PeerId) -> Vec<(GroupId, PeerId, i64, bool)> {
+        let now = presence::now();
+        let timeout = presence::suspend_timeout();
         let mut needed = vec![];
-        for (fgid, online) in &mut self.sessions {
+        for (fgid, online) in &self.sessions {
             // when online is self. skip.
             if online.online == Online::Direct(*self_addr) {
                 continue;
             }
 
-            if online.close_suspend() {
-                needed.push((*fgid, *online.online.addr(), online.db_id));
+            if online.close_suspend(now, timeout) {
+                let is_direct = matches!(online.online, Online::Direct(..));
+                needed.push((*fgid, *online.online.addr(), online.db_id, is_direct));
             }
         }
 
-        for (gid, _, _) in needed.iter() {
+        for (gid, _, id, _) in needed.iter() {
             self.sessions.remove(gid);
+            self.closed(gid, *id);
         }
         needed
     }
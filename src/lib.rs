@@ -8,13 +8,22 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 
 mod account;
+mod actor;
 mod apps;
+mod audit;
+mod autologin;
+mod bootstrap;
+mod config;
 mod consensus;
+mod discovery;
 mod event;
 mod group;
 mod layer;
 mod migrate;
+mod presence;
 mod primitives;
+mod reconnect;
+mod relay;
 mod rpc;
 mod server;
 mod session;
@@ -42,6 +51,11 @@ pub mod android {
                 .as_ptr(),
         );
     }
+
+    #[no_mangle]
+    pub extern "C" fn Java_com_esse_1core_esse_1core_RustCore_stop(_env: JNIEnv, _: JClass) {
+        stop();
+    }
 }
 
 #[no_mangle]
@@ -52,3 +66,9 @@ pub extern "C" fn start(db_path: *const c_char) {
     let rt = tokio::runtime::Runtime::new().unwrap();
     let _ = rt.block_on(server::start(s_path));
 }
+
+/// stop the running daemon, logout all accounts and return from `start`.
+#[no_mangle]
+pub extern "C" fn stop() {
+    server::stop();
+}
@@ -1,5 +1,5 @@
 use std::path::PathBuf;
-use tdn::types::primitive::Result;
+use tdn::types::{group::GroupId, primitive::Result};
 use tdn_storage::local::DStorage;
 
 pub mod consensus;
@@ -125,35 +125,19 @@ pub(crate) fn main_migrate(path: &PathBuf) -> Result<()> {
                     }
                     continue;
                 }
-                CONSENSUS_DB => CONSENSUS_VERSIONS.as_ref(),
-                SESSION_DB => SESSION_VERSIONS.as_ref(),
-                FILE_DB => FILE_VERSIONS.as_ref(),
-                SERVICE_DB => SERVICE_VERSIONS.as_ref(),
-                JARVIS_DB => JARVIS_VERSIONS.as_ref(),
-                GROUP_DB => GROUP_VERSIONS.as_ref(),
-                DAO_DB => DAO_VERSIONS.as_ref(),
-                CHAT_DB => CHAT_VERSIONS.as_ref(),
-                DOMAIN_DB => DOMAIN_VERSIONS.as_ref(),
-                WALLET_DB => WALLET_VERSIONS.as_ref(),
-                CLOUD_DB => CLOUD_VERSIONS.as_ref(),
-                _ => {
-                    continue;
-                }
+                _ => match db_versions(&db_name) {
+                    Some(versions) => versions,
+                    None => continue,
+                },
             };
 
             if db_version != current_versions.len() {
-                let mut matrix = db.query("select gid from accounts")?;
-                while matrix.len() > 0 {
-                    let mut account_path = path.clone();
-                    account_path.push(matrix.pop().unwrap().pop().unwrap().as_str());
-                    account_path.push(&db_name);
-                    let account_db = DStorage::open(account_path)?;
-                    // migrate
-                    for i in &current_versions[db_version..] {
-                        account_db.execute(i)?;
-                    }
-                    account_db.close()?;
-                }
+                // the accounts' databases are migrated when login, see `account_migrate`.
+                // keep the oldest version when the account not login after last upgrade.
+                db.insert(&format!(
+                    "INSERT INTO account_migrates (gid, db_name, version) SELECT gid, '{}', {} FROM accounts WHERE gid NOT IN (SELECT gid FROM account_migrates WHERE db_name = '{}')",
+                    db_name, db_version, db_name,
+                ))?;
 
                 db.update(&format!(
                     "UPDATE migrates SET version = {} where db_name = '{}'",
@@ -249,6 +233,58 @@ pub(crate) fn main_migrate(path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// the versions of the account's database.
+fn db_versions(db_name: &str) -> Option<&'static [&'static str]> {
+    match db_name {
+        CONSENSUS_DB => Some(&CONSENSUS_VERSIONS),
+        SESSION_DB => Some(&SESSION_VERSIONS),
+        FILE_DB => Some(&FILE_VERSIONS),
+        SERVICE_DB => Some(&SERVICE_VERSIONS),
+        JARVIS_DB => Some(&JARVIS_VERSIONS),
+        GROUP_DB => Some(&GROUP_VERSIONS),
+        DAO_DB => Some(&DAO_VERSIONS),
+        CHAT_DB => Some(&CHAT_VERSIONS),
+        DOMAIN_DB => Some(&DOMAIN_VERSIONS),
+        WALLET_DB => Some(&WALLET_VERSIONS),
+        CLOUD_DB => Some(&CLOUD_VERSIONS),
+        _ => None,
+    }
+}
+
+/// migrate the account's databases which not upgraded, when the account login.
+pub(crate) fn account_migrate(path: &PathBuf, gid: &GroupId) -> Result<()> {
+    let mut db_path = path.clone();
+    db_path.push(ACCOUNT_DB);
+    let db = DStorage::open(db_path)?;
+    let matrix = db.query(&format!(
+        "SELECT db_name, version FROM account_migrates WHERE gid = '{}'",
+        gid.to_hex()
+    ))?;
+
+    for mut values in matrix {
+        let db_version = values.pop().unwrap().as_i64() as usize;
+        let db_name = values.pop().unwrap().as_string();
+        if let Some(current_versions) = db_versions(&db_name) {
+            let mut account_path = path.clone();
+            account_path.push(gid.to_hex());
+            account_path.push(&db_name);
+            let account_db = DStorage::open(account_path)?;
+            for i in &current_versions[db_version..] {
+                account_db.execute(i)?;
+            }
+            account_db.close()?;
+        }
+
+        db.delete(&format!(
+            "DELETE FROM account_migrates WHERE gid = '{}' AND db_name = '{}'",
+            gid.to_hex(),
+            db_name
+        ))?;
+    }
+
+    db.close()
+}
+
 pub(crate) fn account_init_migrate(path: &PathBuf) -> Result<()> {
     let mut db_path = path.clone();
     db_path.push(CONSENSUS_DB);
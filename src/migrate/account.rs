@@ -1,5 +1,5 @@
 #[rustfmt::skip]
-pub(super) const ACCOUNT_VERSIONS: [&str; 13] = [
+pub(super) const ACCOUNT_VERSIONS: [&str; 19] = [
   "CREATE TABLE IF NOT EXISTS accounts(
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     gid TEXT NOT NULL,
@@ -31,4 +31,13 @@ pub(super) const ACCOUNT_VERSIONS: [&str; 13] = [
   "INSERT INTO migrates (db_name, version) values ('wallet.db', 0)",
   "INSERT INTO migrates (db_name, version) values ('cloud.db', 0)",
   "INSERT INTO migrates (db_name, version) values ('dao.db', 0)",
+  "ALTER TABLE accounts ADD COLUMN autolock INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE accounts ADD COLUMN bio TEXT NOT NULL DEFAULT '';",
+  "ALTER TABLE accounts ADD COLUMN status TEXT NOT NULL DEFAULT '';",
+  "ALTER TABLE accounts ADD COLUMN status_expire INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE accounts ADD COLUMN domain TEXT NOT NULL DEFAULT '';",
+  "CREATE TABLE IF NOT EXISTS account_migrates(
+    gid TEXT NOT NULL,
+    db_name TEXT NOT NULL,
+    version INTEGER NOT NULL);",
 ];
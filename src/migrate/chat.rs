@@ -1,5 +1,5 @@
 #[rustfmt::skip]
-pub(super) const CHAT_VERSIONS: [&str; 3] = [
+pub(super) const CHAT_VERSIONS: [&str; 16] = [
   "CREATE TABLE IF NOT EXISTS friends(
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     gid TEXT NOT NULL,
@@ -30,4 +30,44 @@ pub(super) const CHAT_VERSIONS: [&str; 3] = [
     content TEXT NOT NULL,
     is_delivery INTEGER NOT NULL,
     datetime INTEGER NOT NULL);",
+  "CREATE TABLE IF NOT EXISTS labels(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL,
+    datetime INTEGER NOT NULL);",
+  "CREATE TABLE IF NOT EXISTS friend_labels(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    lid INTEGER NOT NULL,
+    fid INTEGER NOT NULL);",
+  "ALTER TABLE friends ADD COLUMN is_verified INTEGER NOT NULL DEFAULT 0;",
+  "CREATE TABLE IF NOT EXISTS invitations(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    token TEXT NOT NULL,
+    expire INTEGER NOT NULL,
+    is_once INTEGER NOT NULL,
+    is_auto INTEGER NOT NULL,
+    used INTEGER NOT NULL,
+    datetime INTEGER NOT NULL);",
+  "CREATE TABLE IF NOT EXISTS recovery_holders(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    fid INTEGER NOT NULL,
+    idx INTEGER NOT NULL,
+    threshold INTEGER NOT NULL,
+    total INTEGER NOT NULL,
+    datetime INTEGER NOT NULL);",
+  "CREATE TABLE IF NOT EXISTS recovery_shares(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    owner TEXT NOT NULL,
+    peer TEXT NOT NULL,
+    lang INTEGER NOT NULL,
+    threshold INTEGER NOT NULL,
+    share TEXT NOT NULL,
+    is_collect INTEGER NOT NULL,
+    datetime INTEGER NOT NULL);",
+  "ALTER TABLE friends ADD COLUMN bio TEXT NOT NULL DEFAULT '';",
+  "ALTER TABLE friends ADD COLUMN status TEXT NOT NULL DEFAULT '';",
+  "ALTER TABLE friends ADD COLUMN status_expire INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE friends ADD COLUMN domain TEXT NOT NULL DEFAULT '';",
+  "ALTER TABLE labels ADD COLUMN is_deleted INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE recovery_shares ADD COLUMN setup TEXT NOT NULL DEFAULT '';",
+  "ALTER TABLE recovery_shares ADD COLUMN holders TEXT NOT NULL DEFAULT '';",
 ];
@@ -3,9 +3,12 @@ pub(crate) const FRIEND_TABLE_PATH: i64 = 1;
 pub(crate) const REQUEST_TABLE_PATH: i64 = 2;
 pub(crate) const MESSAGE_TABLE_PATH: i64 = 3;
 pub(crate) const FILE_TABLE_PATH: i64 = 4;
+pub(crate) const SESSION_TABLE_PATH: i64 = 5;
+pub(crate) const LABEL_TABLE_PATH: i64 = 6;
+pub(crate) const AUDIT_TABLE_PATH: i64 = 7;
 
 #[rustfmt::skip]
-pub(super) const CONSENSUS_VERSIONS: [&str; 9] = [
+pub(super) const CONSENSUS_VERSIONS: [&str; 12] = [
   "CREATE TABLE IF NOT EXISTS devices(
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     name TEXT NOT NULL,
@@ -29,4 +32,16 @@ pub(super) const CONSENSUS_VERSIONS: [&str; 9] = [
   "INSERT INTO db_tables (db_name, table_name) values ('session.db', 'requests')",
   "INSERT INTO db_tables (db_name, table_name) values ('session.db', 'messages')",
   "INSERT INTO db_tables (db_name, table_name) values ('file.db', 'files')",
+  "CREATE TABLE IF NOT EXISTS audits(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind INTEGER NOT NULL,
+    info TEXT NOT NULL,
+    device TEXT NOT NULL,
+    prev TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    datetime INTEGER NOT NULL);",
+  "CREATE TRIGGER IF NOT EXISTS audits_no_update BEFORE UPDATE ON audits
+    BEGIN SELECT RAISE(ABORT, 'audit is append-only'); END;",
+  "CREATE TRIGGER IF NOT EXISTS audits_no_delete BEFORE DELETE ON audits
+    BEGIN SELECT RAISE(ABORT, 'audit is append-only'); END;",
 ];
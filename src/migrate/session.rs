@@ -1,5 +1,5 @@
 #[rustfmt::skip]
-pub(super) const SESSION_VERSIONS: [&str; 2] = [
+pub(super) const SESSION_VERSIONS: [&str; 7] = [
   "CREATE TABLE IF NOT EXISTS sessions(
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     fid INTEGER NOT NULL,
@@ -13,4 +13,13 @@ pub(super) const SESSION_VERSIONS: [&str; 2] = [
     last_content TEXT,
     last_readed INTEGER);",
   "INSERT INTO sessions (fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed) VALUES (0, '', '', 3, '', 0, 0, 0, '', 1);", // Jarvis.
+  "ALTER TABLE sessions ADD COLUMN notify INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE sessions ADD COLUMN is_archived INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE sessions ADD COLUMN mute_until INTEGER NOT NULL DEFAULT 0;",
+  "ALTER TABLE sessions ADD COLUMN unread INTEGER NOT NULL DEFAULT 0;",
+  "CREATE TABLE IF NOT EXISTS presences(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    session INTEGER NOT NULL,
+    is_online INTEGER NOT NULL,
+    datetime INTEGER NOT NULL);",
 ];
@@ -0,0 +1,133 @@
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::sync::RwLock as StdRwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdn::types::{
+    group::GroupId,
+    primitive::Result,
+    rpc::{json, RpcParam},
+};
+use tdn_storage::local::DStorage;
+
+use crate::storage::session_db;
+
+/// default heartbeat seconds.
+const DEFAULT_HEARTBEAT: u64 = 120;
+/// default suspend timeout seconds.
+const DEFAULT_SUSPEND: i64 = 600;
+/// min heartbeat seconds, not wake up too often.
+const MIN_HEARTBEAT: u64 = 10;
+/// max history records of every session.
+const HISTORY_MAX: usize = 100;
+
+/// Presence & keep-alive policy, loaded from config.toml, changed by `presence-policy`.
+struct Presence {
+    base: PathBuf,
+    heartbeat: u64,
+    suspend: i64,
+}
+
+static PRESENCE: Lazy<StdRwLock<Presence>> = Lazy::new(|| {
+    StdRwLock::new(Presence {
+        base: PathBuf::new(),
+        heartbeat: DEFAULT_HEARTBEAT,
+        suspend: DEFAULT_SUSPEND,
+    })
+});
+
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|s| s.as_secs())
+        .unwrap_or(0) as i64 // safe for all life.
+}
+
+/// load the policy from config.toml.
+pub(crate) fn init(base: &PathBuf, heartbeat: Option<i64>, suspend: Option<i64>) {
+    if let Ok(mut presence) = PRESENCE.write() {
+        presence.base = base.clone();
+        if let Some(heartbeat) = heartbeat {
+            presence.heartbeat = (heartbeat.max(0) as u64).max(MIN_HEARTBEAT);
+        }
+        if let Some(suspend) = suspend {
+            presence.suspend = suspend.max(0);
+        }
+        info!(
+            "Presence policy : heartbeat {}s, suspend {}s",
+            presence.heartbeat, presence.suspend
+        );
+    }
+}
+
+/// seconds of the heartbeat.
+pub(crate) fn heartbeat() -> u64 {
+    PRESENCE
+        .read()
+        .map(|p| p.heartbeat)
+        .unwrap_or(DEFAULT_HEARTBEAT)
+}
+
+/// seconds of keep-alive when the session suspended.
+pub(crate) fn suspend_timeout() -> i64 {
+    PRESENCE
+        .read()
+        .map(|p| p.suspend)
+        .unwrap_or(DEFAULT_SUSPEND)
+}
+
+/// change the policy in runtime, e.g. mobile in background. return [heartbeat, suspend].
+pub(crate) fn update_policy(heartbeat: Option<i64>, suspend: Option<i64>) -> RpcParam {
+    if let Ok(mut presence) = PRESENCE.write() {
+        if let Some(heartbeat) = heartbeat {
+            presence.heartbeat = (heartbeat.max(0) as u64).max(MIN_HEARTBEAT);
+        }
+        if let Some(suspend) = suspend {
+            presence.suspend = suspend.max(0);
+        }
+    }
+    json!([heartbeat(), suspend_timeout()])
+}
+
+/// record the session's online/offline, and clear the old history.
+pub(crate) fn record(mgid: &GroupId, session: i64, is_online: bool) {
+    let base = match PRESENCE.read() {
+        Ok(presence) => presence.base.clone(),
+        Err(_) => return,
+    };
+    if let Err(e) = session_db(&base, mgid).and_then(|db| insert(&db, session, is_online)) {
+        error!("Presence record failure: {}", e);
+    }
+}
+
+fn insert(db: &DStorage, session: i64, is_online: bool) -> Result<()> {
+    db.insert(&format!(
+        "INSERT INTO presences (session, is_online, datetime) VALUES ({}, {}, {})",
+        session,
+        is_online,
+        now()
+    ))?;
+    db.delete(&format!(
+        "DELETE FROM presences WHERE session = {} AND id NOT IN (SELECT id FROM presences WHERE session = {} ORDER BY id DESC LIMIT {})",
+        session, session, HISTORY_MAX
+    ))?;
+    Ok(())
+}
+
+/// the session's last seen time, and online history [[is_online, datetime]], the newest first.
+pub(crate) fn history(db: &DStorage, session: &i64, limit: i64) -> Result<(i64, Vec<RpcParam>)> {
+    let matrix = db.query(&format!(
+        "SELECT is_online, datetime FROM presences WHERE session = {} ORDER BY id DESC LIMIT {}",
+        session, limit
+    ))?;
+    let mut last_seen = 0;
+    let mut records = vec![];
+    for mut values in matrix {
+        let datetime = values.pop().unwrap().as_i64();
+        let is_online = values.pop().unwrap().as_bool();
+        if last_seen == 0 {
+            last_seen = datetime;
+        }
+        records.push(json!([is_online, datetime]));
+    }
+    Ok((last_seen, records))
+}
@@ -0,0 +1,236 @@
+use once_cell::sync::Lazy;
+use rand::Rng;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::{Duration, Instant};
+use tdn::types::{
+    group::GroupId,
+    message::SendMessage,
+    primitive::PeerId,
+    rpc::{json, rpc_response, RpcParam},
+};
+use tokio::sync::{mpsc::Sender, RwLock};
+
+use crate::group::Group;
+use crate::layer::Layer;
+use crate::server::push_rpc;
+
+/// the first backoff delay (seconds).
+const BACKOFF_BASE: u64 = 2;
+/// the max backoff delay (seconds).
+const BACKOFF_MAX: u64 = 300;
+/// give up after these attempts, the sessions will connect when used.
+const MAX_ATTEMPTS: u32 = 10;
+/// the interval between priority levels, not connect all peers at once.
+const PRIORITY_INTERVAL: u64 = 500;
+
+/// the friends & groups which have open sessions when network lost.
+const PRIORITY_OPEN: u8 = 0;
+/// own devices.
+const PRIORITY_DEVICE: u8 = 1;
+/// other sessions.
+const PRIORITY_OTHER: u8 = 2;
+
+/// Reconnection manager, only one is running, a new network lost will cancel the old.
+#[derive(Default)]
+struct Reconnect {
+    /// the running manager's generation, changed when cancel.
+    generation: u64,
+    /// the peers which connected after network lost.
+    connected: HashSet<PeerId>,
+}
+
+static RECONNECT: Lazy<StdRwLock<Reconnect>> = Lazy::new(|| StdRwLock::new(Reconnect::default()));
+
+/// reconnect status, status is: waiting, connecting, done, failed, cancel.
+#[inline]
+fn reconnect_status(status: &str, attempt: u32, delay: u64, pending: usize) -> RpcParam {
+    rpc_response(
+        0,
+        "network-reconnect",
+        json!([status, attempt, delay, pending]),
+        GroupId::default(),
+    )
+}
+
+/// exponential backoff with jitter, random in [delay/2, delay].
+fn backoff(attempt: u32) -> u64 {
+    let delay = BACKOFF_BASE
+        .saturating_mul(1 << attempt.min(16))
+        .min(BACKOFF_MAX);
+    rand::thread_rng().gen_range(delay / 2..=delay)
+}
+
+/// any peer connected after network lost.
+fn is_returned() -> bool {
+    RECONNECT
+        .read()
+        .map(|r| !r.connected.is_empty())
+        .unwrap_or(false)
+}
+
+/// waiting the backoff delay, return true if network returned.
+async fn wait(generation: u64, delay: u64) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(delay);
+    while Instant::now() < deadline && is_current(generation) {
+        if is_returned() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(PRIORITY_INTERVAL)).await;
+    }
+    is_returned()
+}
+
+fn is_current(generation: u64) -> bool {
+    RECONNECT
+        .read()
+        .map(|r| r.generation == generation)
+        .unwrap_or(false)
+}
+
+/// all connect messages which not connected, the higher priority first.
+async fn targets(
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    open: &HashSet<PeerId>,
+) -> Vec<(u8, SendMessage)> {
+    let connected = RECONNECT
+        .read()
+        .map(|r| r.connected.clone())
+        .unwrap_or(HashSet::new());
+
+    let mut targets = vec![];
+    for (mgid, conns) in group.read().await.all_distribute_conns() {
+        for (addr, conn) in conns {
+            if !connected.contains(&addr) {
+                targets.push((PRIORITY_DEVICE, SendMessage::Group(mgid, conn)));
+            }
+        }
+    }
+
+    let layer_conns = layer.read().await.all_layer_conns().await;
+    for (mgid, conns) in layer_conns.unwrap_or(Default::default()) {
+        for (tgid, addr, conn) in conns {
+            if !connected.contains(&addr) {
+                let priority = if open.contains(&addr) {
+                    PRIORITY_OPEN
+                } else {
+                    PRIORITY_OTHER
+                };
+                targets.push((priority, SendMessage::Layer(mgid, tgid, conn)));
+            }
+        }
+    }
+
+    targets.sort_by_key(|(p, _)| *p);
+    targets
+}
+
+/// send the connect messages by priority, return the number of messages.
+async fn connect(sender: &Sender<SendMessage>, targets: Vec<(u8, SendMessage)>) -> usize {
+    let len = targets.len();
+    let mut last = PRIORITY_OPEN;
+    for (priority, msg) in targets {
+        if priority != last {
+            last = priority;
+            tokio::time::sleep(Duration::from_millis(PRIORITY_INTERVAL)).await;
+        }
+        if sender.send(msg).await.is_err() {
+            break;
+        }
+    }
+    len
+}
+
+/// network lost, cancel the running manager and start a new one.
+pub(crate) async fn start(
+    group: Arc<RwLock<Group>>,
+    layer: Arc<RwLock<Layer>>,
+    sender: Sender<SendMessage>,
+) {
+    let generation = match RECONNECT.write() {
+        Ok(mut r) => {
+            r.generation += 1;
+            r.connected.clear();
+            r.generation
+        }
+        Err(_) => return,
+    };
+
+    // the open sessions before lost.
+    let open: HashSet<PeerId> = layer
+        .read()
+        .await
+        .runnings
+        .values()
+        .flat_map(|running| running.onlines().into_iter().map(|(_, addr)| *addr))
+        .collect();
+
+    tokio::spawn(run(generation, group, layer, sender, open));
+}
+
+/// cancel the running manager, e.g. when shutdown.
+pub(crate) fn cancel() {
+    if let Ok(mut r) = RECONNECT.write() {
+        r.generation += 1;
+    }
+}
+
+/// the peer connected, not reconnect it, and the network returned.
+pub(crate) fn connected(addr: &PeerId) {
+    if let Ok(mut r) = RECONNECT.write() {
+        r.connected.insert(*addr);
+    }
+}
+
+async fn run(
+    generation: u64,
+    group: Arc<RwLock<Group>>,
+    layer: Arc<RwLock<Layer>>,
+    sender: Sender<SendMessage>,
+    open: HashSet<PeerId>,
+) {
+    for attempt in 0..MAX_ATTEMPTS {
+        let delay = backoff(attempt);
+        let pending = targets(&group, &layer, &open).await.len();
+        let _ = push_rpc(
+            &sender,
+            reconnect_status("waiting", attempt, delay, pending),
+        )
+        .await;
+
+        let returned = wait(generation, delay).await;
+        if !is_current(generation) {
+            let _ = push_rpc(&sender, reconnect_status("cancel", attempt, delay, pending)).await;
+            return;
+        }
+
+        let targets = targets(&group, &layer, &open).await;
+        if targets.is_empty() {
+            let _ = push_rpc(&sender, reconnect_status("done", attempt, delay, 0)).await;
+            return;
+        }
+
+        let pending = connect(&sender, targets).await;
+        if returned {
+            // network returned, connect the remain peers once, and stop backoff.
+            debug!("Network returned, reconnect {} peers.", pending);
+            let _ = push_rpc(&sender, reconnect_status("done", attempt, delay, pending)).await;
+            return;
+        }
+        let _ = push_rpc(
+            &sender,
+            reconnect_status("connecting", attempt, delay, pending),
+        )
+        .await;
+    }
+
+    if is_current(generation) {
+        let pending = targets(&group, &layer, &open).await.len();
+        let _ = push_rpc(
+            &sender,
+            reconnect_status("failed", MAX_ATTEMPTS, 0, pending),
+        )
+        .await;
+    }
+}
@@ -0,0 +1,298 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+use tdn::types::{
+    group::GroupId,
+    message::{RecvType, SendMessage, SendType},
+    primitive::{HandleResult, PeerId, Result},
+};
+use tokio::sync::{mpsc::Sender, RwLock};
+
+use crate::apps::app_layer_handle;
+use crate::group::{check_locked, Group, GroupEvent};
+use crate::layer::{Layer, Online};
+use crate::rpc::{session_connect, session_lost};
+use crate::session::Session;
+use crate::storage::session_db;
+
+/// my sessions which relayed by my other device, (mgid, fgid) => device.
+static ROUTES: Lazy<StdRwLock<HashMap<(GroupId, GroupId), PeerId>>> =
+    Lazy::new(|| StdRwLock::new(HashMap::new()));
+
+/// the sessions which I relay for my other devices, (mgid, fgid) => devices.
+static RELAYS: Lazy<StdRwLock<HashMap<(GroupId, GroupId), Vec<PeerId>>>> =
+    Lazy::new(|| StdRwLock::new(HashMap::new()));
+
+/// seconds of waiting the direct connect, then ask my devices to relay.
+const RELAY_WAIT: u64 = 10;
+
+/// the channel to send relay-stop when session closed.
+static SENDER: Lazy<StdRwLock<Option<Sender<SendMessage>>>> = Lazy::new(|| StdRwLock::new(None));
+
+pub(crate) fn init(sender: Sender<SendMessage>) {
+    if let Ok(mut s) = SENDER.write() {
+        *s = Some(sender);
+    }
+}
+
+/// tell the device to stop relaying the session.
+fn stop(mgid: GroupId, fgid: GroupId, device: PeerId) {
+    let data = bincode::serialize(&GroupEvent::RelayStop(fgid)).unwrap_or(vec![]);
+    let msg = SendMessage::Group(mgid, SendType::Event(0, device, data));
+    if let Ok(sender) = SENDER.read() {
+        if let Some(sender) = sender.as_ref() {
+            if sender.try_send(msg).is_err() {
+                warn!("Relay stop to {} failure.", device.to_hex());
+            }
+        }
+    }
+}
+
+/// the session is relayed by my device.
+pub(crate) fn add_route(mgid: GroupId, fgid: GroupId, device: PeerId) {
+    if let Ok(mut routes) = ROUTES.write() {
+        routes.insert((mgid, fgid), device);
+    }
+}
+
+/// the session is closed or connected directly, the device stop relaying.
+pub(crate) fn remove_route(mgid: &GroupId, fgid: &GroupId) {
+    let device = ROUTES
+        .write()
+        .ok()
+        .and_then(|mut routes| routes.remove(&(*mgid, *fgid)));
+    if let Some(device) = device {
+        stop(*mgid, *fgid, device);
+    }
+}
+
+/// the session is closed, stop all relays of it.
+pub(crate) fn closed(mgid: &GroupId, fgid: &GroupId) {
+    remove_route(mgid, fgid);
+
+    let devices = RELAYS
+        .write()
+        .ok()
+        .and_then(|mut relays| relays.remove(&(*mgid, *fgid)))
+        .unwrap_or(vec![]);
+    for device in devices {
+        stop(*mgid, *fgid, device);
+    }
+}
+
+/// wait the direct connect, if failure, ask my online devices to relay the session.
+pub(crate) async fn request(
+    group: Arc<RwLock<Group>>,
+    layer: Arc<RwLock<Layer>>,
+    mgid: GroupId,
+    fgid: GroupId,
+) {
+    tokio::time::sleep(Duration::from_secs(RELAY_WAIT)).await;
+    let is_online = layer
+        .read()
+        .await
+        .running(&mgid)
+        .map(|running| running.is_online(&fgid))
+        .unwrap_or(true);
+    if is_online {
+        return;
+    }
+
+    let group_lock = group.read().await;
+    let sender = group_lock.sender();
+    let mut messages = vec![];
+    if let Ok(running) = group_lock.running(&mgid) {
+        let event = GroupEvent::RelayRequest(fgid);
+        for (addr, (_peer, _id, online)) in &running.distributes {
+            if *online && addr != group_lock.addr() {
+                if let Ok(msg) = group_lock.event_message(*addr, &event) {
+                    messages.push(SendMessage::Group(mgid, msg));
+                }
+            }
+        }
+    }
+    drop(group_lock);
+
+    for msg in messages {
+        let _ = sender.send(msg).await;
+    }
+}
+
+/// relay the session for my device, the friend's events will forward to it.
+pub(crate) fn add_relay(mgid: GroupId, fgid: GroupId, device: PeerId) {
+    if let Ok(mut relays) = RELAYS.write() {
+        let devices = relays.entry((mgid, fgid)).or_insert(vec![]);
+        if !devices.contains(&device) {
+            devices.push(device);
+        }
+    }
+}
+
+/// the layer message to friend, when the session is relayed, send it to the device by group.
+pub(crate) fn wrap(mgid: GroupId, fgid: GroupId, msg: SendType) -> SendMessage {
+    let device = ROUTES
+        .read()
+        .ok()
+        .and_then(|routes| routes.get(&(mgid, fgid)).copied());
+
+    match (device, msg) {
+        (Some(device), SendType::Event(_tid, _addr, data)) => {
+            let event = GroupEvent::RelaySend(fgid, data);
+            let data = bincode::serialize(&event).unwrap_or(vec![]);
+            SendMessage::Group(mgid, SendType::Event(0, device, data))
+        }
+        (_, msg) => SendMessage::Layer(mgid, fgid, msg),
+    }
+}
+
+/// the friend's layer event, forward to my devices which relayed by me.
+pub(crate) fn forward(mgid: &GroupId, fgid: &GroupId, msg: &RecvType) -> Vec<SendMessage> {
+    let mut messages = vec![];
+    if let RecvType::Event(addr, data) = msg {
+        let devices = RELAYS
+            .read()
+            .ok()
+            .and_then(|relays| relays.get(&(*mgid, *fgid)).cloned())
+            .unwrap_or(vec![]);
+        for device in devices {
+            let event = GroupEvent::RelayRecv(*fgid, *addr, data.clone());
+            let data = bincode::serialize(&event).unwrap_or(vec![]);
+            messages.push(SendMessage::Group(*mgid, SendType::Event(0, device, data)));
+        }
+    }
+    messages
+}
+
+/// the friend's gid of the relay event, it is handled in the session's order,
+/// same as the friend's direct events.
+pub(crate) fn session_of(msg: &RecvType) -> Option<GroupId> {
+    if let RecvType::Event(_, bytes) = msg {
+        match bincode::deserialize(bytes) {
+            Ok(GroupEvent::RelayRequest(fgid))
+            | Ok(GroupEvent::RelayResponse(fgid, _))
+            | Ok(GroupEvent::RelaySend(fgid, _))
+            | Ok(GroupEvent::RelayRecv(fgid, ..))
+            | Ok(GroupEvent::RelayStop(fgid)) => Some(fgid),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// handle the relay events from my device, the group and layer are locked separately.
+pub(crate) async fn handle(
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    mgid: GroupId,
+    addr: PeerId,
+    event: GroupEvent,
+) -> Result<HandleResult> {
+    let mut results = HandleResult::new();
+    match event {
+        GroupEvent::RelayRequest(fgid) => {
+            // only relay the friend which connected directly.
+            let is_ok = layer
+                .read()
+                .await
+                .running(&mgid)?
+                .online_direct(&fgid)
+                .is_ok();
+            if is_ok {
+                add_relay(mgid, fgid, addr);
+            }
+            let event = GroupEvent::RelayResponse(fgid, is_ok);
+            let data = bincode::serialize(&event).unwrap_or(vec![]);
+            results.groups.push((mgid, SendType::Event(0, addr, data)));
+        }
+        GroupEvent::RelayResponse(fgid, is_ok) => {
+            let base = group.read().await.base().clone();
+            let mut layer_lock = layer.write().await;
+            let running = layer_lock.running_mut(&mgid)?;
+            if is_ok && !running.is_online(&fgid) {
+                let db = session_db(&base, &mgid)?;
+                let s = Session::get_by_gid(&db, &fgid)?;
+                drop(db);
+                running.check_add_online(fgid, Online::Relay(addr), s.id, s.fid)?;
+                results.rpcs.push(session_connect(mgid, &s.id, &addr));
+            } else if is_ok && running.online(&fgid).ok() != Some(addr) {
+                // had connected by others, not need the relay.
+                let event = GroupEvent::RelayStop(fgid);
+                let data = bincode::serialize(&event).unwrap_or(vec![]);
+                results.groups.push((mgid, SendType::Event(0, addr, data)));
+            }
+        }
+        GroupEvent::RelaySend(fgid, data) => {
+            let faddr = layer.read().await.running(&mgid)?.online_direct(&fgid)?;
+            results
+                .layers
+                .push((mgid, fgid, SendType::Event(0, faddr, data)));
+        }
+        GroupEvent::RelayRecv(fgid, faddr, data) => {
+            // the friend's layer event, handle it as from the friend.
+            let msg = RecvType::Event(faddr, data);
+            if let Some(msg) = check_locked(group, &mgid, fgid, msg).await {
+                return app_layer_handle(layer, fgid, mgid, msg).await;
+            }
+        }
+        GroupEvent::RelayStop(fgid) => {
+            // stop relaying for the device.
+            if let Ok(mut relays) = RELAYS.write() {
+                if let Some(devices) = relays.get_mut(&(mgid, fgid)) {
+                    devices.retain(|d| d != &addr);
+                    if devices.is_empty() {
+                        relays.remove(&(mgid, fgid));
+                    }
+                }
+            }
+
+            // the device stop relaying for me, the session is lost.
+            let is_route = ROUTES
+                .write()
+                .map(|mut routes| {
+                    if routes.get(&(mgid, fgid)) == Some(&addr) {
+                        routes.remove(&(mgid, fgid));
+                        true
+                    } else {
+                        false
+                    }
+                })
+                .unwrap_or(false);
+            if is_route {
+                let mut layer_lock = layer.write().await;
+                if let Ok((sid, _fid)) = layer_lock.get_running_remote_id(&mgid, &fgid) {
+                    layer_lock.remove_online(&mgid, &fgid);
+                    results.rpcs.push(session_lost(mgid, &sid));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(results)
+}
+
+/// my device leave, stop relaying for it, and close the sessions relayed by it.
+pub(crate) async fn device_leave(layer: &Arc<RwLock<Layer>>, device: &PeerId) -> HandleResult {
+    let mut results = HandleResult::new();
+    if let Ok(mut relays) = RELAYS.write() {
+        for (_, devices) in relays.iter_mut() {
+            devices.retain(|d| d != device);
+        }
+        relays.retain(|_, devices| devices.len() > 0);
+    }
+
+    let is_route = ROUTES
+        .read()
+        .map(|routes| routes.values().any(|d| d == device))
+        .unwrap_or(false);
+    if is_route {
+        let mut layer_lock = layer.write().await;
+        for (mgid, running) in layer_lock.runnings.iter_mut() {
+            for sid in running.peer_leave(device) {
+                results.rpcs.push(session_lost(*mgid, &sid));
+            }
+        }
+    }
+    results
+}
@@ -13,15 +13,27 @@ use tokio::sync::{
     RwLock,
 };
 
-use crate::account::lang_from_i64;
-use crate::apps::app_rpc_inject;
-use crate::apps::chat::chat_conn;
+use crate::account::{lang_from_i64, Profile};
+use crate::apps::chat::{chat_conn, Friend};
 use crate::apps::group::{add_layer, group_conn, GroupChat};
+use crate::apps::{app_layer_handle, app_rpc_inject};
+use crate::audit::{Audit, AuditKind, AUDIT_KEY_CONTEXT};
+use crate::autologin;
+use crate::bootstrap;
 use crate::event::InnerEvent;
-use crate::group::Group;
-use crate::layer::{Layer, LayerEvent, Online};
-use crate::session::{connect_session, Session, SessionType};
-use crate::storage::{group_db, session_db};
+use crate::group::{Group, GroupEvent};
+use crate::layer::{Layer, LayerEvent, Online, RunningLayer};
+use crate::migrate::consensus::SESSION_TABLE_PATH;
+use crate::presence;
+use crate::relay;
+use crate::session::{connect_session, Session, SessionNotify, SessionType};
+use crate::storage::{account_wipe, chat_db, consensus_db, group_db, session_db};
+
+use schema::{method, opt, req, rpc_method, ParamType::*, NONE_RESULT};
+
+pub(crate) mod auth;
+pub(crate) mod schema;
+pub(crate) mod unix;
 
 pub(crate) fn init_rpc(
     addr: PeerId,
@@ -70,6 +82,32 @@ pub(crate) fn account_update(mgid: GroupId, name: &str, avatar: String) -> RpcPa
     )
 }
 
+#[inline]
+pub(crate) fn account_profile(mgid: GroupId, profile: &Profile) -> RpcParam {
+    rpc_response(
+        0,
+        "account-profile",
+        json!([
+            mgid.to_hex(),
+            profile.bio,
+            profile.status,
+            profile.status_expire,
+            profile.domain
+        ]),
+        mgid,
+    )
+}
+
+#[inline]
+pub(crate) fn audit_create(mgid: GroupId, audit: &Audit) -> RpcParam {
+    rpc_response(0, "audit-create", audit.to_rpc(), mgid)
+}
+
+#[inline]
+pub(crate) fn account_lock(gid: GroupId) -> RpcParam {
+    rpc_response(0, "account-lock", json!([gid.to_hex()]), gid)
+}
+
 #[inline]
 pub(crate) fn session_create(mgid: GroupId, session: &Session) -> RpcParam {
     rpc_response(0, "session-create", session.to_rpc(), mgid)
@@ -82,8 +120,14 @@ pub(crate) fn session_last(
     time: &i64,
     content: &str,
     readed: bool,
+    unread: i64,
 ) -> RpcParam {
-    rpc_response(0, "session-last", json!([id, time, content, readed]), mgid)
+    rpc_response(
+        0,
+        "session-last",
+        json!([id, time, content, readed, unread]),
+        mgid,
+    )
 }
 
 #[inline]
@@ -97,17 +141,20 @@ pub(crate) fn session_update_name(mgid: GroupId, id: &i64, name: &str) -> RpcPar
 }
 
 #[inline]
-pub(crate) fn session_update(
-    mgid: GroupId,
-    id: &i64,
-    addr: &PeerId,
-    name: &str,
-    is_top: bool,
-) -> RpcParam {
+pub(crate) fn session_update(mgid: GroupId, session: &Session) -> RpcParam {
     rpc_response(
         0,
         "session-update",
-        json!([id, addr.to_hex(), name, is_top]),
+        json!([
+            session.id,
+            session.addr.to_hex(),
+            session.name,
+            session.is_top,
+            session.is_close,
+            session.notify.to_int(),
+            session.is_archived,
+            session.mute_until,
+        ]),
         mgid,
     )
 }
@@ -138,10 +185,15 @@ pub(crate) fn session_close(mgid: GroupId, id: &i64) -> RpcParam {
 }
 
 #[inline]
-fn session_list(sessions: Vec<Session>) -> RpcParam {
+fn session_list(sessions: Vec<Session>, running: &RunningLayer) -> RpcParam {
     let mut results = vec![];
     for session in sessions {
-        results.push(session.to_rpc());
+        let mut rpc = session.to_rpc();
+        // the online session is relayed by my other device.
+        if let Some(fields) = rpc.as_array_mut() {
+            fields.push(json!(running.is_relayed(&session.gid)));
+        }
+        results.push(rpc);
     }
     json!(results)
 }
@@ -153,6 +205,15 @@ pub(crate) async fn sleep_waiting_close_stable(
     layers: HashMap<PeerId, GroupId>,
 ) -> std::result::Result<(), SendError<SendMessage>> {
     tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+    close_stable(&sender, groups, layers).await
+}
+
+/// close the stable connections of the devices & friends.
+pub(crate) async fn close_stable(
+    sender: &Sender<SendMessage>,
+    groups: HashMap<PeerId, ()>,
+    layers: HashMap<PeerId, GroupId>,
+) -> std::result::Result<(), SendError<SendMessage>> {
     for (addr, _) in groups {
         sender
             .send(SendMessage::Group(
@@ -175,6 +236,106 @@ pub(crate) async fn sleep_waiting_close_stable(
     Ok(())
 }
 
+/// login the account, add it to TDN and load the services created by it.
+pub(crate) async fn login(
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    ogid: GroupId,
+    lock: &str,
+    info: String,
+    results: &mut HandleResult,
+) -> Result<()> {
+    let (id, running) = group.write().await.add_running(&ogid, lock)?;
+    if running {
+        return Ok(());
+    }
+    group
+        .write()
+        .await
+        .audit(&ogid, AuditKind::Login, info, results)?;
+
+    // add AddGroup to TDN.
+    results.networks.push(NetworkType::AddGroup(ogid));
+
+    let mut layer_lock = layer.write().await;
+    layer_lock.add_running(&ogid, ogid, id, 0)?; // TODO account current state height.
+
+    // load all services layer created by this account.
+    // 1. group chat.
+    let self_addr = layer_lock.addr.clone();
+    let group_db = group_db(&layer_lock.base, &ogid)?;
+    let group_chats = GroupChat::local(&group_db)?;
+    for g in group_chats {
+        layer_lock.add_running(&g.g_id, ogid, g.id, g.height)?;
+        results.networks.push(NetworkType::AddGroup(g.g_id));
+
+        // 2. online group to self group onlines.
+        if let Some(session) = connect_session(
+            &layer_lock.base,
+            &ogid,
+            &SessionType::Group,
+            &g.id,
+            &self_addr,
+        )? {
+            layer_lock.running_mut(&ogid)?.check_add_online(
+                g.g_id,
+                Online::Direct(self_addr),
+                session.id,
+                g.id,
+            )?;
+        }
+    }
+    drop(layer_lock);
+
+    debug!("Account Logined: {}.", ogid.to_hex());
+    Ok(())
+}
+
+/// logout all running accounts, send offline event to friends and remove them from TDN,
+/// return the stable connections which need close.
+pub(crate) async fn logout_all(
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    info: String,
+    results: &mut HandleResult,
+) -> Result<(HashMap<PeerId, ()>, HashMap<PeerId, GroupId>)> {
+    let layer_lock = layer.read().await;
+    let mut group_lock = group.write().await;
+    let keys = group_lock.list_running_user();
+
+    for gid in keys {
+        // best-effort, the locked account has no key to audit, and it still need logout.
+        if let Err(e) = group_lock.audit(&gid, AuditKind::Logout, info.clone(), results) {
+            warn!("Account {} logout audit failure: {}", gid.to_hex(), e);
+        }
+
+        if let Ok(running) = layer_lock.running(&gid) {
+            for (fgid, addr) in running.onlines() {
+                // send a event that is offline.
+                let data = bincode::serialize(&LayerEvent::Offline(*fgid))?;
+                let msg = SendType::Event(0, *addr, data);
+                results.layers.push((gid, *fgid, msg));
+            }
+        }
+
+        debug!("Account Offline: {}.", gid.to_hex());
+        // add Remove Group to TDN.
+        results.networks.push(NetworkType::DelGroup(gid));
+    }
+    drop(group_lock);
+    drop(layer_lock);
+
+    let mut layer_lock = layer.write().await;
+    let layers = layer_lock.remove_all_running();
+    drop(layer_lock);
+
+    let mut group_lock = group.write().await;
+    let groups = group_lock.remove_all_running();
+    drop(group_lock);
+
+    Ok((groups, layers))
+}
+
 #[inline]
 pub(crate) async fn inner_rpc(uid: u64, method: &str, sender: &Sender<SendMessage>) -> Result<()> {
     // Inner network default rpc method. only use in http-rpc.
@@ -210,6 +371,30 @@ pub(crate) async fn inner_rpc(uid: u64, method: &str, sender: &Sender<SendMessag
     Err(anyhow!("not found"))
 }
 
+/// validate the request's params by the method's schema.
+/// return the error response when invalid. unknown method is handled by RpcHandler.
+pub(crate) fn validate_rpc(params: &RpcParam) -> Option<RpcParam> {
+    let method = params["method"].as_str().unwrap_or("");
+    let schema = schema::find(method)?;
+    let empty = vec![];
+    let values = params["params"].as_array().unwrap_or(&empty);
+    match schema.validate(values) {
+        Ok(()) => None,
+        Err(msg) => {
+            let id = params["id"].as_u64().unwrap_or(0);
+            Some(auth::rpc_error(id, -32602, &msg))
+        }
+    }
+}
+
+/// the remote gid of the session-scoped request, which has the `fgid` param in schema.
+pub(crate) fn session_rpc(params: &RpcParam) -> Option<GroupId> {
+    let method = params["method"].as_str().unwrap_or("");
+    let schema = schema::find(method)?;
+    let i = schema.params.iter().position(|p| p.name == "fgid")?;
+    GroupId::from_hex(params["params"][i].as_str()?).ok()
+}
+
 fn new_rpc_handler(
     addr: PeerId,
     group: Arc<RwLock<Group>>,
@@ -217,21 +402,49 @@ fn new_rpc_handler(
 ) -> RpcHandler<RpcState> {
     let mut handler = RpcHandler::new(RpcState { group, layer });
 
-    handler.add_method("echo", |_, params, _| async move {
-        Ok(HandleResult::rpc(json!(params)))
-    });
+    rpc_method!(
+        handler,
+        method("echo", "echo the params.", &[], "params"),
+        |_, params, _| async move { Ok(HandleResult::rpc(json!(params))) }
+    );
 
-    handler.add_method("account-system-info", move |_, _, _| async move {
-        Ok(HandleResult::rpc(json!(vec![addr.to_hex()])))
-    });
+    rpc_method!(
+        handler,
+        method(
+            "rpc-discover",
+            "the OpenRPC document of all methods.",
+            &[],
+            "document",
+        ),
+        |_, _, _| async move { Ok(HandleResult::rpc(schema::discover(&schema::all()))) }
+    );
 
-    handler.add_method(
-        "add-bootstrap",
+    rpc_method!(
+        handler,
+        method(
+            "account-system-info",
+            "this device's peer id.",
+            &[],
+            "[addr]",
+        ),
+        move |_, _, _| async move { Ok(HandleResult::rpc(json!(vec![addr.to_hex()]))) }
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "add-bootstrap",
+            "connect to the bootstrap peer, and save it as seed.",
+            &[req("socket", Str), req("transport", Str)],
+            NONE_RESULT,
+        ),
         |_gid, params: Vec<RpcParam>, _| async move {
             let socket = params[0].as_str().ok_or(RpcError::ParseError)?;
             let transport = params[1].as_str().ok_or(RpcError::ParseError)?;
 
             if let Ok(addr) = socket.parse::<SocketAddr>() {
+                // persist it, used as seed when restart.
+                bootstrap::add_seed(addr, transport)?;
                 Ok(HandleResult::network(NetworkType::Connect(
                     Peer::socket_transport(addr, transport),
                 )))
@@ -241,17 +454,48 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-list",
+    rpc_method!(
+        handler,
+        method(
+            "bootstrap-list",
+            "list the cached peers, seeds and public seeds.",
+            &[],
+            "[is_private, [[socket, transport, source]]]",
+        ),
+        |_, _, _| async move { Ok(HandleResult::rpc(bootstrap::list())) }
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "bootstrap-remove",
+            "remove the seed and cached peer.",
+            &[req("socket", Str)],
+            "[socket]",
+        ),
+        |_gid, params: Vec<RpcParam>, _| async move {
+            let socket = params[0].as_str().ok_or(RpcError::ParseError)?;
+            let addr = socket
+                .parse::<SocketAddr>()
+                .or(Err(RpcError::InvalidRequest))?;
+            bootstrap::remove_seed(&addr)?;
+            Ok(HandleResult::rpc(json!([socket])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "account-list",
+            "list all accounts.",
+            &[],
+            "[[gid, name, avatar]]",
+        ),
         |_gid, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let mut users: Vec<Vec<String>> = vec![];
             let group_lock = state.group.read().await;
-            for (gid, user) in group_lock.list_users().iter() {
-                users.push(vec![
-                    gid.to_hex(),
-                    user.name.clone(),
-                    base64::encode(&user.avatar),
-                ]);
+            for (gid, name, avatar) in group_lock.list_users() {
+                users.push(vec![gid.to_hex(), name.clone(), base64::encode(avatar)]);
             }
             drop(group_lock);
 
@@ -259,8 +503,14 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-generate",
+    rpc_method!(
+        handler,
+        method(
+            "account-generate",
+            "generate the mnemonic.",
+            &[req("lang", Int)],
+            "[mnemonic]",
+        ),
         |_gid, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
             let lang = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let language = lang_from_i64(lang);
@@ -269,8 +519,21 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-create",
+    rpc_method!(
+        handler,
+        method(
+            "account-create",
+            "create account from the mnemonic.",
+            &[
+                req("lang", Int),
+                req("seed", Str),
+                req("pass", Str),
+                req("name", Str),
+                req("lock", Str),
+                req("avatar", Str),
+            ],
+            "[gid]",
+        ),
         |_gid, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let lang = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let seed = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -298,8 +561,21 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-restore",
+    rpc_method!(
+        handler,
+        method(
+            "account-restore",
+            "restore account from the mnemonic, and sync from the device.",
+            &[
+                req("lang", Int),
+                req("seed", Str),
+                req("pass", Str),
+                req("name", Str),
+                req("lock", Str),
+                req("addr", Str),
+            ],
+            "[gid]",
+        ),
         |_gid, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let lang = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let seed = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -338,8 +614,21 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-update",
+    rpc_method!(
+        handler,
+        method(
+            "account-update",
+            "update the name, avatar and profile.",
+            &[
+                req("name", Str),
+                req("avatar", Str),
+                opt("bio", Str),
+                opt("status", Str),
+                opt("status_expire", Int),
+                opt("domain", Str),
+            ],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let name = params[0].as_str().ok_or(RpcError::ParseError)?;
             let avatar = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -348,7 +637,6 @@ fn new_rpc_handler(
 
             let mut group_lock = state.group.write().await;
             group_lock.update_account(gid, name, avatar_bytes.clone())?;
-            let user = group_lock.clone_user(&gid)?;
 
             let mut results = HandleResult::new();
             group_lock.broadcast(
@@ -358,17 +646,52 @@ fn new_rpc_handler(
                 0,
                 &mut results,
             )?;
+
+            // optional profile: bio, status, status_expire, domain.
+            if params.len() > 2 {
+                let old = group_lock.account(&gid)?.profile.clone();
+                let profile = Profile::new(
+                    params[2].as_str().map(|s| s.to_owned()).unwrap_or(old.bio),
+                    params
+                        .get(3)
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_owned())
+                        .unwrap_or(old.status),
+                    params
+                        .get(4)
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(old.status_expire),
+                    params
+                        .get(5)
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_owned())
+                        .unwrap_or(old.domain),
+                );
+                group_lock.update_profile(gid, profile.clone())?;
+                group_lock.broadcast(&gid, InnerEvent::UserProfile(profile), 0, 0, &mut results)?;
+            }
+            let user = group_lock.clone_user(&gid)?;
+            let profile = group_lock.account(&gid)?.profile.clone();
             drop(group_lock);
 
             // broadcast all friends.
-            state.layer.read().await.broadcast(user, &mut results);
+            let layer_lock = state.layer.read().await;
+            layer_lock.broadcast(user, &mut results);
+            layer_lock.broadcast_profile(gid, profile, &mut results);
+            drop(layer_lock);
 
             Ok(results)
         },
     );
 
-    handler.add_method(
-        "account-pin-check",
+    rpc_method!(
+        handler,
+        method(
+            "account-pin-check",
+            "check the account's pin.",
+            &[req("gid", Hex), req("lock", Str)],
+            "[is_ok]",
+        ),
         |_gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let gid = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
             let lock = params[1].as_str().ok_or(RpcError::ParseError)?;
@@ -377,119 +700,288 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-pin",
+    rpc_method!(
+        handler,
+        method(
+            "account-pin",
+            "change the account's pin.",
+            &[req("old", Str), req("new", Str)],
+            "[new]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let old = params[0].as_str().ok_or(RpcError::ParseError)?;
             let new = params[1].as_str().ok_or(RpcError::ParseError)?;
-            let result = HandleResult::rpc(json!([new]));
-            state.group.write().await.pin(&gid, old, new)?;
-            Ok(result)
+            let mut results = HandleResult::rpc(json!([new]));
+            let mut group_lock = state.group.write().await;
+            group_lock.pin(&gid, old, new)?;
+            autologin::update(group_lock.base(), &gid, new)?;
+            group_lock.audit(&gid, AuditKind::Pin, String::new(), &mut results)?;
+            Ok(results)
         },
     );
 
-    handler.add_method(
-        "account-mnemonic",
+    rpc_method!(
+        handler,
+        method(
+            "account-mnemonic",
+            "show the account's mnemonic.",
+            &[req("lock", Str)],
+            "[mnemonic]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let lock = params[0].as_str().ok_or(RpcError::ParseError)?;
 
-            let mnemonic = state.group.read().await.mnemonic(&gid, lock)?;
-            Ok(HandleResult::rpc(json!([mnemonic])))
+            let mut group_lock = state.group.write().await;
+            let mnemonic = group_lock.mnemonic(&gid, lock)?;
+            let mut results = HandleResult::rpc(json!([mnemonic]));
+            group_lock.audit(&gid, AuditKind::Mnemonic, String::new(), &mut results)?;
+            Ok(results)
         },
     );
 
-    handler.add_method(
-        "account-login",
+    rpc_method!(
+        handler,
+        method(
+            "account-login",
+            "login the account.",
+            &[req("gid", Hex), req("lock", Str)],
+            "[gid]",
+        ),
         |_gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let ogid = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
             let me_lock = params[1].as_str().ok_or(RpcError::ParseError)?;
 
             let mut results = HandleResult::rpc(json!([ogid.to_hex()]));
+            login(
+                &state.group,
+                &state.layer,
+                ogid,
+                me_lock,
+                String::new(),
+                &mut results,
+            )
+            .await?;
+            Ok(results)
+        },
+    );
 
-            let (id, running) = state.group.write().await.add_running(&ogid, me_lock)?;
-            if running {
-                return Ok(results);
+    rpc_method!(
+        handler,
+        method("account-logout", "logout all accounts.", &[], NONE_RESULT),
+        |_gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let mut results = HandleResult::new();
+            let (groups, layers) =
+                logout_all(&state.group, &state.layer, String::new(), &mut results).await?;
+            let sender = state.group.read().await.sender();
+            tokio::spawn(sleep_waiting_close_stable(sender, groups, layers));
+
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "account-delete",
+            "delete the account and wipe data, and close the friendships, close_friends = false keeps them.",
+            &[
+                req("gid", Hex),
+                req("lock", Str),
+                opt("close_friends", Bool),
+            ],
+            NONE_RESULT,
+        ),
+        |_gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let gid = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
+            let lock = params[1].as_str().ok_or(RpcError::ParseError)?;
+            // default close the friendships, opt out to keep them in my other devices.
+            let close_friends = params.get(2).and_then(|v| v.as_bool()).unwrap_or(true);
+
+            let group_lock = state.group.read().await;
+            if !group_lock.check_lock(&gid, lock) {
+                return Err(RpcError::Custom("Lock is invalid!".to_owned()));
             }
 
-            // add AddGroup to TDN.
-            results.networks.push(NetworkType::AddGroup(ogid));
+            let mut results = HandleResult::new();
 
-            let mut layer_lock = state.layer.write().await;
-            layer_lock.add_running(&ogid, ogid, id, 0)?; // TODO account current state height.
-
-            // load all services layer created by this account.
-            // 1. group chat.
-            let self_addr = layer_lock.addr.clone();
-            let group_db = group_db(&layer_lock.base, &ogid)?;
-            let group_chats = GroupChat::local(&group_db)?;
-            for g in group_chats {
-                layer_lock.add_running(&g.g_id, ogid, g.id, g.height)?;
-                results.networks.push(NetworkType::AddGroup(g.g_id));
-
-                // 2. online group to self group onlines.
-                if let Some(session) = connect_session(
-                    &layer_lock.base,
-                    &ogid,
-                    &SessionType::Group,
-                    &g.id,
-                    &self_addr,
-                )? {
-                    layer_lock.running_mut(&ogid)?.check_add_online(
-                        g.g_id,
-                        Online::Direct(self_addr),
-                        session.id,
-                        g.id,
-                    )?;
+            // 1. close all friendship when wipe the identity everywhere.
+            if close_friends {
+                let db = chat_db(group_lock.base(), &gid)?;
+                let friends = Friend::list(&db)?;
+                drop(db);
+                let data = bincode::serialize(&LayerEvent::Close)?;
+                for friend in friends {
+                    if !friend.is_closed {
+                        let msg = SendType::Event(0, friend.addr, data.clone());
+                        results.layers.push((gid, friend.gid, msg));
+                    }
                 }
             }
-            drop(layer_lock);
 
-            debug!("Account Logined: {}.", ogid.to_hex());
+            // 2. delete this device in my other devices.
+            if let Ok(running) = group_lock.running(&gid) {
+                let event = GroupEvent::DeviceDelete(*group_lock.addr());
+                for (addr, (_peer, _id, online)) in &running.distributes {
+                    if *online && addr != group_lock.addr() {
+                        results
+                            .groups
+                            .push((gid, group_lock.event_message(*addr, &event)?));
+                    }
+                }
+            }
+            drop(group_lock);
+
+            // 3. logout account.
+            let layers = state.layer.write().await.remove_running(&gid);
+            let mut group_lock = state.group.write().await;
+            let groups = group_lock.remove_running(&gid);
+            group_lock.delete_account(&gid, lock)?;
+            let sender = group_lock.sender();
+            let base = group_lock.base().clone();
+            drop(group_lock);
+            results.networks.push(NetworkType::DelGroup(gid));
+
+            // 4. wipe all data when connections closed.
+            let wipe_base = base.clone();
+            tokio::spawn(async move {
+                let _ = sleep_waiting_close_stable(sender, groups, layers).await;
+                if let Err(e) = account_wipe(&wipe_base, &gid).await {
+                    error!("Account wipe failure: {:?}", e);
+                }
+            });
 
+            // the account is deleted, a stale autologin entry only fails to login when start.
+            if let Err(e) = autologin::remove(&base, &gid) {
+                error!("Account autologin remove failure: {:?}", e);
+            }
+
+            debug!("Account Deleted: {}.", gid.to_hex());
             Ok(results)
         },
     );
 
-    handler.add_method(
-        "account-logout",
-        |_gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
-            let mut results = HandleResult::new();
+    rpc_method!(
+        handler,
+        method(
+            "account-autolock",
+            "set the auto-lock seconds, 0 is disabled.",
+            &[req("seconds", Int)],
+            NONE_RESULT,
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let seconds = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            if seconds < 0 {
+                return Err(RpcError::InvalidRequest);
+            }
+            state.group.write().await.update_autolock(&gid, seconds)?;
+            Ok(HandleResult::new())
+        },
+    );
 
-            let group_lock = state.group.read().await;
-            let layer_lock = state.layer.read().await;
-            let keys = group_lock.list_running_user();
-
-            for gid in keys {
-                for (fgid, addr) in layer_lock.running(&gid)?.onlines() {
-                    // send a event that is offline.
-                    let data = bincode::serialize(&LayerEvent::Offline(*fgid))?;
-                    let msg = SendType::Event(0, *addr, data);
-                    results.layers.push((gid, *fgid, msg));
-                }
+    rpc_method!(
+        handler,
+        method(
+            "account-autologin",
+            "enable/disable the unattended auto-login when daemon start.",
+            &[req("lock", Str), req("enable", Bool)],
+            NONE_RESULT,
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let lock = params[0].as_str().ok_or(RpcError::ParseError)?;
+            let enable = params[1].as_bool().ok_or(RpcError::ParseError)?;
 
-                debug!("Account Offline: {}.", gid.to_hex());
-                // add Remove Group to TDN.
-                results.networks.push(NetworkType::DelGroup(gid));
+            let mut group_lock = state.group.write().await;
+            if !group_lock.check_lock(&gid, lock) {
+                return Err(RpcError::Custom("Lock is invalid!".to_owned()));
             }
-            drop(group_lock);
-            drop(layer_lock);
 
-            let mut layer_lock = state.layer.write().await;
-            let layers = layer_lock.remove_all_running();
-            drop(layer_lock);
+            let mut results = HandleResult::new();
+            let base = group_lock.base().clone();
+            let info = if enable {
+                autologin::enable(&base, &gid, lock)?;
+                "enabled, the lock is stored in the device".to_owned()
+            } else {
+                autologin::disable(&base, &gid)?;
+                "disabled".to_owned()
+            };
+            group_lock.audit(&gid, AuditKind::AutoLogin, info, &mut results)?;
+            Ok(results)
+        },
+    );
 
-            let mut group_lock = state.group.write().await;
-            let groups = group_lock.remove_all_running();
-            let sender = group_lock.sender();
-            drop(group_lock);
-            tokio::spawn(sleep_waiting_close_stable(sender, groups, layers));
+    rpc_method!(
+        handler,
+        method("account-lock", "lock the account.", &[], "[gid]"),
+        |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            state.group.write().await.lock(&gid)?;
+            Ok(HandleResult::rpc(json!([gid.to_hex()])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "account-unlock",
+            "unlock the account.",
+            &[req("lock", Str)],
+            "[gid]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let lock = params[0].as_str().ok_or(RpcError::ParseError)?;
+
+            let events = state.group.write().await.unlock(&gid, lock)?;
+            let mut results = HandleResult::rpc(json!([gid.to_hex()]));
+
+            // handle the queued events.
+            for (fgid, msg) in events {
+                if let Ok(res) = app_layer_handle(&state.layer, fgid, gid, msg).await {
+                    results.rpcs.extend(res.rpcs);
+                    results.groups.extend(res.groups);
+                    results.layers.extend(res.layers);
+                    results.networks.extend(res.networks);
+                }
+            }
 
             Ok(results)
         },
     );
 
-    handler.add_method(
-        "account-online",
+    rpc_method!(
+        handler,
+        method(
+            "audit-list",
+            "list the audit logs and verify the chain.",
+            &[opt("from", Int), opt("limit", Int)],
+            "[broken_id, [Audit]]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let from = params.get(0).and_then(|v| v.as_i64()).unwrap_or(0);
+            let limit = params.get(1).and_then(|v| v.as_i64()).unwrap_or(100);
+
+            let group_lock = state.group.read().await;
+            let key = group_lock.derive_key(&gid, AUDIT_KEY_CONTEXT)?;
+            let db = consensus_db(group_lock.base(), &gid)?;
+            drop(group_lock);
+            let broken = Audit::verify(&db, &key)?;
+            let audits: Vec<RpcParam> = Audit::list(&db, from, limit)?
+                .iter()
+                .map(|a| a.to_rpc())
+                .collect();
+            db.close()?;
+
+            // broken is the first tampered entry's id, null if chain is valid.
+            Ok(HandleResult::rpc(json!([broken, audits])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "account-online",
+            "connect the account to the distributed devices.",
+            &[req("gid", Hex)],
+            NONE_RESULT,
+        ),
         |_gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let gid = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
 
@@ -508,8 +1000,14 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "account-offline",
+    rpc_method!(
+        handler,
+        method(
+            "account-offline",
+            "disconnect the account from the network.",
+            &[req("gid", Hex)],
+            NONE_RESULT,
+        ),
         |_gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let gid = GroupId::from_hex(params[0].as_str().ok_or(RpcError::ParseError)?)?;
 
@@ -538,27 +1036,49 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "session-list",
-        |gid: GroupId, _params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+    rpc_method!(
+        handler,
+        method(
+            "session-list",
+            "list the sessions, filter by archived and unread. the last field is_relayed.",
+            &[opt("is_archived", Bool), opt("only_unread", Bool)],
+            "[Session]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let db = session_db(state.layer.read().await.base(), &gid)?;
-            Ok(HandleResult::rpc(session_list(Session::list(&db)?)))
+            // optional filters: is_archived, only_unread.
+            let sessions = if let Some(is_archived) = params.get(0).and_then(|v| v.as_bool()) {
+                let only_unread = params.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+                Session::list_by(&db, is_archived, only_unread)?
+            } else {
+                Session::list(&db)?
+            };
+            let layer_lock = state.layer.read().await;
+            let running = layer_lock.running(&gid)?;
+            Ok(HandleResult::rpc(session_list(sessions, running)))
         },
     );
 
-    handler.add_method(
-        "session-connect",
+    rpc_method!(
+        handler,
+        method(
+            "session-connect",
+            "connect the session.",
+            &[req("id", Int), req("remote", Hex)],
+            "[id, addr]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let remote = GroupId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
 
-            let group_lock = state.group.read().await;
-            let db = session_db(group_lock.base(), &gid)?;
-            Session::readed(&db, &id)?;
-
             let mut layer_lock = state.layer.write().await;
             let online = layer_lock.running_mut(&gid)?.active(&remote, true);
             drop(layer_lock);
+
+            // lock the layer before the group, same as the layer handle.
+            let group_lock = state.group.read().await;
+            let db = session_db(group_lock.base(), &gid)?;
+            Session::readed(&db, &id)?;
             if let Some(addr) = online {
                 return Ok(HandleResult::rpc(json!([id, addr.to_hex()])));
             }
@@ -573,6 +1093,14 @@ fn new_rpc_handler(
                     results
                         .layers
                         .push((gid, s.gid, chat_conn(proof, Peer::peer(s.addr))));
+
+                    // ask my online devices to relay it, when direct connect failure.
+                    tokio::spawn(relay::request(
+                        state.group.clone(),
+                        state.layer.clone(),
+                        gid,
+                        s.gid,
+                    ));
                 }
                 SessionType::Group => {
                     let proof = group_lock.prove_addr(&gid, &s.addr)?;
@@ -588,8 +1116,14 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "session-suspend",
+    rpc_method!(
+        handler,
+        method(
+            "session-suspend",
+            "suspend the session.",
+            &[req("id", Int), req("remote", Hex), req("must", Bool)],
+            "[id]",
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let remote = GroupId::from_hex(params[1].as_str().ok_or(RpcError::ParseError)?)?;
@@ -633,8 +1167,14 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "session-readed",
+    rpc_method!(
+        handler,
+        method(
+            "session-readed",
+            "mark the session readed.",
+            &[req("id", Int)],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let db = session_db(state.group.read().await.base(), &gid)?;
@@ -643,16 +1183,124 @@ fn new_rpc_handler(
         },
     );
 
-    handler.add_method(
-        "session-update",
+    rpc_method!(
+        handler,
+        method(
+            "session-notify",
+            "change the session's notify level.",
+            &[req("id", Int), req("notify", Int)],
+            "[id, notify]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            let notify = SessionNotify::from_int(params[1].as_i64().ok_or(RpcError::ParseError)?);
+
+            let mut group_lock = state.group.write().await;
+            let db = session_db(group_lock.base(), &gid)?;
+            Session::update_notify(&db, &id, &notify)?;
+            let s = Session::get(&db, &id)?;
+            drop(db);
+
+            let mut results = HandleResult::rpc(json!([id, notify.to_int()]));
+            group_lock.broadcast(
+                &gid,
+                InnerEvent::SessionUpdate(s.gid, s.is_top, s.notify, s.is_archived, s.mute_until),
+                SESSION_TABLE_PATH,
+                s.id,
+                &mut results,
+            )?;
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "session-update",
+            "update the session's top, close, archived and mute.",
+            &[
+                req("id", Int),
+                req("is_top", Bool),
+                req("is_close", Bool),
+                opt("is_archived", Bool),
+                opt("mute_until", Int),
+            ],
+            NONE_RESULT,
+        ),
         |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
             let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
             let is_top = params[1].as_bool().ok_or(RpcError::ParseError)?;
             let is_close = params[2].as_bool().ok_or(RpcError::ParseError)?;
 
-            let db = session_db(state.group.read().await.base(), &gid)?;
-            Session::update(&db, &id, is_top, is_close)?;
-            Ok(HandleResult::new())
+            let mut group_lock = state.group.write().await;
+            let db = session_db(group_lock.base(), &gid)?;
+            let mut s = Session::get(&db, &id)?;
+            // optional params: is_archived, mute_until. keep current when missing.
+            s.is_top = is_top;
+            s.is_close = is_close;
+            if let Some(is_archived) = params.get(3).and_then(|v| v.as_bool()) {
+                s.is_archived = is_archived;
+            }
+            if let Some(mute_until) = params.get(4).and_then(|v| v.as_i64()) {
+                s.mute_until = mute_until;
+            }
+            Session::update(&db, &id, s.is_top, s.is_close, s.is_archived, s.mute_until)?;
+            drop(db);
+
+            let mut results = HandleResult::new();
+            group_lock.broadcast(
+                &gid,
+                InnerEvent::SessionUpdate(s.gid, s.is_top, s.notify, s.is_archived, s.mute_until),
+                SESSION_TABLE_PATH,
+                s.id,
+                &mut results,
+            )?;
+            Ok(results)
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "presence",
+            "the session's online status, last seen and online history (newest first).",
+            &[req("id", Int), opt("limit", Int)],
+            "[id, is_online, last_seen, [[is_online, datetime]]]",
+        ),
+        |gid: GroupId, params: Vec<RpcParam>, state: Arc<RpcState>| async move {
+            let id = params[0].as_i64().ok_or(RpcError::ParseError)?;
+            let limit = params.get(1).and_then(|v| v.as_i64()).unwrap_or(20);
+
+            let layer_lock = state.layer.read().await;
+            let db = session_db(layer_lock.base(), &gid)?;
+            let s = Session::get(&db, &id)?;
+            let (mut last_seen, history) = presence::history(&db, &id, limit)?;
+            drop(db);
+
+            let is_online = layer_lock.running(&gid)?.is_online(&s.gid);
+            if is_online {
+                last_seen = presence::now();
+            }
+            Ok(HandleResult::rpc(json!([
+                id, is_online, last_seen, history
+            ])))
+        },
+    );
+
+    rpc_method!(
+        handler,
+        method(
+            "presence-policy",
+            "change the heartbeat & suspend timeout seconds, missing is keep.",
+            &[opt("heartbeat", Int), opt("suspend", Int)],
+            "[heartbeat, suspend]",
+        ),
+        |_gid: GroupId, params: Vec<RpcParam>, _state: Arc<RpcState>| async move {
+            let heartbeat = params.get(0).and_then(|v| v.as_i64());
+            let suspend = params.get(1).and_then(|v| v.as_i64());
+            Ok(HandleResult::rpc(presence::update_policy(
+                heartbeat, suspend,
+            )))
         },
     );
 
@@ -0,0 +1,331 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tdn::types::{
+    group::GroupId,
+    primitive::Result,
+    rpc::{json, rpc_response, RpcParam},
+};
+
+/// the admin token file in data directory, client read it to handshake.
+pub(crate) const RPC_TOKEN_FILE: &'static str = "rpc.token";
+/// other tokens which created by admin, every line is: token scope,scope.
+const RPC_TOKENS_FILE: &'static str = "rpc.tokens";
+
+/// methods which only read data (besides the suffix rules).
+const READ_METHODS: [&str; 8] = [
+    "echo",
+    "account-system-info",
+    "wallet-token",
+    "wallet-nft",
+    "rpc-discover",
+    "rpc-ping",
+    "rpc-subscribe",
+    "rpc-unsubscribe",
+];
+
+/// suffix of methods which only read data.
+const READ_SUFFIXES: [&str; 11] = [
+    "-list",
+    "-echo",
+    "-detail",
+    "-status",
+    "-safety",
+    "-check",
+    "-holders",
+    "-shares",
+    "-info",
+    "-gas-price",
+    "-last",
+];
+
+/// methods which can read or change the secret of account.
+const ADMIN_METHODS: [&str; 6] = [
+    "account-mnemonic",
+    "account-delete",
+    "account-pin",
+    "account-autologin",
+    "chat-recovery-setup",
+    "chat-recovery-combine",
+];
+
+/// Permission scope of the RPC token.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Scope {
+    /// only read methods.
+    Read,
+    /// all methods, except wallet and admin.
+    Write,
+    /// wallet methods.
+    Wallet,
+    /// all methods.
+    Admin,
+}
+
+impl Scope {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Wallet => "wallet",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim() {
+            "read" => Some(Scope::Read),
+            "write" => Some(Scope::Write),
+            "wallet" => Some(Scope::Wallet),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+
+    /// the scope which the method required.
+    fn required(method: &str) -> Scope {
+        if ADMIN_METHODS.contains(&method) || method.starts_with("rpc-token-") {
+            Scope::Admin
+        } else if READ_METHODS.contains(&method)
+            || READ_SUFFIXES.iter().any(|s| method.ends_with(s))
+        {
+            Scope::Read
+        } else if method.starts_with("wallet-") {
+            Scope::Wallet
+        } else {
+            Scope::Write
+        }
+    }
+
+    fn allow(scopes: &[Scope], method: &str) -> bool {
+        match Scope::required(method) {
+            Scope::Read => scopes.len() > 0,
+            Scope::Write => scopes.contains(&Scope::Write) || scopes.contains(&Scope::Admin),
+            Scope::Wallet => scopes.contains(&Scope::Wallet) || scopes.contains(&Scope::Admin),
+            Scope::Admin => scopes.contains(&Scope::Admin),
+        }
+    }
+}
+
+pub(crate) enum AuthResult {
+    /// the request is authorised, continue handle it.
+    Pass,
+    /// the request is handled (or denied), send the response.
+    Reply(RpcParam),
+    /// the `rpc-auth` handshake is success, send the response.
+    Authed(RpcParam),
+}
+
+/// Local RPC authentication, token-based.
+pub(crate) struct RpcAuth {
+    base: PathBuf,
+    /// all tokens and scopes, include the admin token.
+    tokens: HashMap<String, Vec<Scope>>,
+    /// WebSocket clients which had handshaked, the token and last request time.
+    sessions: HashMap<u64, (String, Instant)>,
+}
+
+#[inline]
+fn new_token() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+}
+
+/// write the token file, only the user which running daemon can read it.
+fn save_file(path: &PathBuf, content: &str) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // fix the mode when file created by others.
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options.open(path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+#[inline]
+pub(crate) fn rpc_error(id: u64, code: i64, msg: &str) -> RpcParam {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": msg}})
+}
+
+impl RpcAuth {
+    /// load the tokens, if the admin token file not exists, generate it.
+    pub async fn init(base: PathBuf) -> Result<Self> {
+        let mut token_path = base.clone();
+        token_path.push(RPC_TOKEN_FILE);
+        let admin = if token_path.exists() {
+            tokio::fs::read_to_string(&token_path)
+                .await?
+                .trim()
+                .to_owned()
+        } else {
+            let token = new_token();
+            save_file(&token_path, &token)?;
+            token
+        };
+
+        let mut tokens = HashMap::new();
+        let mut tokens_path = base.clone();
+        tokens_path.push(RPC_TOKENS_FILE);
+        if tokens_path.exists() {
+            let content = tokio::fs::read_to_string(&tokens_path).await?;
+            for line in content.lines() {
+                let mut items = line.split_whitespace();
+                if let (Some(token), Some(scopes)) = (items.next(), items.next()) {
+                    let scopes = scopes.split(",").filter_map(Scope::from_str).collect();
+                    tokens.insert(token.to_owned(), scopes);
+                }
+            }
+        }
+        tokens.insert(admin, vec![Scope::Admin]);
+
+        Ok(RpcAuth {
+            base,
+            tokens,
+            sessions: HashMap::new(),
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut content = String::new();
+        for (token, scopes) in &self.tokens {
+            if scopes.contains(&Scope::Admin) {
+                continue;
+            }
+            let scopes: Vec<&str> = scopes.iter().map(|s| s.to_str()).collect();
+            content.push_str(&format!("{} {}\n", token, scopes.join(",")));
+        }
+
+        let mut path = self.base.clone();
+        path.push(RPC_TOKENS_FILE);
+        save_file(&path, &content)
+    }
+
+    /// remove the sessions of closed WebSocket clients (idle too long).
+    pub fn expire(&mut self, idle: Duration) {
+        self.sessions
+            .retain(|_, (_, active)| active.elapsed() < idle);
+    }
+
+    /// check the request, WebSocket client need handshake with `rpc-auth` first,
+    /// or carry the `token` in every request (HTTP).
+    pub fn handle(&mut self, uid: u64, is_ws: bool, params: &RpcParam) -> AuthResult {
+        let id = params["id"].as_u64().unwrap_or(0);
+        let method = params["method"].as_str().unwrap_or("");
+
+        if method == "rpc-auth" {
+            let token = params["params"][0].as_str().unwrap_or("");
+            return if let Some(scopes) = self.tokens.get(token) {
+                let names: Vec<&str> = scopes.iter().map(|s| s.to_str()).collect();
+                let res = rpc_response(id, method, json!(names), GroupId::default());
+                if is_ws {
+                    self.sessions
+                        .insert(uid, (token.to_owned(), Instant::now()));
+                }
+                AuthResult::Authed(res)
+            } else {
+                AuthResult::Reply(rpc_error(id, -32001, "token is invalid."))
+            };
+        }
+
+        let scopes = if let Some(token) = params["token"].as_str() {
+            self.tokens.get(token)
+        } else if is_ws {
+            if let Some((token, active)) = self.sessions.get_mut(&uid) {
+                *active = Instant::now();
+                self.tokens.get(token)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        let scopes = match scopes {
+            Some(scopes) => scopes.clone(),
+            None => return AuthResult::Reply(rpc_error(id, -32001, "unauthorized.")),
+        };
+
+        if !Scope::allow(&scopes, method) {
+            return AuthResult::Reply(rpc_error(id, -32003, "permission denied."));
+        }
+
+        match method {
+            "rpc-token-create" => {
+                // params: [scopes].
+                let mut scopes = vec![];
+                if let Some(items) = params["params"][0].as_array() {
+                    for item in items {
+                        match item.as_str().and_then(Scope::from_str) {
+                            Some(Scope::Admin) | None => {}
+                            Some(scope) => scopes.push(scope),
+                        }
+                    }
+                }
+                if scopes.len() == 0 {
+                    return AuthResult::Reply(rpc_error(id, -32602, "scopes is invalid."));
+                }
+
+                let token = new_token();
+                let names: Vec<&str> = scopes.iter().map(|s| s.to_str()).collect();
+                let res = json!([token, names]);
+                self.tokens.insert(token, scopes);
+                if self.save().is_err() {
+                    return AuthResult::Reply(rpc_error(id, -32603, "tokens save failure."));
+                }
+                AuthResult::Reply(rpc_response(id, method, res, GroupId::default()))
+            }
+            "rpc-token-list" => {
+                let mut res = vec![];
+                for (token, scopes) in &self.tokens {
+                    if !scopes.contains(&Scope::Admin) {
+                        let names: Vec<&str> = scopes.iter().map(|s| s.to_str()).collect();
+                        res.push(json!([token, names]));
+                    }
+                }
+                AuthResult::Reply(rpc_response(id, method, json!(res), GroupId::default()))
+            }
+            "rpc-token-delete" => {
+                let token = params["params"][0].as_str().unwrap_or("");
+                if let Some(scopes) = self.tokens.get(token) {
+                    if !scopes.contains(&Scope::Admin) {
+                        self.tokens.remove(token);
+                        let _ = self.save();
+                    }
+                }
+                AuthResult::Reply(rpc_response(id, method, json!([token]), GroupId::default()))
+            }
+            _ => AuthResult::Pass,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_scopes() {
+        assert!(Scope::required("rpc-ping") == Scope::Read);
+        assert!(Scope::required("rpc-subscribe") == Scope::Read);
+        assert!(Scope::required("rpc-unsubscribe") == Scope::Read);
+        assert!(Scope::required("chat-friend-list") == Scope::Read);
+        assert!(Scope::required("wallet-token") == Scope::Read);
+        assert!(Scope::required("wallet-transfer") == Scope::Wallet);
+        assert!(Scope::required("chat-message-create") == Scope::Write);
+        assert!(Scope::required("account-delete") == Scope::Admin);
+        assert!(Scope::required("rpc-token-create") == Scope::Admin);
+
+        assert!(Scope::allow(&[Scope::Read], "rpc-ping"));
+        assert!(!Scope::allow(&[Scope::Read], "wallet-transfer"));
+        assert!(Scope::allow(&[Scope::Admin], "wallet-transfer"));
+    }
+}
@@ -0,0 +1,368 @@
+use once_cell::sync::Lazy;
+use std::sync::RwLock as StdRwLock;
+use tdn::types::rpc::{json, RpcParam};
+
+use ParamType::*;
+
+/// Type of the positional param.
+#[derive(Clone, Copy)]
+pub(crate) enum ParamType {
+    Int,
+    Bool,
+    Str,
+    /// hex string of GroupId/PeerId/Proof.
+    Hex,
+    IntList,
+    StrList,
+    /// not checked.
+    Any,
+}
+
+impl ParamType {
+    fn to_str(&self) -> &'static str {
+        match self {
+            ParamType::Int => "integer",
+            ParamType::Bool => "boolean",
+            ParamType::Str => "string",
+            ParamType::Hex => "hex string",
+            ParamType::IntList => "list of integer",
+            ParamType::StrList => "list of string",
+            ParamType::Any => "any",
+        }
+    }
+
+    fn check(&self, value: &RpcParam) -> bool {
+        match self {
+            ParamType::Int => value.is_i64(),
+            ParamType::Bool => value.is_boolean(),
+            ParamType::Str => value.is_string(),
+            ParamType::Hex => value
+                .as_str()
+                .map(|s| s.len() % 2 == 0 && s.chars().all(|c| c.is_ascii_hexdigit()))
+                .unwrap_or(false),
+            ParamType::IntList => value
+                .as_array()
+                .map(|v| v.iter().all(|i| i.is_i64()))
+                .unwrap_or(false),
+            ParamType::StrList => value
+                .as_array()
+                .map(|v| v.iter().all(|i| i.is_string()))
+                .unwrap_or(false),
+            ParamType::Any => true,
+        }
+    }
+
+    /// JSON Schema of the type.
+    fn schema(&self) -> RpcParam {
+        match self {
+            ParamType::Int => json!({"type": "integer"}),
+            ParamType::Bool => json!({"type": "boolean"}),
+            ParamType::Str => json!({"type": "string"}),
+            ParamType::Hex => json!({"type": "string", "pattern": "^([0-9a-fA-F]{2})*$"}),
+            ParamType::IntList => json!({"type": "array", "items": {"type": "integer"}}),
+            ParamType::StrList => json!({"type": "array", "items": {"type": "string"}}),
+            ParamType::Any => json!({}),
+        }
+    }
+}
+
+/// Positional param of the method.
+pub(crate) struct Param {
+    pub name: &'static str,
+    pub ty: ParamType,
+    pub required: bool,
+}
+
+/// Required param.
+pub(crate) const fn req(name: &'static str, ty: ParamType) -> Param {
+    Param {
+        name,
+        ty,
+        required: true,
+    }
+}
+
+/// Optional param, it can be missing or null.
+pub(crate) const fn opt(name: &'static str, ty: ParamType) -> Param {
+    Param {
+        name,
+        ty,
+        required: false,
+    }
+}
+
+/// the method has no response, the results are pushed.
+pub(crate) const NONE_RESULT: &'static str = "none";
+
+/// Schema of the RPC method, registered with the method by `rpc_method!`.
+pub(crate) struct MethodSchema {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub params: &'static [Param],
+    /// layout of the result, e.g. `[gid]`, `[Friend]` is list of `Friend::to_rpc()`.
+    pub result: &'static str,
+}
+
+pub(crate) const fn method(
+    name: &'static str,
+    summary: &'static str,
+    params: &'static [Param],
+    result: &'static str,
+) -> MethodSchema {
+    MethodSchema {
+        name,
+        summary,
+        params,
+        result,
+    }
+}
+
+/// the methods' schema which registered in RpcHandler.
+static SCHEMAS: Lazy<StdRwLock<Vec<&'static MethodSchema>>> = Lazy::new(|| StdRwLock::new(vec![]));
+
+/// add the method to RpcHandler with its schema,
+/// the params are validated by the schema before handle.
+macro_rules! rpc_method {
+    ($handler:expr, $schema:expr, $f:expr $(,)?) => {{
+        const SCHEMA: $crate::rpc::schema::MethodSchema = $schema;
+        $crate::rpc::schema::register(&SCHEMA);
+        $handler.add_method(SCHEMA.name, $f);
+    }};
+}
+pub(crate) use rpc_method;
+
+pub(crate) fn register(schema: &'static MethodSchema) {
+    if let Ok(mut schemas) = SCHEMAS.write() {
+        if !schemas.iter().any(|s| s.name == schema.name) {
+            schemas.push(schema);
+        }
+    }
+}
+
+/// the method's schema, include the methods handled in server.
+pub(crate) fn find(name: &str) -> Option<&'static MethodSchema> {
+    if let Some(schema) = SERVER_SCHEMAS.iter().find(|s| s.name == name) {
+        return Some(schema);
+    }
+    SCHEMAS
+        .read()
+        .ok()?
+        .iter()
+        .find(|s| s.name == name)
+        .copied()
+}
+
+/// all methods' schema, include the methods handled in server.
+pub(crate) fn all() -> Vec<&'static MethodSchema> {
+    let mut schemas: Vec<&'static MethodSchema> = SERVER_SCHEMAS.iter().collect();
+    if let Ok(registered) = SCHEMAS.read() {
+        schemas.extend(registered.iter());
+    }
+    schemas
+}
+
+/// the methods which handled in server, not in RpcHandler.
+const SERVER_SCHEMAS: &[MethodSchema] = &[
+    method(
+        "rpc-auth",
+        "handshake with the token.",
+        &[req("token", Str)],
+        "[scopes]",
+    ),
+    method(
+        "rpc-token-create",
+        "create a token with scopes (read, write, wallet).",
+        &[req("scopes", StrList)],
+        "[token, [scopes]]",
+    ),
+    method(
+        "rpc-token-list",
+        "list all tokens.",
+        &[],
+        "[[token, [scopes]]]",
+    ),
+    method(
+        "rpc-token-delete",
+        "delete the token.",
+        &[req("token", Str)],
+        "[token]",
+    ),
+    method(
+        "rpc-subscribe",
+        "subscribe the notifications, empty filter is all.",
+        &[opt("gids", StrList), opt("topics", StrList)],
+        "[gids, topics]",
+    ),
+    method(
+        "rpc-unsubscribe",
+        "unsubscribe all notifications.",
+        &[],
+        "[]",
+    ),
+    method(
+        "rpc-ping",
+        "keep the WebSocket client alive, idle client is removed after 120s.",
+        &[],
+        "[]",
+    ),
+    method(
+        "network-stable",
+        "list the stable connections (HTTP only).",
+        &[],
+        "[[peer, is_direct]]",
+    ),
+    method(
+        "network-dht",
+        "list the DHT peers (HTTP only).",
+        &[],
+        "[[peer, is_direct]]",
+    ),
+];
+
+impl MethodSchema {
+    /// check the positional params, return the precise error message.
+    /// extra params are ignored.
+    pub fn validate(&self, params: &[RpcParam]) -> std::result::Result<(), String> {
+        for (i, param) in self.params.iter().enumerate() {
+            match params.get(i) {
+                None | Some(RpcParam::Null) => {
+                    if param.required {
+                        return Err(format!(
+                            "{}: params[{}] `{}` is missing, expect {}.",
+                            self.name,
+                            i,
+                            param.name,
+                            param.ty.to_str()
+                        ));
+                    }
+                }
+                Some(value) => {
+                    if !param.ty.check(value) {
+                        return Err(format!(
+                            "{}: params[{}] `{}` expect {}, got {}.",
+                            self.name,
+                            i,
+                            param.name,
+                            param.ty.to_str(),
+                            value
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// OpenRPC method object.
+    fn to_rpc(&self) -> RpcParam {
+        let params: Vec<RpcParam> = self
+            .params
+            .iter()
+            .map(|p| json!({"name": p.name, "required": p.required, "schema": p.ty.schema()}))
+            .collect();
+        // "none" is no response, the results are pushed.
+        let result_schema = if self.result == NONE_RESULT {
+            json!({"type": "null"})
+        } else {
+            json!({"type": "array"})
+        };
+        json!({
+            "name": self.name,
+            "summary": self.summary,
+            "paramStructure": "by-position",
+            "params": params,
+            "result": {
+                "name": "result",
+                "description": self.result,
+                "schema": result_schema,
+            },
+        })
+    }
+}
+
+/// OpenRPC document of all methods.
+pub(crate) fn discover(schemas: &[&MethodSchema]) -> RpcParam {
+    let methods: Vec<RpcParam> = schemas.iter().map(|s| s.to_rpc()).collect();
+    json!({
+        "openrpc": "1.2.6",
+        "info": {
+            "title": "ESSE RPC",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn sources(dir: &Path, files: &mut Vec<(String, String)>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            // dao app is not compiled.
+            if path.ends_with("dao") {
+                continue;
+            }
+            if path.is_dir() {
+                sources(&path, files);
+            } else if path.extension().map(|e| e == "rs").unwrap_or(false) {
+                let content = std::fs::read_to_string(&path).unwrap();
+                files.push((path.display().to_string(), content));
+            }
+        }
+    }
+
+    #[test]
+    fn every_method_has_schema() {
+        let mut files = vec![];
+        sources(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut files,
+        );
+
+        let mut names: Vec<String> = SERVER_SCHEMAS.iter().map(|s| s.name.to_owned()).collect();
+        for (path, content) in files {
+            // the macro is defined in it.
+            if path.ends_with("schema.rs") {
+                continue;
+            }
+            // only rpc_method! can add the method, it registers the schema.
+            assert!(
+                !content.contains(".add_method("),
+                "{} add method without schema.",
+                path
+            );
+
+            for (i, _) in content.match_indices("rpc_method!(") {
+                let rest = &content[i..];
+                let start = rest.find("method(").unwrap() + "method(".len();
+                let rest = rest[start..].trim_start().trim_start_matches('"');
+                let name = rest[..rest.find('"').unwrap()].to_owned();
+                assert!(!names.contains(&name), "{} schema is duplicated.", name);
+                names.push(name);
+            }
+        }
+        assert!(names.len() > SERVER_SCHEMAS.len());
+    }
+
+    #[test]
+    fn validate_params() {
+        const SCHEMA: MethodSchema = method(
+            "test",
+            "",
+            &[req("id", Int), opt("name", Str), opt("addr", Hex)],
+            NONE_RESULT,
+        );
+        assert!(SCHEMA.validate(&[json!(1)]).is_ok());
+        assert!(SCHEMA
+            .validate(&[json!(1), RpcParam::Null, json!("0a")])
+            .is_ok());
+        assert!(SCHEMA.validate(&[]).is_err());
+        assert!(SCHEMA.validate(&[json!("1")]).is_err());
+        assert!(SCHEMA
+            .validate(&[json!(1), json!("a"), json!("0g")])
+            .is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock as StdRwLock;
+use tdn::types::rpc::{json, RpcParam};
+use tokio::sync::mpsc::Sender;
+
+use crate::server::register_subscriber;
+
+/// default socket file in data directory.
+pub(crate) const DEFAULT_UNIX_FILE: &'static str = "esse.sock";
+
+/// unix clients' uid, use the high range, not conflict with TDN's uid.
+const UNIX_UID_START: u64 = 1 << 63;
+static UNIX_UID: AtomicU64 = AtomicU64::new(UNIX_UID_START);
+
+/// connected unix clients, uid => response sender.
+static UNIX_CLIENTS: Lazy<StdRwLock<HashMap<u64, Sender<RpcParam>>>> =
+    Lazy::new(|| StdRwLock::new(HashMap::new()));
+
+/// the unix socket path of `rpc_unix` in config.toml,
+/// not configured or empty string will use the default socket file in data directory.
+pub(crate) fn unix_config(db_path: &PathBuf, value: &Option<String>) -> PathBuf {
+    match value.as_ref().map(|v| v.trim()) {
+        Some(v) if !v.is_empty() => PathBuf::from(v),
+        _ => db_path.join(DEFAULT_UNIX_FILE),
+    }
+}
+
+/// check the uid is unix client (maybe closed).
+pub(crate) fn is_client(uid: u64) -> bool {
+    uid >= UNIX_UID_START
+}
+
+/// send the response/notification to the unix client.
+pub(crate) async fn send(uid: u64, param: RpcParam) {
+    let sender = UNIX_CLIENTS
+        .read()
+        .ok()
+        .and_then(|clients| clients.get(&uid).cloned());
+    if let Some(sender) = sender {
+        let _ = sender.send(param).await;
+    }
+}
+
+/// listen the unix socket, every line is a JSON-RPC request/response.
+/// the requests are sent to main loop, same as WebSocket RPC.
+#[cfg(unix)]
+pub(crate) async fn listen(
+    path: PathBuf,
+    requests: Sender<(u64, RpcParam)>,
+) -> tdn::types::primitive::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+
+    use std::os::unix::fs::DirBuilderExt;
+
+    // remove the stale socket file.
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    // bind in a private (0700) directory and fix the mode, then move it to the path,
+    // so others can not connect it before the mode changed.
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(DEFAULT_UNIX_FILE);
+    let dir = path.with_file_name(format!(".{}.bind", name));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let tmp = dir.join(name);
+    let listener = UnixListener::bind(&tmp)?;
+    // only the user which running daemon can connect.
+    std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+    std::fs::rename(&tmp, &path)?;
+    std::fs::remove_dir(&dir)?;
+    info!("Config RPC UNIX : {:?}", path);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let uid = UNIX_UID.fetch_add(1, Ordering::Relaxed);
+        let (sender, mut recver) = tokio::sync::mpsc::channel::<RpcParam>(128);
+        if let Ok(mut clients) = UNIX_CLIENTS.write() {
+            clients.insert(uid, sender);
+        }
+        register_subscriber(uid);
+
+        let requests = requests.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            loop {
+                tokio::select! {
+                    line = lines.next_line() => match line {
+                        Ok(Some(line)) => {
+                            if line.trim().is_empty() {
+                                continue;
+                            }
+                            match serde_json::from_str::<RpcParam>(&line) {
+                                Ok(params) => {
+                                    if requests.send((uid, params)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                Err(_) => {
+                                    let err = json!({"jsonrpc": "2.0", "id": 0, "error": {"code": -32700, "message": "parse error."}});
+                                    if writer.write_all(format!("{}\n", err).as_bytes()).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                        _ => break,
+                    },
+                    Some(param) = recver.recv() => {
+                        if writer.write_all(format!("{}\n", param).as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if let Ok(mut clients) = UNIX_CLIENTS.write() {
+                clients.remove(&uid);
+            }
+            // clear the subscriber of this client.
+            let _ = requests
+                .send((uid, json!({"method": "rpc-unsubscribe", "params": []})))
+                .await;
+        });
+    }
+}
@@ -1,30 +1,225 @@
-use once_cell::sync::OnceCell;
+use once_cell::sync::Lazy;
 use simplelog::{CombinedLogger, Config as LogConfig, LevelFilter};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::{Duration, Instant};
 use tdn::{
     prelude::*,
-    types::primitive::{HandleResult, Result},
+    types::{
+        message::RecvType,
+        primitive::{HandleResult, Result},
+        rpc::{json, rpc_response, RpcParam},
+    },
 };
 use tokio::{
     sync::mpsc::{error::SendError, Sender},
-    sync::RwLock,
+    sync::{Notify, RwLock},
 };
 
-use crate::account::Account;
+use crate::account::{Account, AccountMeta};
+use crate::actor::{account_key, session_key, Actors};
 use crate::apps::app_layer_handle;
-use crate::group::Group;
+use crate::autologin;
+use crate::bootstrap;
+use crate::config::EsseConfig;
+use crate::discovery;
+use crate::group::{check_locked, group_handle, Group};
 use crate::layer::Layer;
-use crate::migrate::main_migrate;
-use crate::primitives::network_seeds;
-use crate::rpc::{init_rpc, inner_rpc};
-use crate::storage::account_db;
+use crate::migrate::{account_migrate, main_migrate};
+use crate::presence;
+use crate::reconnect;
+use crate::relay;
+use crate::rpc::auth::{rpc_error, AuthResult, RpcAuth, RPC_TOKEN_FILE};
+use crate::rpc::unix::{self as unix_rpc, unix_config};
+use crate::rpc::{close_stable, init_rpc, inner_rpc, login, logout_all, session_rpc, validate_rpc};
+use crate::storage::{account_db, flush_writes};
 
 pub const DEFAULT_WS_ADDR: &'static str = "127.0.0.1:8080";
 pub const DEFAULT_LOG_FILE: &'static str = "esse.log.txt";
 
-pub static RPC_WS_UID: OnceCell<u64> = OnceCell::new();
+/// TDN not notify when WebSocket client closed, the client which idle longer
+/// than it will be removed, client keep alive by any request or `rpc-ping`.
+const RPC_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// all connected WebSocket RPC clients, and their filter.
+static RPC_SUBSCRIBERS: Lazy<StdRwLock<HashMap<u64, Subscriber>>> =
+    Lazy::new(|| StdRwLock::new(HashMap::new()));
+
+/// notified when the daemon need stop, by signals or `stop` FFI.
+static SHUTDOWN: Lazy<Notify> = Lazy::new(|| Notify::new());
+
+/// WebSocket RPC client's subscribe filter.
+struct Subscriber {
+    /// accounts filter, empty is all accounts.
+    gids: Vec<GroupId>,
+    /// topics filter (method's prefix, e.g. "chat-"), empty is all topics.
+    topics: Vec<String>,
+    /// the last request time of the client.
+    active: Instant,
+}
+
+impl Default for Subscriber {
+    fn default() -> Self {
+        Subscriber {
+            gids: vec![],
+            topics: vec![],
+            active: Instant::now(),
+        }
+    }
+}
+
+impl Subscriber {
+    fn is_match(&self, param: &RpcParam) -> bool {
+        if self.gids.len() > 0 {
+            // the event not belong to any account, always push.
+            if let Some(Ok(gid)) = param["gid"].as_str().map(|g| GroupId::from_hex(g)) {
+                if gid != GroupId::default() && !self.gids.contains(&gid) {
+                    return false;
+                }
+            }
+        }
+
+        if self.topics.len() > 0 {
+            let method = param["method"].as_str().unwrap_or("");
+            return self.topics.iter().any(|t| method.starts_with(t.as_str()));
+        }
+
+        true
+    }
+}
+
+/// push the notification to all matched WebSocket RPC clients.
+pub(crate) async fn push_rpc(
+    sender: &Sender<SendMessage>,
+    param: RpcParam,
+) -> std::result::Result<(), SendError<SendMessage>> {
+    push_rpc_except(sender, param, 0).await
+}
+
+async fn push_rpc_except(
+    sender: &Sender<SendMessage>,
+    param: RpcParam,
+    except: u64,
+) -> std::result::Result<(), SendError<SendMessage>> {
+    let uids: Vec<u64> = if let Ok(subscribers) = RPC_SUBSCRIBERS.read() {
+        subscribers
+            .iter()
+            .filter(|(uid, s)| **uid != except && s.is_match(&param))
+            .map(|(uid, _)| *uid)
+            .collect()
+    } else {
+        vec![]
+    };
+
+    for uid in uids {
+        send_rpc(sender, uid, param.clone(), true).await?;
+    }
+    Ok(())
+}
+
+/// the peer connected successfully, cache it to try first when restart,
+/// and not reconnect it after network lost.
+fn peer_connected(msg: &RecvType) {
+    match msg {
+        RecvType::Connect(peer, _)
+        | RecvType::ResultConnect(peer, _)
+        | RecvType::Result(peer, true, _) => {
+            bootstrap::connected(peer);
+            reconnect::connected(&peer.id);
+        }
+        _ => {}
+    }
+}
+
+/// send the rpc to the client, WebSocket/HTTP by TDN, or the unix socket.
+async fn send_rpc(
+    sender: &Sender<SendMessage>,
+    uid: u64,
+    param: RpcParam,
+    is_ws: bool,
+) -> std::result::Result<(), SendError<SendMessage>> {
+    if unix_rpc::is_client(uid) {
+        unix_rpc::send(uid, param).await;
+        Ok(())
+    } else {
+        sender.send(SendMessage::Rpc(uid, param, is_ws)).await
+    }
+}
+
+/// register the WebSocket client, and handle the subscribe methods.
+/// return true if the method is handled.
+async fn subscribe_rpc(uid: u64, params: &RpcParam, sender: &Sender<SendMessage>) -> bool {
+    let method = params["method"].as_str().unwrap_or("");
+    if let Some(res) = subscribe(uid, method, params) {
+        let id = params["id"].as_u64().unwrap_or(0);
+        let res = rpc_response(id, method, res, GroupId::default());
+        let _ = send_rpc(sender, uid, res, true).await;
+        true
+    } else {
+        false
+    }
+}
+
+fn subscribe(uid: u64, method: &str, params: &RpcParam) -> Option<RpcParam> {
+    let mut subscribers = RPC_SUBSCRIBERS.write().ok()?;
+    match method {
+        "rpc-subscribe" => {
+            // params: [gids], [topics].
+            let mut subscriber = Subscriber::default();
+            if let Some(gids) = params["params"][0].as_array() {
+                for gid in gids {
+                    if let Some(Ok(gid)) = gid.as_str().map(|g| GroupId::from_hex(g)) {
+                        subscriber.gids.push(gid);
+                    }
+                }
+            }
+            if let Some(topics) = params["params"][1].as_array() {
+                for topic in topics {
+                    if let Some(topic) = topic.as_str() {
+                        subscriber.topics.push(topic.to_owned());
+                    }
+                }
+            }
+            let gids: Vec<String> = subscriber.gids.iter().map(|g| g.to_hex()).collect();
+            let res = json!([gids, subscriber.topics]);
+            subscribers.insert(uid, subscriber);
+            Some(res)
+        }
+        "rpc-unsubscribe" => {
+            subscribers.remove(&uid);
+            Some(json!([]))
+        }
+        "rpc-ping" => {
+            if let Some(subscriber) = subscribers.get_mut(&uid) {
+                subscriber.active = Instant::now();
+            }
+            Some(json!([]))
+        }
+        _ => {
+            // only refresh, the unsubscribed client not subscribe again.
+            if let Some(subscriber) = subscribers.get_mut(&uid) {
+                subscriber.active = Instant::now();
+            }
+            None
+        }
+    }
+}
+
+/// new client (handshaked WebSocket or unix socket) default subscribe all.
+pub(crate) fn register_subscriber(uid: u64) {
+    if let Ok(mut subscribers) = RPC_SUBSCRIBERS.write() {
+        subscribers.entry(uid).or_default().active = Instant::now();
+    }
+}
+
+/// remove the WebSocket clients which closed (idle too long).
+fn expire_subscribers() {
+    if let Ok(mut subscribers) = RPC_SUBSCRIBERS.write() {
+        subscribers
+            .retain(|uid, s| unix_rpc::is_client(*uid) || s.active.elapsed() < RPC_IDLE_TIMEOUT);
+    }
+}
 
 pub async fn start(db_path: String) -> Result<()> {
     let db_path = PathBuf::from(db_path);
@@ -36,11 +231,27 @@ pub async fn start(db_path: String) -> Result<()> {
     main_migrate(&db_path)?;
     info!("Core storage path {:?}", db_path);
 
+    let mut auth = RpcAuth::init(db_path.clone()).await?;
+    info!("RPC token file  : {:?}", db_path.join(RPC_TOKEN_FILE));
+
+    let esse_config = EsseConfig::load(&db_path).await;
     let mut config = Config::load_save(db_path.clone()).await;
     config.db_path = Some(db_path.clone());
-    config.p2p_allowlist.append(&mut network_seeds());
+    config
+        .p2p_allowlist
+        .append(&mut bootstrap::init(&db_path, esse_config.p2p_private));
+    presence::init(
+        &db_path,
+        esse_config.presence_heartbeat,
+        esse_config.presence_suspend,
+    );
+    // the unattended accounts, login after network started.
+    let autologins = autologin::init(&db_path);
+    // unix socket rpc is always open (only the user can connect it),
+    // when it is configured, not open the default ws.
+    let unix_path = unix_config(&db_path, &esse_config.rpc_unix);
     // use self sign to bootstrap peer.
-    if config.rpc_ws.is_none() {
+    if config.rpc_ws.is_none() && esse_config.rpc_unix.is_none() {
         // set default ws addr.
         config.rpc_ws = Some(DEFAULT_WS_ADDR.parse().unwrap());
     }
@@ -54,51 +265,160 @@ pub async fn start(db_path: String) -> Result<()> {
     );
 
     let rand_secret = config.secret.clone();
+    let p2p_peer = config.p2p_peer.clone();
 
+    // only load the accounts' metadata, the first account is loaded when start,
+    // and others are loaded & added to TDN when login.
     let account_db = account_db(&db_path)?;
-    let accounts = Account::all(&account_db)?;
-    account_db.close()?;
+    let metas = AccountMeta::all(&account_db)?;
     let mut me: HashMap<GroupId, Account> = HashMap::new();
-    for account in accounts {
-        me.insert(account.gid, account);
+    if let Some(first) = metas.first() {
+        account_migrate(&db_path, &first.gid)?;
+        me.insert(first.gid, Account::get(&account_db, &first.gid)?);
     }
+    account_db.close()?;
     config.group_ids = me.keys().cloned().collect();
 
     let (peer_id, sender, mut recver) = start_with_config(config).await.unwrap();
+    relay::init(sender.clone());
     info!("Network Peer id : {}", peer_id.to_hex());
 
     let group = Arc::new(RwLock::new(
-        Group::init(rand_secret, sender.clone(), peer_id, me, db_path.clone()).await?,
+        Group::init(
+            rand_secret,
+            sender.clone(),
+            peer_id,
+            metas,
+            me,
+            db_path.clone(),
+        )
+        .await?,
     ));
     let layer = Arc::new(RwLock::new(
-        Layer::init(db_path, peer_id, group.clone()).await?,
+        Layer::init(db_path.clone(), peer_id, group.clone()).await?,
     ));
 
-    let rpc = init_rpc(peer_id, group.clone(), layer.clone());
-    //let mut group_rpcs: HashMap<u64, GroupId> = HashMap::new();
-    let mut now_rpc_uid = 0;
+    let rpc = Arc::new(init_rpc(peer_id, group.clone(), layer.clone()));
+
+    // login the unattended accounts, e.g. running on headless server.
+    for (gid, lock) in autologins {
+        let mut results = HandleResult::new();
+        let info = "auto-login, unattended".to_owned();
+        match login(&group, &layer, gid, &lock, info, &mut results).await {
+            Ok(()) => {
+                info!("Auto-login account: {}.", gid.to_hex());
+                handle(results, 0, true, &sender).await;
+            }
+            Err(e) => error!("Auto-login account {} failure: {}", gid.to_hex(), e),
+        }
+    }
+
+    // announce & find my devices and friends in local network.
+    discovery::start(
+        &esse_config,
+        peer_id,
+        &p2p_peer,
+        group.clone(),
+        layer.clone(),
+        sender.clone(),
+    );
 
     // running session remain task.
     tokio::spawn(session_remain(peer_id, layer.clone(), sender.clone()));
 
-    while let Some(message) = recver.recv().await {
+    // running account auto-lock task.
+    tokio::spawn(account_autolock(group.clone(), sender.clone()));
+
+    // the messages are handled by actors, concurrently between accounts and sessions.
+    let mut actors = Actors::init();
+
+    // running unix socket rpc, requests are handled in main loop.
+    let (unix_sender, mut unix_recver) = tokio::sync::mpsc::channel(128);
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        if let Err(e) = unix_rpc::listen(unix_path, unix_sender).await {
+            error!("RPC UNIX: {:?}", e);
+        }
+    });
+    #[cfg(not(unix))]
+    drop((unix_path, unix_sender));
+
+    // check the closed WebSocket clients.
+    let mut idle_check = tokio::time::interval(RPC_IDLE_TIMEOUT / 4);
+
+    loop {
+        let message = tokio::select! {
+            message = recver.recv() => match message {
+                Some(message) => message,
+                None => break,
+            },
+            Some((uid, params)) = unix_recver.recv() => ReceiveMessage::Rpc(uid, params, true),
+            _ = idle_check.tick() => {
+                expire_subscribers();
+                auth.expire(RPC_IDLE_TIMEOUT);
+                continue;
+            }
+            _ = SHUTDOWN.notified() => break,
+        };
+
         match message {
             ReceiveMessage::Group(fgid, g_msg) => {
-                if let Ok(handle_result) = group
-                    .write()
-                    .await
-                    .handle(fgid, g_msg, &layer, now_rpc_uid)
-                    .await
-                {
-                    handle(handle_result, now_rpc_uid, true, &sender).await;
-                }
+                peer_connected(&g_msg);
+                let (group, layer, sender) = (group.clone(), layer.clone(), sender.clone());
+                // relayed friend's events keep the order with the direct events.
+                let key = match relay::session_of(&g_msg) {
+                    Some(rgid) => session_key(fgid, rgid),
+                    None => account_key(fgid),
+                };
+                actors.send(key, async move {
+                    if let RecvType::Leave(addr) = &g_msg {
+                        let handle_result = relay::device_leave(&layer, addr).await;
+                        handle(handle_result, 0, true, &sender).await;
+                    }
+
+                    if let Ok(handle_result) = group_handle(&group, &layer, fgid, g_msg, 0).await {
+                        handle(handle_result, 0, true, &sender).await;
+                    }
+                });
             }
             ReceiveMessage::Layer(fgid, tgid, l_msg) => {
-                if let Ok(handle_result) = app_layer_handle(&layer, fgid, tgid, l_msg).await {
-                    handle(handle_result, now_rpc_uid, true, &sender).await;
-                }
+                peer_connected(&l_msg);
+                let (group, layer, sender) = (group.clone(), layer.clone(), sender.clone());
+                actors.send(session_key(tgid, fgid), async move {
+                    // restricted mode when account is locked.
+                    let l_msg = match check_locked(&group, &tgid, fgid, l_msg).await {
+                        Some(l_msg) => l_msg,
+                        None => return,
+                    };
+                    // forward to my devices which relayed by me.
+                    for msg in relay::forward(&tgid, &fgid, &l_msg) {
+                        sender.send(msg).await.expect("TDN channel closed");
+                    }
+                    if let Ok(handle_result) = app_layer_handle(&layer, fgid, tgid, l_msg).await {
+                        handle(handle_result, 0, true, &sender).await;
+                    }
+                });
             }
             ReceiveMessage::Rpc(uid, params, is_ws) => {
+                // local rpc authentication & permission check.
+                // unix socket is protected by the file permission.
+                if !unix_rpc::is_client(uid) {
+                    match auth.handle(uid, is_ws, &params) {
+                        AuthResult::Pass => {}
+                        AuthResult::Reply(res) => {
+                            let _ = sender.send(SendMessage::Rpc(uid, res, is_ws)).await;
+                            continue;
+                        }
+                        AuthResult::Authed(res) => {
+                            if is_ws {
+                                register_subscriber(uid);
+                            }
+                            let _ = sender.send(SendMessage::Rpc(uid, res, is_ws)).await;
+                            continue;
+                        }
+                    }
+                }
+
                 if !is_ws {
                     if inner_rpc(uid, params["method"].as_str().unwrap(), &sender)
                         .await
@@ -108,57 +428,101 @@ pub async fn start(db_path: String) -> Result<()> {
                     }
                 }
 
-                if now_rpc_uid != uid && is_ws {
-                    let _ = RPC_WS_UID.set(uid);
-                    now_rpc_uid = uid
+                if is_ws && subscribe_rpc(uid, &params, &sender).await {
+                    continue;
                 }
 
-                if let Ok(handle_result) = rpc.handle(params).await {
-                    handle(handle_result, uid, is_ws, &sender).await;
+                // check the params by method's schema.
+                if let Some(res) = validate_rpc(&params) {
+                    let _ = send_rpc(&sender, uid, res, is_ws).await;
+                    continue;
                 }
+
+                // rpc from UI, account is active.
+                let gid = params["gid"].as_str().map(|g| GroupId::from_hex(g));
+                let gid = gid.and_then(|g| g.ok()).unwrap_or(GroupId::default());
+                // session's rpc keeps the order with the session's events.
+                let key = match session_rpc(&params) {
+                    Some(fgid) => session_key(gid, fgid),
+                    None => account_key(gid),
+                };
+                let (group, rpc, sender) = (group.clone(), rpc.clone(), sender.clone());
+                actors.send(key, async move {
+                    // restricted mode, only unlock & account methods when locked.
+                    let method = params["method"].as_str().unwrap_or("");
+                    if gid != GroupId::default() && !group.read().await.active(&gid, method) {
+                        let id = params["id"].as_u64().unwrap_or(0);
+                        let res = rpc_error(id, -32004, "account is locked.");
+                        let _ = send_rpc(&sender, uid, res, is_ws).await;
+                        return;
+                    }
+
+                    if let Ok(handle_result) = rpc.handle(params).await {
+                        handle(handle_result, uid, is_ws, &sender).await;
+                    }
+                });
             }
             ReceiveMessage::NetworkLost => {
                 sender
                     .send(SendMessage::Network(NetworkType::NetworkReboot))
                     .await
                     .expect("TDN channel closed");
-                let t_sender = sender.clone();
-                let g_conns = group.read().await.all_distribute_conns();
-                let l_conns = layer
-                    .read()
-                    .await
-                    .all_layer_conns()
-                    .await
-                    .unwrap_or(HashMap::new());
-                tokio::spawn(sleep_waiting_reboot(t_sender, g_conns, l_conns));
+                reconnect::start(group.clone(), layer.clone(), sender.clone()).await;
             }
         }
     }
 
+    shutdown(actors, &group, &layer, &sender).await;
     Ok(())
 }
 
-#[inline]
-async fn sleep_waiting_reboot(
-    sender: Sender<SendMessage>,
-    groups: HashMap<GroupId, Vec<SendType>>,
-    layers: HashMap<GroupId, Vec<(GroupId, SendType)>>,
-) -> std::result::Result<(), SendError<SendMessage>> {
-    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+/// stop the running daemon gracefully.
+pub fn stop() {
+    SHUTDOWN.notify_one();
+}
 
-    for (gid, conns) in groups {
-        for conn in conns {
-            sender.send(SendMessage::Group(gid, conn)).await?;
+/// waiting the handling messages, logout all accounts, send offline events,
+/// and flush the pending file writes.
+/// the databases are opened per request, so all closed when tasks finished.
+async fn shutdown(
+    actors: Actors,
+    group: &Arc<RwLock<Group>>,
+    layer: &Arc<RwLock<Layer>>,
+    sender: &Sender<SendMessage>,
+) {
+    info!("Shutdown...");
+    reconnect::cancel();
+    if !actors.stop(Duration::from_secs(5)).await {
+        error!("Shutdown handling messages timeout.");
+    }
+    let mut results = HandleResult::new();
+    match logout_all(group, layer, "shutdown".to_owned(), &mut results).await {
+        Ok((groups, layers)) => {
+            handle(results, 0, true, sender).await;
+            // waiting the offline events sent, then close the connections.
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let _ = close_stable(sender, groups, layers).await;
         }
+        Err(e) => error!("Shutdown logout failure: {}", e),
     }
 
-    for (fgid, conns) in layers {
-        for (tgid, conn) in conns {
-            sender.send(SendMessage::Layer(fgid, tgid, conn)).await?;
-        }
+    if !flush_writes(Duration::from_secs(10)).await {
+        error!("Shutdown file writes timeout.");
     }
+    // waiting TDN send the disconnect messages.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    info!("Shutdown completed.");
+}
 
-    Ok(())
+async fn account_autolock(group: Arc<RwLock<Group>>, sender: Sender<SendMessage>) {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        let locked = group.write().await.check_autolock();
+        for gid in locked {
+            debug!("Account Locked: {}.", gid.to_hex());
+            let _ = push_rpc(&sender, crate::rpc::account_lock(gid)).await;
+        }
+    }
 }
 
 async fn session_remain(
@@ -167,16 +531,19 @@ async fn session_remain(
     sender: Sender<SendMessage>,
 ) -> Result<()> {
     loop {
-        tokio::time::sleep(std::time::Duration::from_secs(120)).await;
-        if let Some(uid) = RPC_WS_UID.get() {
+        tokio::time::sleep(Duration::from_secs(presence::heartbeat())).await;
+        {
             let mut layer_lock = layer.write().await;
             let mut rpcs = vec![];
             let mut addrs = HashMap::new();
 
             for (_, running) in layer_lock.runnings.iter_mut() {
                 let closed = running.close_suspend(&self_addr);
-                for (gid, addr, sid) in closed {
-                    addrs.insert(addr, false);
+                for (gid, addr, sid, is_direct) in closed {
+                    // relayed session's addr is my device, not disconnect it.
+                    if is_direct {
+                        addrs.insert(addr, false);
+                    }
                     rpcs.push(crate::rpc::session_lost(gid, &sid));
                 }
             }
@@ -193,7 +560,7 @@ async fn session_remain(
             drop(layer_lock);
 
             for rpc in rpcs {
-                let _ = sender.send(SendMessage::Rpc(*uid, rpc, true)).await;
+                let _ = push_rpc(&sender, rpc).await;
             }
 
             for (addr, keep) in addrs {
@@ -211,6 +578,7 @@ async fn session_remain(
     }
 }
 
+/// when uid is 0, the result is pushed to all subscribers.
 #[inline]
 async fn handle(handle_result: HandleResult, uid: u64, is_ws: bool, sender: &Sender<SendMessage>) {
     let HandleResult {
@@ -223,8 +591,18 @@ async fn handle(handle_result: HandleResult, uid: u64, is_ws: bool, sender: &Sen
     loop {
         if rpcs.len() != 0 {
             let msg = rpcs.remove(0);
-            sender
-                .send(SendMessage::Rpc(uid, msg, is_ws))
+            if uid == 0 && is_ws {
+                push_rpc(sender, msg).await.expect("TDN channel closed");
+                continue;
+            }
+
+            // notification also push to other subscribers.
+            if is_ws && msg["id"].as_u64() == Some(0) {
+                push_rpc_except(sender, msg.clone(), uid)
+                    .await
+                    .expect("TDN channel closed");
+            }
+            send_rpc(sender, uid, msg, is_ws)
                 .await
                 .expect("TDN channel closed");
         } else {
@@ -260,7 +638,7 @@ async fn handle(handle_result: HandleResult, uid: u64, is_ws: bool, sender: &Sen
         if layers.len() != 0 {
             let (fgid, tgid, msg) = layers.remove(0);
             sender
-                .send(SendMessage::Layer(fgid, tgid, msg))
+                .send(relay::wrap(fgid, tgid, msg))
                 .await
                 .expect("TDN channel closed");
         } else {
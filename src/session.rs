@@ -1,4 +1,6 @@
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tdn::types::{
     group::GroupId,
     primitive::{PeerId, Result},
@@ -36,18 +38,63 @@ impl SessionType {
     }
 }
 
+/// Session's notification rule.
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
+pub(crate) enum SessionNotify {
+    /// notify every message.
+    All,
+    /// only notify when mentioned.
+    Mention,
+    /// never notify.
+    Mute,
+}
+
+impl SessionNotify {
+    pub fn to_int(&self) -> i64 {
+        match self {
+            SessionNotify::All => 0,
+            SessionNotify::Mention => 1,
+            SessionNotify::Mute => 2,
+        }
+    }
+
+    pub fn from_int(i: i64) -> Self {
+        match i {
+            0 => SessionNotify::All,
+            1 => SessionNotify::Mention,
+            2 => SessionNotify::Mute,
+            _ => SessionNotify::All,
+        }
+    }
+
+    /// check if need notify UI when new message coming.
+    pub fn check(&self, mentioned: bool) -> bool {
+        match self {
+            SessionNotify::All => true,
+            SessionNotify::Mention => mentioned,
+            SessionNotify::Mute => false,
+        }
+    }
+}
+
 pub(crate) struct Session {
     pub id: i64,
-    fid: i64,
+    pub fid: i64,
     pub gid: GroupId,
     pub addr: PeerId,
     pub s_type: SessionType,
-    name: String,
-    is_top: bool,
-    is_close: bool,
+    pub name: String,
+    pub is_top: bool,
+    pub is_close: bool,
     pub last_datetime: i64,
     pub last_content: String,
     pub last_readed: bool,
+    pub notify: SessionNotify,
+    pub is_archived: bool,
+    /// mute notifications until this timestamp (seconds), 0 is not muted.
+    pub mute_until: i64,
+    /// unread messages number.
+    pub unread: i64,
 }
 
 impl Session {
@@ -71,9 +118,28 @@ impl Session {
             last_datetime: datetime,
             last_content: "".to_owned(),
             last_readed: true,
+            notify: SessionNotify::All,
+            is_archived: false,
+            mute_until: 0,
+            unread: 0,
         }
     }
 
+    /// check if session is muted at this time.
+    pub fn is_muted(&self) -> bool {
+        let start = SystemTime::now();
+        let now = start
+            .duration_since(UNIX_EPOCH)
+            .map(|s| s.as_secs())
+            .unwrap_or(0) as i64; // safe for all life.
+        self.mute_until > now
+    }
+
+    /// check if need notify UI when new message coming.
+    pub fn need_notify(&self, is_me: bool, mentioned: bool) -> bool {
+        !is_me && !self.is_muted() && self.notify.check(mentioned)
+    }
+
     pub fn to_rpc(&self) -> RpcParam {
         json!([
             self.id,
@@ -87,11 +153,19 @@ impl Session {
             self.last_datetime,
             self.last_content,
             self.last_readed,
+            self.notify.to_int(),
+            self.is_archived,
+            self.mute_until,
+            self.unread,
         ])
     }
 
     fn from_values(mut v: Vec<DsValue>) -> Self {
         Self {
+            unread: v.pop().unwrap().as_i64(),
+            mute_until: v.pop().unwrap().as_i64(),
+            is_archived: v.pop().unwrap().as_bool(),
+            notify: SessionNotify::from_int(v.pop().unwrap().as_i64()),
             last_readed: v.pop().unwrap().as_bool(),
             last_content: v.pop().unwrap().as_string(),
             last_datetime: v.pop().unwrap().as_i64(),
@@ -125,7 +199,7 @@ impl Session {
             );
             db.update(&sql)?;
         } else {
-            let sql = format!("INSERT INTO sessions (fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed) VALUES ({}, '{}', '{}', {}, '{}', {}, {}, {}, '{}', {})",
+            let sql = format!("INSERT INTO sessions (fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread) VALUES ({}, '{}', '{}', {}, '{}', {}, {}, {}, '{}', {}, {}, {}, {}, {})",
             self.fid,
             self.gid.to_hex(),
             self.addr.to_hex(),
@@ -136,6 +210,10 @@ impl Session {
             self.last_datetime,
             self.last_content,
             self.last_readed,
+            self.notify.to_int(),
+            self.is_archived,
+            self.mute_until,
+            self.unread,
         );
             let id = db.insert(&sql)?;
             self.id = id;
@@ -145,7 +223,7 @@ impl Session {
     }
 
     pub fn get(db: &DStorage, id: &i64) -> Result<Session> {
-        let sql = format!("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed FROM sessions WHERE id = {}", id);
+        let sql = format!("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread FROM sessions WHERE id = {}", id);
         let mut matrix = db.query(&sql)?;
         if matrix.len() > 0 {
             Ok(Session::from_values(matrix.pop().unwrap())) // safe unwrap()
@@ -155,7 +233,7 @@ impl Session {
     }
 
     pub fn list(db: &DStorage) -> Result<Vec<Session>> {
-        let matrix = db.query("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed FROM sessions ORDER BY last_datetime DESC")?;
+        let matrix = db.query("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread FROM sessions ORDER BY last_datetime DESC")?;
         let mut sessions = vec![];
         for values in matrix {
             sessions.push(Session::from_values(values));
@@ -163,10 +241,49 @@ impl Session {
         Ok(sessions)
     }
 
-    pub fn update(db: &DStorage, id: &i64, is_top: bool, is_close: bool) -> Result<usize> {
+    /// list sessions by archived status, and check if only has unread messages.
+    pub fn list_by(db: &DStorage, is_archived: bool, only_unread: bool) -> Result<Vec<Session>> {
+        let sql = format!("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread FROM sessions WHERE is_archived = {}{} ORDER BY last_datetime DESC",
+            is_archived,
+            if only_unread { " AND unread > 0" } else { "" },
+        );
+        let matrix = db.query(&sql)?;
+        let mut sessions = vec![];
+        for values in matrix {
+            sessions.push(Session::from_values(values));
+        }
+        Ok(sessions)
+    }
+
+    pub fn get_by_gid(db: &DStorage, gid: &GroupId) -> Result<Session> {
+        let sql = format!("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread FROM sessions WHERE gid = '{}'", gid.to_hex());
+        let mut matrix = db.query(&sql)?;
+        if matrix.len() > 0 {
+            Ok(Session::from_values(matrix.pop().unwrap())) // safe unwrap()
+        } else {
+            Err(anyhow!("session missing."))
+        }
+    }
+
+    pub fn update(
+        db: &DStorage,
+        id: &i64,
+        is_top: bool,
+        is_close: bool,
+        is_archived: bool,
+        mute_until: i64,
+    ) -> Result<usize> {
+        db.update(&format!(
+            "UPDATE sessions SET is_top = {}, is_close = {}, is_archived = {}, mute_until = {} WHERE id = {}",
+            is_top, is_close, is_archived, mute_until, id
+        ))
+    }
+
+    pub fn update_notify(db: &DStorage, id: &i64, notify: &SessionNotify) -> Result<usize> {
         db.update(&format!(
-            "UPDATE sessions SET is_top = {}, is_close = {} WHERE id = {}",
-            is_top, is_close, id
+            "UPDATE sessions SET notify = {} WHERE id = {}",
+            notify.to_int(),
+            id
         ))
     }
 
@@ -232,25 +349,36 @@ impl Session {
         }
     }
 
+    /// update the last message, the readed and unread follow the session's notification rule.
     pub fn last(
         db: &DStorage,
         fid: &i64,
         s_type: &SessionType,
         datetime: &i64,
         content: &str,
-        readed: bool,
-    ) -> Result<i64> {
+        is_me: bool,
+        mentioned: bool,
+    ) -> Result<Session> {
         let sql = format!(
-            "SELECT id from sessions WHERE fid = {} AND s_type = {}",
+            "SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread FROM sessions WHERE fid = {} AND s_type = {}",
             fid,
             s_type.to_int()
         );
         let mut matrix = db.query(&sql)?;
 
-        if let Some(mut values) = matrix.pop() {
-            let id = values.pop().unwrap().as_i64();
-            db.update(&format!("UPDATE sessions SET is_close = false, last_datetime = {}, last_content = '{}', last_readed = {} WHERE id = {}", datetime, content, if readed { 1 } else { 0 }, id))?;
-            Ok(id)
+        if let Some(values) = matrix.pop() {
+            let mut session = Session::from_values(values);
+            let readed = !session.need_notify(is_me, mentioned);
+            session.is_close = false;
+            session.last_datetime = *datetime;
+            session.last_content = content.to_owned();
+            session.last_readed = readed;
+            if !readed {
+                session.unread += 1;
+            }
+
+            db.update(&format!("UPDATE sessions SET is_close = false, last_datetime = {}, last_content = '{}', last_readed = {}, unread = {} WHERE id = {}", datetime, content, if readed { 1 } else { 0 }, session.unread, session.id))?;
+            Ok(session)
         } else {
             Err(anyhow!("session missing"))
         }
@@ -258,7 +386,7 @@ impl Session {
 
     pub fn readed(db: &DStorage, id: &i64) -> Result<usize> {
         db.update(&format!(
-            "UPDATE sessions SET last_readed = 1 WHERE id = {}",
+            "UPDATE sessions SET last_readed = 1, unread = 0 WHERE id = {}",
             id
         ))
     }
@@ -274,7 +402,7 @@ pub(crate) fn connect_session(
 ) -> Result<Option<Session>> {
     let db = session_db(base, mgid)?;
 
-    let sql = format!("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed FROM sessions WHERE s_type = {} AND fid = {}", s_type.to_int(), fid);
+    let sql = format!("SELECT id, fid, gid, addr, s_type, name, is_top, is_close, last_datetime, last_content, last_readed, notify, is_archived, mute_until, unread FROM sessions WHERE s_type = {} AND fid = {}", s_type.to_int(), fid);
 
     let mut matrix = db.query(&sql)?;
     if matrix.len() > 0 {
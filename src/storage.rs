@@ -1,7 +1,9 @@
 use image::{load_from_memory, DynamicImage, GenericImageView};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use std::future::Future;
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
 use tdn::types::{group::GroupId, primitive::Result};
@@ -12,6 +14,9 @@ use crate::migrate::{
     GROUP_DB, JARVIS_DB, SERVICE_DB, SESSION_DB, WALLET_DB,
 };
 
+/// the spawned file writes which not finished.
+static PENDING_WRITES: AtomicUsize = AtomicUsize::new(0);
+
 const FILES_DIR: &'static str = "files";
 const IMAGE_DIR: &'static str = "images";
 const THUMB_DIR: &'static str = "thumbs";
@@ -57,6 +62,27 @@ pub(crate) async fn read_file(base: &PathBuf) -> Result<Vec<u8>> {
     Ok(fs::read(base).await?)
 }
 
+/// spawn the file write task, and track it until finished.
+fn spawn_write<F: Future<Output = ()> + Send + 'static>(task: F) {
+    PENDING_WRITES.fetch_add(1, Ordering::SeqCst);
+    tokio::spawn(async move {
+        task.await;
+        PENDING_WRITES.fetch_sub(1, Ordering::SeqCst);
+    });
+}
+
+/// waiting all spawned file writes finished, return false if timeout.
+pub(crate) async fn flush_writes(timeout: Duration) -> bool {
+    let start = Instant::now();
+    while PENDING_WRITES.load(Ordering::SeqCst) > 0 {
+        if start.elapsed() > timeout {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    true
+}
+
 pub(crate) async fn copy_file(
     target: &PathBuf,
     base: &PathBuf,
@@ -95,7 +121,9 @@ pub(crate) fn write_file_sync(
     path.push(gid.to_hex());
     path.push(FILES_DIR);
     path.push(name);
-    tokio::spawn(async move { fs::write(path, bytes).await });
+    spawn_write(async move {
+        let _ = fs::write(path, bytes).await;
+    });
 
     Ok(name.to_owned())
 }
@@ -157,13 +185,15 @@ pub(crate) fn write_image_sync(base: &PathBuf, gid: &GroupId, bytes: Vec<u8>) ->
     let mut thumb_path = path.clone();
     thumb_path.push(THUMB_DIR);
     thumb_path.push(name.clone());
-    tokio::spawn(async move {
+    spawn_write(async move {
         let _ = thumb.save(thumb_path);
     });
 
     path.push(IMAGE_DIR);
     path.push(name.clone());
-    tokio::spawn(async move { fs::write(path, bytes).await });
+    spawn_write(async move {
+        let _ = fs::write(path, bytes).await;
+    });
 
     Ok(name)
 }
@@ -178,7 +208,7 @@ pub(crate) async fn write_image(base: &PathBuf, gid: &GroupId, bytes: &[u8]) ->
     let mut thumb_path = path.clone();
     thumb_path.push(THUMB_DIR);
     thumb_path.push(name.clone());
-    tokio::spawn(async move {
+    spawn_write(async move {
         let _ = thumb.save(thumb_path);
     });
 
@@ -253,7 +283,9 @@ pub(crate) fn write_avatar_sync(
     path.push(gid.to_hex());
     path.push(AVATAR_DIR);
     path.push(avatar_png(remote));
-    tokio::spawn(async move { fs::write(path, bytes).await });
+    spawn_write(async move {
+        let _ = fs::write(path, bytes).await;
+    });
     Ok(())
 }
 
@@ -308,7 +340,9 @@ pub(crate) fn write_record_sync(
     path.push(gid.to_hex());
     path.push(RECORD_DIR);
     path.push(format!("{}.m4a", datetime));
-    tokio::spawn(async move { fs::write(path, bytes).await });
+    spawn_write(async move {
+        let _ = fs::write(path, bytes).await;
+    });
 
     Ok(format!("{}_{}.m4a", t, datetime))
 }
@@ -423,6 +457,39 @@ pub(crate) fn cloud_db(base: &PathBuf, gid: &GroupId) -> Result<DStorage> {
     DStorage::open(db_path)
 }
 
+/// overwrite all files with zero, and remove them. (best effort in SSD/COW file system)
+fn wipe_dir_sync(path: &PathBuf) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        if entry_path.is_dir() {
+            wipe_dir_sync(&entry_path)?;
+        } else {
+            let mut len = std::fs::metadata(&entry_path)?.len() as usize;
+            let mut file = std::fs::OpenOptions::new().write(true).open(&entry_path)?;
+            let zeros = [0u8; 4096];
+            while len > 0 {
+                let size = std::cmp::min(len, zeros.len());
+                std::io::Write::write_all(&mut file, &zeros[..size])?;
+                len -= size;
+            }
+            file.sync_all()?;
+            drop(file);
+            std::fs::remove_file(&entry_path)?;
+        }
+    }
+    std::fs::remove_dir(path)
+}
+
+/// securely wipe account's all databases and media files.
+pub(crate) async fn account_wipe(base: &PathBuf, gid: &GroupId) -> Result<()> {
+    let mut path = base.clone();
+    path.push(gid.to_hex());
+    if path.exists() {
+        tokio::task::spawn_blocking(move || wipe_dir_sync(&path)).await??;
+    }
+    Ok(())
+}
+
 /// account independent db and storage directory.
 pub(crate) async fn account_init(base: &PathBuf, gid: &GroupId) -> Result<()> {
     let mut db_path = base.clone();
@@ -1,3 +1,4 @@
 pub(crate) mod answer;
 pub(crate) mod crypto;
 pub(crate) mod device_status;
+pub(crate) mod shamir;
@@ -2,7 +2,11 @@ use aes_gcm::{
     aead::{generic_array::GenericArray, Aead, NewAead},
     Aes256Gcm,
 };
+use rand::Rng;
 use sha2::{Digest, Sha256};
+use tdn::types::group::GroupId;
+
+const SAFETY_PREFIX: &str = "esse-safety";
 
 const FIX_PADDING: [u8; 19] = [
     69, 83, 83, 69, 70, 111, 114, 68, 97, 116, 97, 83, 101, 99, 117, 114, 105, 116, 121,
@@ -191,3 +195,103 @@ pub fn _decrypt_multiple(
     }
     Ok(pbytes)
 }
+
+/// encrypted bytes with the key, random nonce in the head.
+pub fn encrypt_with_key(key: &[u8], ptext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let cipher = build_keycipher(key);
+    let nonce_bytes = rand::thread_rng().gen::<[u8; 12]>(); // 96-bit key.
+    let nonce = GenericArray::from_slice(&nonce_bytes);
+    let mut ctext = cipher
+        .encrypt(nonce, ptext)
+        .or(Err(anyhow!("encrypt data failure.")))?;
+    let mut bytes = nonce_bytes.to_vec();
+    bytes.append(&mut ctext);
+    Ok(bytes)
+}
+
+/// decrypted bytes with the key, random nonce in the head.
+pub fn decrypt_with_key(key: &[u8], ctext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if ctext.len() < 12 {
+        return Err(anyhow!("decrypt data failure."));
+    }
+    let cipher = build_keycipher(key);
+    let nonce = GenericArray::from_slice(&ctext[0..12]);
+    cipher
+        .decrypt(nonce, &ctext[12..])
+        .or(Err(anyhow!("decrypt data failure.")))
+}
+
+/// the public key of the secret, which can seal the data to the secret.
+pub fn seal_public(secret: &[u8; 32]) -> [u8; 32] {
+    let secret = x25519_dalek::StaticSecret::from(*secret);
+    *x25519_dalek::PublicKey::from(&secret).as_bytes()
+}
+
+fn seal_key(secret: &x25519_dalek::StaticSecret, public: &[u8; 32]) -> [u8; 32] {
+    let shared = secret.diffie_hellman(&x25519_dalek::PublicKey::from(*public));
+    blake3::derive_key("esse seal", shared.as_bytes())
+}
+
+/// encrypted bytes to the public key, only the secret can decrypt it,
+/// the ephemeral public key in the head.
+pub fn seal(public: &[u8; 32], ptext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let ephemeral = x25519_dalek::StaticSecret::from(rand::thread_rng().gen::<[u8; 32]>());
+    let mut bytes = seal_public(&ephemeral.to_bytes()).to_vec();
+    bytes.append(&mut encrypt_with_key(&seal_key(&ephemeral, public), ptext)?);
+    Ok(bytes)
+}
+
+/// decrypted bytes which sealed to the secret's public key.
+pub fn open(secret: &[u8; 32], ctext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if ctext.len() < 32 {
+        return Err(anyhow!("decrypt data failure."));
+    }
+    let mut public = [0u8; 32];
+    public.copy_from_slice(&ctext[0..32]);
+    let secret = x25519_dalek::StaticSecret::from(*secret);
+    decrypt_with_key(&seal_key(&secret, &public), &ctext[32..])
+}
+
+/// both identities's hasher, same result in both sides.
+fn safety_hasher(a: &GroupId, b: &GroupId) -> blake3::Hasher {
+    let (first, second) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(SAFETY_PREFIX.as_bytes());
+    hasher.update(&first.0);
+    hasher.update(&second.0);
+    hasher
+}
+
+/// safety number of two identities, 12 groups of 5 digits.
+pub fn safety_number(a: &GroupId, b: &GroupId) -> String {
+    let mut bytes = [0u8; 60];
+    safety_hasher(a, b).finalize_xof().fill(&mut bytes);
+
+    let mut groups = vec![];
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 8];
+        buf[..5].copy_from_slice(chunk);
+        groups.push(format!("{:05}", u64::from_le_bytes(buf) % 100000));
+    }
+    groups.join(" ")
+}
+
+/// safety QR payload, show to the friend: prefix:my_gid:fingerprint.
+pub fn safety_qr(me: &GroupId, remote: &GroupId) -> String {
+    let fingerprint = safety_hasher(me, remote).finalize();
+    format!("{}:{}:{}", SAFETY_PREFIX, me.to_hex(), fingerprint.to_hex())
+}
+
+/// check the scanned safety QR payload, return the friend's gid.
+pub fn check_safety_qr(me: &GroupId, qr: &str) -> anyhow::Result<GroupId> {
+    let parts: Vec<&str> = qr.trim().split(':').collect();
+    if parts.len() != 3 || parts[0] != SAFETY_PREFIX {
+        return Err(anyhow!("safety code invalid."));
+    }
+    let remote = GroupId::from_hex(parts[1]).or(Err(anyhow!("safety code invalid.")))?;
+    let fingerprint = safety_hasher(me, &remote).finalize();
+    if fingerprint.to_hex().as_str() != parts[2] {
+        return Err(anyhow!("safety code not matching."));
+    }
+    Ok(remote)
+}
@@ -0,0 +1,158 @@
+use rand::Rng;
+
+/// multiply in GF(2^8), AES polynomial x^8 + x^4 + x^3 + x + 1.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut p = 0u8;
+    while b > 0 {
+        if b & 1 == 1 {
+            p ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry > 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    p
+}
+
+/// inverse in GF(2^8), a^254.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// split secret to n shares, any threshold shares can combine it.
+/// every share is: [x, y0, y1, ...].
+pub fn split(secret: &[u8], threshold: u8, n: u8) -> anyhow::Result<Vec<Vec<u8>>> {
+    if threshold == 0 || threshold > n {
+        return Err(anyhow!("shares threshold invalid."));
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut shares: Vec<Vec<u8>> = (1..=n).map(|x| vec![x]).collect();
+    for byte in secret {
+        // random polynomial, coefficient[0] is the secret byte.
+        let mut coeffs = vec![*byte];
+        for _ in 1..threshold {
+            coeffs.push(rng.gen::<u8>());
+        }
+
+        for share in shares.iter_mut() {
+            let x = share[0];
+            let mut y = 0u8;
+            for c in coeffs.iter().rev() {
+                y = gf_mul(y, x) ^ c;
+            }
+            share.push(y);
+        }
+    }
+
+    Ok(shares)
+}
+
+/// combine shares (Lagrange interpolation at 0).
+pub fn combine(shares: &[Vec<u8>]) -> anyhow::Result<Vec<u8>> {
+    if shares.len() == 0 {
+        return Err(anyhow!("shares is empty."));
+    }
+    let len = shares[0].len();
+    for (i, share) in shares.iter().enumerate() {
+        if share.len() != len || share[0] == 0 {
+            return Err(anyhow!("shares invalid."));
+        }
+        if shares[..i].iter().any(|s| s[0] == share[0]) {
+            return Err(anyhow!("shares duplicated."));
+        }
+    }
+
+    let mut secret = vec![];
+    for index in 1..len {
+        let mut value = 0u8;
+        for (i, si) in shares.iter().enumerate() {
+            let mut basis = 1u8;
+            for (j, sj) in shares.iter().enumerate() {
+                if i != j {
+                    // x_j / (x_j - x_i), sub is xor in GF(2^8).
+                    basis = gf_mul(basis, gf_mul(sj[0], gf_inv(sj[0] ^ si[0])));
+                }
+            }
+            value ^= gf_mul(si[index], basis);
+        }
+        secret.push(value);
+    }
+
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] =
+        b"abandon ability able about above absent absorb abstract absurd abuse access accident";
+
+    #[test]
+    fn gf_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn split_combine() {
+        let shares = split(SECRET, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        for share in &shares {
+            assert_eq!(share.len(), SECRET.len() + 1);
+        }
+
+        // any threshold shares can combine.
+        for i in 0..5 {
+            for j in (i + 1)..5 {
+                for k in (j + 1)..5 {
+                    let picked = vec![shares[i].clone(), shares[j].clone(), shares[k].clone()];
+                    assert_eq!(combine(&picked).unwrap(), SECRET);
+                }
+            }
+        }
+        assert_eq!(combine(&shares).unwrap(), SECRET);
+
+        let single = split(SECRET, 1, 1).unwrap();
+        assert_eq!(combine(&single).unwrap(), SECRET);
+    }
+
+    #[test]
+    fn below_threshold() {
+        let shares = split(SECRET, 3, 5).unwrap();
+        for i in 0..5 {
+            for j in (i + 1)..5 {
+                let picked = vec![shares[i].clone(), shares[j].clone()];
+                assert_ne!(combine(&picked).unwrap(), SECRET);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(split(SECRET, 0, 3).is_err());
+        assert!(split(SECRET, 4, 3).is_err());
+
+        let shares = split(SECRET, 2, 3).unwrap();
+        assert!(combine(&[]).is_err());
+        assert!(combine(&[shares[0].clone(), shares[0].clone()]).is_err());
+        let mut short = shares[1].clone();
+        short.pop();
+        assert!(combine(&[shares[0].clone(), short]).is_err());
+    }
+}